@@ -0,0 +1,65 @@
+//! Timings of the key comparisons behind attribute lookups, run with `cargo bench`.
+//!
+//! Compares the `KeyMatch::Exact` mode used by the lookups with a plain `==`, and times the
+//! parse and lookups of a graph whose records have many attributes.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use gml_parser::{util::KeyMatch, Graph, ReadableGMLAttributes};
+
+// The fastest of a few runs of `f`, each repeating it `iterations` times
+fn time<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(f());
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or(Duration::ZERO);
+    println!("{:<32} {:>12.1?} per iteration", name, best / iterations);
+}
+
+fn main() {
+    let keys: Vec<String> = (0..32).map(|i| format!("attribute_{}", i)).collect();
+    let needle = "attribute_31";
+    time("== over 32 keys", 100_000, || {
+        black_box(&keys)
+            .iter()
+            .position(|key| key == black_box(needle))
+    });
+    time("KeyMatch::Exact over 32 keys", 100_000, || {
+        black_box(&keys)
+            .iter()
+            .position(|key| KeyMatch::Exact.matches(key, black_box(needle)))
+    });
+    time("KeyMatch::IgnoreAsciiCase", 100_000, || {
+        black_box(&keys)
+            .iter()
+            .position(|key| KeyMatch::IgnoreAsciiCase.matches(key, black_box(needle)))
+    });
+
+    let mut text = String::from("graph [\n");
+    for id in 0..2_000 {
+        text.push_str(&format!("node [\nid {}\n", id));
+        for key in &keys {
+            text.push_str(&format!("{} {}\n", key, id));
+        }
+        text.push_str(&format!("label \"{}\"\n]\n", id));
+    }
+    text.push_str("]\n");
+    time("parse 2000 nodes", 1, || Graph::from_str(&text).unwrap());
+    let graph = Graph::from_str(&text).unwrap();
+    time("get_attribute on 2000 nodes", 100, || {
+        graph
+            .nodes
+            .iter()
+            .filter(|node| node.get_attribute(needle).is_some())
+            .count()
+    });
+}
@@ -0,0 +1,44 @@
+//! Timings of [ParseOptions::skip_keys] on a graph with large `graphics` blocks, run with
+//! `cargo bench --bench skip_keys`.
+//!
+//! The skipped values are still matched by the grammar, so both take about as long. The
+//! gain is in the memory the values would take.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use gml_parser::{Graph, ParseOptions};
+
+// The fastest of a few runs of `f`
+fn time<T>(name: &str, mut f: impl FnMut() -> T) {
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or(Duration::ZERO);
+    println!("{:<32} {:>12.1?}", name, best);
+}
+
+fn main() {
+    let mut text = String::from("graph [\n");
+    for id in 0..1_000 {
+        text.push_str(&format!("node [\nid {}\ngraphics [\n", id));
+        for point in 0..100 {
+            text.push_str(&format!("point [\nx {}.5\ny {}.5\n]\n", point, id));
+        }
+        text.push_str("]\n]\n");
+    }
+    text.push_str("]\n");
+    let skip = ParseOptions::default().skip_key("graphics");
+    time("parse 1000 graphics nodes", || {
+        Graph::from_str(&text).unwrap()
+    });
+    time("skip_keys graphics", || {
+        Graph::from_str_with(&text, &skip).unwrap()
+    });
+}
@@ -0,0 +1,1131 @@
+//! Graph algorithms operating on the nodes and edges of a [Graph].
+//!
+//! Edges that reference a node id which is not present in [Graph::nodes] are ignored.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use crate::{Edge, Error, GMLValue, Graph, HasGMLAttributes, ReadableGMLAttributes, Result};
+
+/// How the edges of a [Graph::bipartite_projection] are weighted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectionWeight {
+    /// The number of distinct neighbors the two nodes share, stored as an int `weight`
+    SharedNeighbors,
+    /// For every shared neighbor, the weights of the edges from both nodes to it added up and
+    /// stored as a float `weight`. The weight of an edge is read from the named attribute,
+    /// counting 1 when it is missing or not numeric.
+    SumWeights(String),
+}
+
+/// Which edges count towards the degree of a node, see [Graph::degrees_by].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DegreeMode {
+    /// Every edge touching the node, with self loops counted twice
+    #[default]
+    Undirected,
+    /// Edges whose source is the node
+    Out,
+    /// Edges whose target is the node
+    In,
+}
+
+/// How the direction of each edge is decided by [Graph::neighbors_with] and the other
+/// `_with` queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectednessPolicy {
+    /// Every edge is directed when [Graph::directed] is `Some(true)`, as in the queries
+    /// without a policy
+    #[default]
+    Graph,
+    /// The `directed` flag of an edge, see [Edge::directed], overrides the graph-level one,
+    /// for mixed graphs
+    PerEdge,
+}
+
+impl Graph {
+    /// Whether `edge` is directed under `policy`.
+    pub fn is_edge_directed(&self, edge: &Edge, policy: DirectednessPolicy) -> bool {
+        let per_edge = match policy {
+            DirectednessPolicy::Graph => None,
+            DirectednessPolicy::PerEdge => edge.directed(),
+        };
+        per_edge.unwrap_or(self.directed == Some(true))
+    }
+    // Maps every node id to its position in self.nodes
+    fn node_indices(&self) -> HashMap<i64, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect()
+    }
+    // (source, target) index pairs of every edge whose endpoints exist
+    fn edge_indices<'a>(
+        &'a self,
+        indices: &'a HashMap<i64, usize>,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.edges
+            .iter()
+            .filter_map(|edge| Some((*indices.get(&edge.source)?, *indices.get(&edge.target)?)))
+    }
+    // Neighbors of every node index, ignoring the direction of the edges
+    fn undirected_adjacency(&self, indices: &HashMap<i64, usize>) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for (source, target) in self.edge_indices(indices) {
+            adjacency[source].push(target);
+            if source != target {
+                adjacency[target].push(source);
+            }
+        }
+        adjacency
+    }
+    /// Whether there is at least one edge from `source` to `target`. Unless [Graph::directed]
+    /// is `Some(true)` an edge from `target` to `source` counts as well.
+    pub fn has_edge(&self, source: i64, target: i64) -> bool {
+        self.has_edge_with(source, target, DirectednessPolicy::Graph)
+    }
+    /// Like [Graph::has_edge], deciding the direction of every edge with `policy`.
+    pub fn has_edge_with(&self, source: i64, target: i64, policy: DirectednessPolicy) -> bool {
+        self.edges
+            .iter()
+            .any(|edge| edge.connects(source, target, self.is_edge_directed(edge, policy)))
+    }
+    /// The ids of the nodes an edge leads to from `id`, sorted and without repeats: the
+    /// targets of its edges, and the sources of the undirected ones.
+    /// A node with a self loop is its own neighbor.
+    pub fn neighbors(&self, id: i64) -> Vec<i64> {
+        self.neighbors_with(id, DirectednessPolicy::Graph)
+    }
+    /// Like [Graph::neighbors], deciding the direction of every edge with `policy`.
+    pub fn neighbors_with(&self, id: i64, policy: DirectednessPolicy) -> Vec<i64> {
+        self.neighbors_where(id, &self.node_indices(), |edge| {
+            self.is_edge_directed(edge, policy)
+        })
+    }
+    /// The ids of the nodes that are neighbors of both `a` and `b`, sorted, ignoring the
+    /// direction of the edges. A node with a self loop is its own neighbor.
+    pub fn common_neighbors(&self, a: i64, b: i64) -> Vec<i64> {
+        let ids = self.node_indices();
+        let (a, b) = (
+            self.neighbors_where(a, &ids, |_| false),
+            self.neighbors_where(b, &ids, |_| false),
+        );
+        a.into_iter()
+            .filter(|id| b.binary_search(id).is_ok())
+            .collect()
+    }
+    /// The Jaccard similarity of the neighbors of `a` and `b`: the number of
+    /// [common neighbors](Graph::common_neighbors) divided by the number of nodes that are a
+    /// neighbor of either, ignoring the direction of the edges.
+    ///
+    /// Returns 0 when neither node has a neighbor, including when they don't exist.
+    pub fn jaccard(&self, a: i64, b: i64) -> f64 {
+        let ids = self.node_indices();
+        let (a, b) = (
+            self.neighbors_where(a, &ids, |_| false),
+            self.neighbors_where(b, &ids, |_| false),
+        );
+        let common = a.iter().filter(|id| b.binary_search(id).is_ok()).count();
+        let union = a.len() + b.len() - common;
+        if union == 0 {
+            0.0
+        } else {
+            common as f64 / union as f64
+        }
+    }
+    // Sorted ids of the nodes an edge leads to from `id`, following the edges for which
+    // `directed` is false in both directions. Edges to nodes missing from `ids` are skipped.
+    fn neighbors_where(
+        &self,
+        id: i64,
+        ids: &HashMap<i64, usize>,
+        directed: impl Fn(&Edge) -> bool,
+    ) -> Vec<i64> {
+        let mut neighbors: Vec<i64> = self
+            .edges
+            .iter()
+            .filter(|edge| ids.contains_key(&edge.source) && ids.contains_key(&edge.target))
+            .filter_map(|edge| {
+                if edge.source == id {
+                    Some(edge.target)
+                } else if edge.target == id && !directed(edge) {
+                    Some(edge.source)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+    /// The number of edges from `source` to `target`, see [Graph::has_edge].
+    /// A self loop is counted once.
+    pub fn edge_count_between(&self, source: i64, target: i64) -> usize {
+        let directed = self.directed == Some(true);
+        self.edges
+            .iter()
+            .filter(|edge| edge.connects(source, target, directed))
+            .count()
+    }
+    /// The degree of every node id.
+    ///
+    /// When [Graph::directed] is `Some(true)` this is the number of outgoing edges. Otherwise
+    /// it is the number of edges touching the node, with self loops counted twice.
+    pub fn degrees(&self) -> HashMap<i64, usize> {
+        self.degrees_with(DirectednessPolicy::Graph)
+    }
+    /// Like [Graph::degrees], deciding the direction of every edge with `policy`: directed
+    /// edges count for their source and undirected ones for both endpoints.
+    pub fn degrees_with(&self, policy: DirectednessPolicy) -> HashMap<i64, usize> {
+        self.count_degrees(|edge| (true, !self.is_edge_directed(edge, policy)))
+    }
+    /// The degree of every node id, counting the edges selected by `mode` regardless of
+    /// [Graph::directed].
+    pub fn degrees_by(&self, mode: DegreeMode) -> HashMap<i64, usize> {
+        self.count_degrees(|_| (mode != DegreeMode::In, mode != DegreeMode::Out))
+    }
+    // Degrees where `ends` tells whether an edge counts for its source and for its target.
+    // Edges to missing nodes are skipped.
+    fn count_degrees(&self, ends: impl Fn(&Edge) -> (bool, bool)) -> HashMap<i64, usize> {
+        let mut degrees: HashMap<i64, usize> = self.nodes.iter().map(|node| (node.id, 0)).collect();
+        for edge in &self.edges {
+            if !degrees.contains_key(&edge.source) || !degrees.contains_key(&edge.target) {
+                continue;
+            }
+            let (source, target) = ends(edge);
+            if source {
+                *degrees.get_mut(&edge.source).unwrap() += 1;
+            }
+            if target {
+                *degrees.get_mut(&edge.target).unwrap() += 1;
+            }
+        }
+        degrees
+    }
+    /// The degree of the node `id`, see [Graph::degrees]. None if there is no such node.
+    pub fn degree(&self, id: i64) -> Option<usize> {
+        self.degree_with(id, DirectednessPolicy::Graph)
+    }
+    /// The degree of the node `id`, see [Graph::degrees_with]. None if there is no such node.
+    pub fn degree_with(&self, id: i64, policy: DirectednessPolicy) -> Option<usize> {
+        self.nodes.iter().any(|node| node.id == id).then(|| {
+            let ids = self.node_indices();
+            self.edges
+                .iter()
+                .filter(|edge| ids.contains_key(&edge.source) && ids.contains_key(&edge.target))
+                .map(|edge| {
+                    let directed = self.is_edge_directed(edge, policy);
+                    (edge.source == id) as usize + (!directed && edge.target == id) as usize
+                })
+                .sum()
+        })
+    }
+    /// The mean of [Graph::degrees], 0 for a graph without nodes.
+    pub fn average_degree(&self) -> f64 {
+        mean_degree(&self.degrees())
+    }
+    /// How many nodes have each degree, see [Graph::degrees]. This is
+    /// [Graph::degree_histogram] unless [Graph::directed] is `Some(true)`.
+    pub fn degree_distribution(&self) -> BTreeMap<usize, usize> {
+        histogram(self.degrees())
+    }
+    /// How many nodes have each degree, ignoring the direction of the edges.
+    /// See [Graph::degree_histogram_by] to count only outgoing or incoming edges.
+    pub fn degree_histogram(&self) -> BTreeMap<usize, usize> {
+        self.degree_histogram_by(DegreeMode::Undirected)
+    }
+    /// How many nodes have each degree, see [Graph::degrees_by].
+    pub fn degree_histogram_by(&self, mode: DegreeMode) -> BTreeMap<usize, usize> {
+        histogram(self.degrees_by(mode))
+    }
+    /// Ids of the nodes no edge touches, in declaration order.
+    /// A node with only a self loop is not isolated.
+    pub fn isolated_nodes(&self) -> Vec<i64> {
+        self.isolated_nodes_by(DegreeMode::Undirected)
+    }
+    /// Ids of the nodes with a degree of zero, see [Graph::degrees_by], in declaration order.
+    pub fn isolated_nodes_by(&self, mode: DegreeMode) -> Vec<i64> {
+        let mut degrees = self.degrees_by(mode);
+        self.nodes
+            .iter()
+            .filter(|node| degrees.remove(&node.id) == Some(0))
+            .map(|node| node.id)
+            .collect()
+    }
+    /// Remove the nodes returned by [Graph::isolated_nodes] and return how many there were.
+    pub fn remove_isolated(&mut self) -> usize {
+        let isolated: HashSet<i64> = self.isolated_nodes().into_iter().collect();
+        let before = self.nodes.len();
+        self.retain_nodes(|node| !isolated.contains(&node.id));
+        before - self.nodes.len()
+    }
+    /// Guess whether the edges are directed, for files without a `directed` key.
+    ///
+    /// The heuristic looks at the edges between two different nodes. If an edge from `a` to
+    /// `b` and one from `b` to `a` both exist, the file likely lists undirected edges in both
+    /// directions and this returns false. Otherwise it returns true as long as there is at
+    /// least one such edge. Graphs with only self loops or no edges return false.
+    ///
+    /// Undirected graphs that list every edge once can't be told apart from directed ones
+    /// and are reported as directed. [Graph::directed] is not looked at.
+    pub fn infer_directed(&self) -> bool {
+        let pairs: HashSet<(i64, i64)> = self
+            .edges
+            .iter()
+            .filter(|edge| edge.source != edge.target)
+            .map(|edge| (edge.source, edge.target))
+            .collect();
+        !pairs.is_empty()
+            && pairs
+                .iter()
+                .all(|&(source, target)| !pairs.contains(&(target, source)))
+    }
+    /// Pairs of different nodes that have edges in both orientations, which likely means an
+    /// undirected edge was listed twice and would be counted twice by undirected algorithms.
+    ///
+    /// Each pair is reported once, in the orientation of its first edge, in the order the
+    /// reversed edges appear. Repeated edges with the same orientation are not reported.
+    /// Always empty for directed graphs, where `directed` is `Some(true)`.
+    pub fn check_undirected_duplicates(&self) -> Vec<(i64, i64)> {
+        if self.directed == Some(true) {
+            return Vec::new();
+        }
+        // The first edge between each pair of nodes, the smaller id first
+        let mut first: HashMap<(i64, i64), &Edge> = HashMap::new();
+        let mut reported = HashSet::new();
+        let mut duplicates = Vec::new();
+        for edge in self.edges.iter().filter(|edge| edge.source != edge.target) {
+            let pair = (edge.source.min(edge.target), edge.source.max(edge.target));
+            match first.get(&pair) {
+                None => {
+                    first.insert(pair, edge);
+                }
+                Some(earlier) => {
+                    if !earlier.connects(edge.source, edge.target, true) && reported.insert(pair) {
+                        duplicates.push((earlier.source, earlier.target));
+                    }
+                }
+            }
+        }
+        duplicates
+    }
+    /// Split the nodes into two sides such that every edge connects the two sides,
+    /// ignoring the direction of the edges.
+    ///
+    /// Returns None if the graph contains an odd cycle (including self loops).
+    /// Within each connected component, the first declared node is put on the first side.
+    /// Both sides are sorted by node id.
+    pub fn bipartite(&self) -> Option<(Vec<i64>, Vec<i64>)> {
+        let indices = self.node_indices();
+        let adjacency = self.undirected_adjacency(&indices);
+        let mut side: Vec<Option<bool>> = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for start in 0..self.nodes.len() {
+            if side[start].is_some() {
+                continue;
+            }
+            side[start] = Some(false);
+            queue.push_back(start);
+            while let Some(v) = queue.pop_front() {
+                let color = side[v]?;
+                for &w in &adjacency[v] {
+                    match side[w] {
+                        None => {
+                            side[w] = Some(!color);
+                            queue.push_back(w);
+                        }
+                        Some(other) if other == color => return None,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for (node, side) in self.nodes.iter().zip(side) {
+            if side == Some(false) {
+                left.push(node.id);
+            } else {
+                right.push(node.id);
+            }
+        }
+        left.sort_unstable();
+        right.sort_unstable();
+        Some((left, right))
+    }
+    /// The one-mode projection onto the nodes whose `partition_attr` equals `keep_value`.
+    ///
+    /// Two kept nodes are connected iff they share a neighbor in the other partition, and the
+    /// edge is weighted according to `weight_mode`. The result is undirected, contains the kept
+    /// nodes in their original order and its edges sorted by endpoints with `source < target`.
+    ///
+    /// Errors if an edge doesn't connect a kept node with a node of the other partition.
+    pub fn bipartite_projection(
+        &self,
+        partition_attr: &str,
+        keep_value: &GMLValue,
+        weight_mode: ProjectionWeight,
+    ) -> Result<Graph> {
+        let is_kept: HashMap<i64, bool> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let value = node.get_attribute(partition_attr).map(|(_, v)| v);
+                (node.id, value == Some(keep_value))
+            })
+            .collect();
+        // For every node of the other partition, the kept nodes connected to it
+        // along with the summed weight of the edges between them
+        let mut others: HashMap<i64, BTreeMap<i64, f64>> = HashMap::new();
+        for edge in &self.edges {
+            let (Some(&source_kept), Some(&target_kept)) =
+                (is_kept.get(&edge.source), is_kept.get(&edge.target))
+            else {
+                continue;
+            };
+            if source_kept == target_kept {
+                return Err(Error::NotBipartite {
+                    source: edge.source,
+                    target: edge.target,
+                });
+            }
+            let (kept, other) = if source_kept {
+                (edge.source, edge.target)
+            } else {
+                (edge.target, edge.source)
+            };
+            let weight = match &weight_mode {
+                ProjectionWeight::SharedNeighbors => 0.0,
+                ProjectionWeight::SumWeights(attr) => edge.attr_f64(attr).unwrap_or(1.0),
+            };
+            *others.entry(other).or_default().entry(kept).or_default() += weight;
+        }
+
+        let mut projected: BTreeMap<(i64, i64), (i64, f64)> = BTreeMap::new();
+        for neighbors in others.values() {
+            let neighbors: Vec<(&i64, &f64)> = neighbors.iter().collect();
+            for (i, (a, wa)) in neighbors.iter().enumerate() {
+                for (b, wb) in &neighbors[i + 1..] {
+                    let entry = projected.entry((**a, **b)).or_default();
+                    entry.0 += 1;
+                    entry.1 += **wa + **wb;
+                }
+            }
+        }
+
+        let mut graph = Graph::new();
+        graph.directed = Some(false);
+        graph.nodes = self
+            .nodes
+            .iter()
+            .filter(|node| is_kept[&node.id])
+            .cloned()
+            .collect();
+        graph.edges = projected
+            .into_iter()
+            .map(|((a, b), (shared, sum))| {
+                let mut edge = Edge::new(a, b);
+                let weight = match weight_mode {
+                    ProjectionWeight::SharedNeighbors => GMLValue::GMLInt(shared),
+                    ProjectionWeight::SumWeights(_) => GMLValue::GMLFloat(sum),
+                };
+                edge.attrs.push(("weight".to_owned(), weight));
+                edge
+            })
+            .collect();
+        Ok(graph)
+    }
+    /// Components of the graph when the direction of the edges is ignored.
+    ///
+    /// Each component is sorted by node id and the components are ordered by their smallest id.
+    pub fn weakly_connected_components(&self) -> Vec<Vec<i64>> {
+        let mut sets = self.union_find();
+        let mut components: HashMap<i64, Vec<i64>> = HashMap::new();
+        for node in &self.nodes {
+            if let Some(root) = sets.find(&node.id) {
+                components.entry(root).or_default().push(node.id);
+            }
+        }
+        sorted_components(components.into_values().collect())
+    }
+    /// Split the graph into one graph per weakly connected component, largest first and then
+    /// by smallest node id.
+    ///
+    /// Every component keeps the `directed` flag, id, label, name, attributes and warnings of
+    /// the graph, and gets a `component_index` attribute with its position in the result.
+    /// Nodes and edges keep their relative order. An edge to a missing node goes with the
+    /// component of its other endpoint, and edges between two missing nodes are dropped.
+    pub fn split_components(mut self) -> Vec<Graph> {
+        let mut components = self.weakly_connected_components();
+        // Stable, so components of the same size stay ordered by smallest id
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+        let index: HashMap<i64, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, component)| component.iter().map(move |id| (*id, i)))
+            .collect();
+        let nodes = std::mem::take(&mut self.nodes);
+        let edges = std::mem::take(&mut self.edges);
+        let mut graphs: Vec<Graph> = (0..components.len())
+            .map(|i| {
+                let mut graph = self.clone();
+                graph.set_attribute("component_index", i as i64);
+                graph
+            })
+            .collect();
+        for node in nodes {
+            graphs[index[&node.id]].nodes.push(node);
+        }
+        for edge in edges {
+            let component = index.get(&edge.source).or_else(|| index.get(&edge.target));
+            if let Some(&i) = component {
+                graphs[i].edges.push(edge);
+            }
+        }
+        graphs
+    }
+    /// The node ids split into the weakly connected components of the graph, ready to answer
+    /// connectivity queries or to be extended with more edges.
+    pub fn union_find(&self) -> UnionFind<i64> {
+        let mut sets = UnionFind::new();
+        for node in &self.nodes {
+            sets.insert(node.id);
+        }
+        for edge in &self.edges {
+            if sets.indices.contains_key(&edge.source) && sets.indices.contains_key(&edge.target) {
+                sets.union(edge.source, edge.target);
+            }
+        }
+        sets
+    }
+    /// Ids of the nodes reachable from any of `sources`, including the sources themselves,
+    /// found with a single breadth-first search started from all of them at once.
+    ///
+    /// Edges are followed from source to target when [Graph::directed] is `Some(true)` and in
+    /// both directions otherwise. Sources that are not a node of the graph are ignored.
+    pub fn reachable_from(&self, sources: &[i64]) -> HashSet<i64> {
+        let indices = self.node_indices();
+        let adjacency = if self.directed == Some(true) {
+            let mut adjacency = vec![Vec::new(); self.nodes.len()];
+            for (source, target) in self.edge_indices(&indices) {
+                adjacency[source].push(target);
+            }
+            adjacency
+        } else {
+            self.undirected_adjacency(&indices)
+        };
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<usize> = sources
+            .iter()
+            .filter_map(|id| indices.get(id).copied())
+            .filter(|&i| !std::mem::replace(&mut seen[i], true))
+            .collect();
+        let mut reached = HashSet::new();
+        while let Some(v) = queue.pop_front() {
+            reached.insert(self.nodes[v].id);
+            for &w in &adjacency[v] {
+                if !seen[w] {
+                    seen[w] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+        reached
+    }
+    /// Strongly connected components computed with Tarjan's algorithm. Edges are always
+    /// followed from source to target regardless of [Graph::directed].
+    ///
+    /// Each component is sorted by node id and the components are ordered by their smallest id.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<i64>> {
+        let indices = self.node_indices();
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for (source, target) in self.edge_indices(&indices) {
+            adjacency[source].push(target);
+        }
+
+        const UNVISITED: usize = usize::MAX;
+        let mut index = vec![UNVISITED; self.nodes.len()];
+        let mut lowlink = vec![0; self.nodes.len()];
+        let mut on_stack = vec![false; self.nodes.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+        // Explicit call stack of (node, next neighbor to visit) so deep graphs can't overflow
+        let mut calls: Vec<(usize, usize)> = Vec::new();
+        for root in 0..self.nodes.len() {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            calls.push((root, 0));
+            while let Some((v, child)) = calls.pop() {
+                if child == 0 {
+                    index[v] = next_index;
+                    lowlink[v] = next_index;
+                    next_index += 1;
+                    stack.push(v);
+                    on_stack[v] = true;
+                } else {
+                    let w = adjacency[v][child - 1];
+                    lowlink[v] = lowlink[v].min(lowlink[w]);
+                }
+                let mut recursed = false;
+                for (i, &w) in adjacency[v].iter().enumerate().skip(child) {
+                    if index[w] == UNVISITED {
+                        calls.push((v, i + 1));
+                        calls.push((w, 0));
+                        recursed = true;
+                        break;
+                    } else if on_stack[w] {
+                        lowlink[v] = lowlink[v].min(index[w]);
+                    }
+                }
+                if recursed || lowlink[v] != index[v] {
+                    continue;
+                }
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(self.nodes[w].id);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+        sorted_components(components)
+    }
+}
+
+fn sorted_components(mut components: Vec<Vec<i64>>) -> Vec<Vec<i64>> {
+    for component in &mut components {
+        component.sort_unstable();
+    }
+    components.sort_unstable_by_key(|component| component[0]);
+    components
+}
+
+// The mean of `degrees`, 0 when there are none
+pub(crate) fn mean_degree(degrees: &HashMap<i64, usize>) -> f64 {
+    if degrees.is_empty() {
+        return 0.0;
+    }
+    degrees.values().sum::<usize>() as f64 / degrees.len() as f64
+}
+
+fn histogram(degrees: HashMap<i64, usize>) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for degree in degrees.into_values() {
+        *histogram.entry(degree).or_default() += 1;
+    }
+    histogram
+}
+
+impl Edge {
+    /// Whether the edge goes from `source` to `target`, or from `target` to `source` as well
+    /// unless `directed`. The endpoint matching behind [Graph::has_edge] and
+    /// [Graph::check_undirected_duplicates].
+    ///
+    /// ```
+    /// use gml_parser::Edge;
+    ///
+    /// let edge = Edge::new(1, 2);
+    /// assert!(edge.connects(2, 1, false));
+    /// assert!(!edge.connects(2, 1, true));
+    /// ```
+    pub fn connects(&self, source: i64, target: i64, directed: bool) -> bool {
+        (self.source == source && self.target == target)
+            || (!directed && self.source == target && self.target == source)
+    }
+}
+
+/// Disjoint sets of keys supporting near constant time merges and connectivity queries,
+/// using path compression and union by rank.
+///
+/// Keys are added on their first [UnionFind::insert] or [UnionFind::union] and start
+/// out in a set of their own.
+///
+/// ```
+/// use gml_parser::UnionFind;
+///
+/// let mut sets = UnionFind::new();
+/// sets.union(1, 2);
+/// sets.union(3, 4);
+/// assert!(!sets.connected(&1, &4));
+/// sets.union(2, 3);
+/// assert!(sets.connected(&1, &4));
+/// assert_eq!(sets.num_sets(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnionFind<T = i64> {
+    indices: HashMap<T, usize>,
+    keys: Vec<T>,
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    sets: usize,
+}
+
+impl<T: Hash + Eq + Clone> Default for UnionFind<T> {
+    fn default() -> Self {
+        UnionFind {
+            indices: HashMap::new(),
+            keys: Vec::new(),
+            parent: Vec::new(),
+            rank: Vec::new(),
+            sets: 0,
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> UnionFind<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add `key` in a set of its own. Returns false if it was already present.
+    pub fn insert(&mut self, key: T) -> bool {
+        if self.indices.contains_key(&key) {
+            return false;
+        }
+        self.index(key);
+        true
+    }
+    /// Merge the sets containing `a` and `b`, adding them if needed.
+    /// Returns false if they were already in the same set.
+    pub fn union(&mut self, a: T, b: T) -> bool {
+        let (a, b) = (self.index(a), self.index(b));
+        let (a, b) = (self.root(a), self.root(b));
+        if a == b {
+            return false;
+        }
+        match self.rank[a].cmp(&self.rank[b]) {
+            Ordering::Less => self.parent[a] = b,
+            Ordering::Greater => self.parent[b] = a,
+            Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+        self.sets -= 1;
+        true
+    }
+    /// The representative of the set containing `key`, or None if `key` was never added.
+    ///
+    /// Two keys are in the same set iff they have the same representative. The representative
+    /// of a set may change when it is merged with another one.
+    pub fn find(&mut self, key: &T) -> Option<T> {
+        let index = *self.indices.get(key)?;
+        let root = self.root(index);
+        Some(self.keys[root].clone())
+    }
+    /// Whether `a` and `b` are in the same set. Keys that were never added aren't
+    /// connected to anything.
+    pub fn connected(&mut self, a: &T, b: &T) -> bool {
+        match (self.indices.get(a), self.indices.get(b)) {
+            (Some(&a), Some(&b)) => self.root(a) == self.root(b),
+            _ => false,
+        }
+    }
+    /// The number of disjoint sets.
+    pub fn num_sets(&self) -> usize {
+        self.sets
+    }
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn index(&mut self, key: T) -> usize {
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+        let index = self.keys.len();
+        self.indices.insert(key.clone(), index);
+        self.keys.push(key);
+        self.parent.push(index);
+        self.rank.push(0);
+        self.sets += 1;
+        index
+    }
+    fn root(&mut self, mut x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn load(path: &str) -> Graph {
+        Graph::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn components() {
+        let graph = load("tests/components.gml");
+        assert_eq!(
+            graph.weakly_connected_components(),
+            vec![vec![1, 2, 3, 4, 5], vec![6, 7]]
+        );
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![1, 2, 3], vec![4, 5], vec![6], vec![7]]
+        );
+
+        let mut graph = load("tests/components.gml");
+        graph.label = Some("parts".into());
+        graph.set_attribute("source", "test");
+        graph.nodes.insert(0, Node::new(9));
+        graph.edges.push(Edge::new(99, 7));
+        graph.edges.push(Edge::new(99, 98));
+        let parts = graph.clone().split_components();
+        let ids = |graph: &Graph| graph.nodes.iter().map(|node| node.id).collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(ids(&parts[0]), vec![1, 2, 3, 4, 5]);
+        assert_eq!(ids(&parts[1]), vec![6, 7]);
+        assert_eq!(ids(&parts[2]), vec![9]);
+        assert_eq!(parts[0].edges, graph.edges[..6]);
+        assert_eq!(parts[1].edges, vec![Edge::new(6, 7), Edge::new(99, 7)]);
+        assert!(parts[2].edges.is_empty());
+        for (i, part) in parts.iter().enumerate() {
+            assert_eq!(part.directed, Some(true));
+            assert_eq!(part.label.as_deref(), Some("parts"));
+            assert_eq!(part.attr_str("source"), Some("test"));
+            assert_eq!(part.attr_i64("component_index"), Some(i as i64));
+        }
+        assert!(Graph::new().split_components().is_empty());
+
+        let graph = load("tests/synoptic.gml");
+        assert_eq!(graph.weakly_connected_components().len(), 1);
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![0], vec![1, 2, 3], vec![4], vec![5], vec![6]]
+        );
+    }
+
+    #[test]
+    fn bipartite() {
+        let graph = load("tests/bipartite.gml");
+        assert_eq!(graph.bipartite(), Some((vec![1, 2, 3], vec![10, 11, 12])));
+        // A triangle
+        let graph = load("tests/wikipedia.gml");
+        assert_eq!(graph.bipartite(), None);
+        // Self loop
+        let graph = load("tests/synoptic.gml");
+        assert_eq!(graph.bipartite(), None);
+    }
+
+    #[test]
+    fn bipartite_projection() {
+        let graph = load("tests/bipartite.gml");
+        let user = GMLValue::GMLString("user".into());
+        let projection = graph
+            .bipartite_projection("type", &user, ProjectionWeight::SharedNeighbors)
+            .unwrap();
+        let ids: Vec<i64> = projection.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        let edges: Vec<(i64, i64, Option<i64>)> = projection
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.attr_i64("weight")))
+            .collect();
+        assert_eq!(edges, vec![(1, 2, Some(1)), (1, 3, Some(1))]);
+
+        let projection = graph
+            .bipartite_projection("type", &user, ProjectionWeight::SumWeights("weight".into()))
+            .unwrap();
+        let edges: Vec<(i64, i64, Option<f64>)> = projection
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.attr_f64("weight")))
+            .collect();
+        assert_eq!(edges, vec![(1, 2, Some(6.0)), (1, 3, Some(5.0))]);
+
+        let item = GMLValue::GMLString("item".into());
+        let projection = graph
+            .bipartite_projection("type", &item, ProjectionWeight::SharedNeighbors)
+            .unwrap();
+        let edges: Vec<(i64, i64)> = projection
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target))
+            .collect();
+        assert_eq!(edges, vec![(10, 11), (11, 12)]);
+
+        let graph = load("tests/wikipedia.gml");
+        let err = graph
+            .bipartite_projection(
+                "thisIsASampleAttribute",
+                &GMLValue::GMLInt(42),
+                ProjectionWeight::SharedNeighbors,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::NotBipartite {
+                source: 2,
+                target: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new();
+        assert!(sets.is_empty());
+        assert_eq!(sets.find(&1), None);
+        assert!(!sets.connected(&1, &1));
+        assert!(sets.insert(1));
+        assert!(!sets.insert(1));
+        assert!(sets.connected(&1, &1));
+        assert!(sets.union(1, 2));
+        assert!(sets.union(-3, 4));
+        assert_eq!(sets.num_sets(), 2);
+        assert!(sets.connected(&2, &1));
+        assert!(!sets.connected(&2, &4));
+        assert!(sets.union(4, 5));
+        assert!(!sets.union(5, -3));
+        assert_eq!(sets.find(&5), sets.find(&-3));
+        assert!(sets.union(5, 2));
+        assert!(sets.connected(&1, &-3));
+        assert_eq!(sets.num_sets(), 1);
+        assert_eq!(sets.len(), 5);
+
+        let graph = load("tests/components.gml");
+        let mut sets = graph.union_find();
+        assert_eq!(sets.num_sets(), 2);
+        assert!(sets.connected(&1, &5));
+        assert!(!sets.connected(&1, &7));
+        assert!(!sets.connected(&1, &100));
+        sets.union(5, 7);
+        assert_eq!(sets.num_sets(), 1);
+    }
+
+    #[test]
+    fn common_neighbors_and_jaccard() {
+        let mut graph = load("tests/wikipedia.gml");
+        assert_eq!(graph.common_neighbors(1, 2), vec![3]);
+        assert_eq!(graph.common_neighbors(2, 1), vec![3]);
+        assert_eq!(graph.jaccard(1, 2), 1.0 / 3.0);
+        assert_eq!(graph.jaccard(1, 1), 1.0);
+
+        // Direction is ignored, dangling edges and missing nodes are not neighbors
+        graph.nodes.push(Node::new(4));
+        graph.edges.push(Edge::new(4, 3));
+        graph.edges.push(Edge::new(4, 99));
+        assert_eq!(graph.common_neighbors(1, 4), vec![3]);
+        assert_eq!(graph.common_neighbors(3, 4), Vec::<i64>::new());
+        assert_eq!(graph.jaccard(1, 4), 0.5);
+        assert_eq!(graph.common_neighbors(1, 99), Vec::<i64>::new());
+        assert_eq!(graph.jaccard(1, 99), 0.0);
+        graph.nodes.push(Node::new(5));
+        assert_eq!(graph.jaccard(5, 6), 0.0);
+    }
+
+    #[test]
+    fn degrees() {
+        let mut graph = load("tests/wikipedia.gml");
+        assert_eq!(graph.average_degree(), 1.0);
+        assert_eq!(graph.degree_distribution(), [(1, 3)].into());
+        assert_eq!(graph.degree(2), Some(1));
+        assert_eq!(graph.degree(9), None);
+
+        graph.directed = None;
+        graph.edges.push(Edge::new(1, 1));
+        graph.edges.push(Edge::new(1, 9));
+        assert_eq!(graph.degree(1), Some(4));
+        assert_eq!(graph.degrees()[&1], 4);
+        assert_eq!(graph.average_degree(), 8.0 / 3.0);
+        assert_eq!(graph.degree_distribution(), [(2, 2), (4, 1)].into());
+        assert_eq!(Graph::new().average_degree(), 0.0);
+    }
+
+    #[test]
+    fn degree_histogram_star() {
+        let mut graph = Graph::new();
+        graph.directed = Some(true);
+        graph.nodes.extend((0..5).map(Node::new));
+        graph.edges.extend((1..5).map(|leaf| Edge::new(0, leaf)));
+        assert_eq!(graph.degree_histogram(), [(1, 4), (4, 1)].into());
+        assert_eq!(
+            graph.degree_histogram_by(DegreeMode::Out),
+            [(0, 4), (4, 1)].into()
+        );
+        assert_eq!(
+            graph.degree_histogram_by(DegreeMode::In),
+            [(0, 1), (1, 4)].into()
+        );
+        assert!(graph.isolated_nodes().is_empty());
+        assert_eq!(graph.isolated_nodes_by(DegreeMode::Out), vec![1, 2, 3, 4]);
+        assert_eq!(graph.isolated_nodes_by(DegreeMode::In), vec![0]);
+    }
+
+    #[test]
+    fn isolated_nodes() {
+        let mut graph = load("tests/wikipedia.gml");
+        graph
+            .nodes
+            .extend([Node::new(7), Node::new(5), Node::new(6)]);
+        graph.edges.push(Edge::new(6, 6));
+        // Edges to missing nodes don't count
+        graph.edges.push(Edge::new(5, 99));
+        assert_eq!(graph.isolated_nodes(), vec![7, 5]);
+        assert_eq!(graph.degree_histogram(), [(0, 2), (2, 4)].into());
+
+        let mut graph = load("tests/isolated.gml");
+        assert_eq!(graph.isolated_nodes(), vec![3]);
+        let edges = graph.edges.clone();
+        assert_eq!(graph.remove_isolated(), 1);
+        let ids: Vec<i64> = graph.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(ids, vec![1, 2, 4]);
+        assert_eq!(graph.edges, edges);
+        assert_eq!(graph.remove_isolated(), 0);
+    }
+
+    #[test]
+    fn reachable_from() {
+        let mut graph = Graph::new();
+        graph.directed = Some(true);
+        graph.nodes.extend((1..=8).map(Node::new));
+        for (source, target) in [(1, 2), (2, 3), (4, 5), (6, 4), (3, 3), (7, 8)] {
+            graph.edges.push(Edge::new(source, target));
+        }
+        graph.edges.push(Edge::new(5, 99));
+        assert_eq!(
+            graph.reachable_from(&[1, 4, 42]),
+            [1, 2, 3, 4, 5].into_iter().collect()
+        );
+        assert_eq!(graph.reachable_from(&[8, 8]), [8].into_iter().collect());
+        assert!(graph.reachable_from(&[]).is_empty());
+
+        graph.directed = Some(false);
+        assert_eq!(
+            graph.reachable_from(&[1, 4]),
+            [1, 2, 3, 4, 5, 6].into_iter().collect()
+        );
+        assert_eq!(graph.reachable_from(&[8]), [7, 8].into_iter().collect());
+    }
+
+    #[test]
+    fn infer_directed() {
+        let mut graph = load("tests/asymmetric.gml");
+        assert_eq!(graph.directed, None);
+        assert!(graph.infer_directed());
+
+        graph.edges.push(Edge::new(2, 1));
+        assert!(!graph.infer_directed());
+        graph.retain_edges(|edge| edge.source == edge.target);
+        assert!(!graph.infer_directed());
+        assert!(!Graph::new().infer_directed());
+    }
+
+    #[test]
+    fn undirected_duplicates() {
+        let mut graph = load("tests/reversed.gml");
+        assert_eq!(graph.check_undirected_duplicates(), vec![(1, 2)]);
+        graph.edges.push(Edge::new(3, 2));
+        graph.edges.push(Edge::new(1, 2));
+        assert_eq!(graph.check_undirected_duplicates(), vec![(1, 2), (2, 3)]);
+        graph.directed = Some(true);
+        assert!(graph.check_undirected_duplicates().is_empty());
+        assert!(load("tests/weighted.gml")
+            .check_undirected_duplicates()
+            .is_empty());
+    }
+
+    #[test]
+    fn mixed_directedness() {
+        let mut graph = load("tests/mixed.gml");
+        assert_eq!(graph.edges[0].directed(), Some(true));
+        assert_eq!(graph.edges[1].directed(), None);
+        assert_eq!(graph.edges[3].directed(), Some(false));
+
+        let neighbors = |graph: &Graph, policy| -> Vec<Vec<i64>> {
+            (1..=4).map(|id| graph.neighbors_with(id, policy)).collect()
+        };
+        let graph_level = vec![vec![2, 4], vec![1, 3], vec![2, 4], vec![1, 3]];
+        assert_eq!(neighbors(&graph, DirectednessPolicy::Graph), graph_level);
+        assert_eq!(graph.neighbors(2), vec![1, 3]);
+        assert_eq!(
+            neighbors(&graph, DirectednessPolicy::PerEdge),
+            vec![vec![2, 4], vec![3], vec![2], vec![1, 3]]
+        );
+        assert!(graph.has_edge(2, 1));
+        assert!(!graph.has_edge_with(2, 1, DirectednessPolicy::PerEdge));
+        assert!(graph.has_edge_with(1, 4, DirectednessPolicy::PerEdge));
+        let degrees = graph.degrees_with(DirectednessPolicy::PerEdge);
+        assert_eq!(
+            (degrees[&1], degrees[&2], degrees[&3], degrees[&4]),
+            (2, 1, 1, 2)
+        );
+        assert_eq!(graph.degrees(), graph.degrees_by(DegreeMode::Undirected));
+        assert_eq!(graph.degree_with(4, DirectednessPolicy::PerEdge), Some(2));
+        assert_eq!(graph.degree_with(2, DirectednessPolicy::PerEdge), Some(1));
+        let options = AdjacencyListOptions {
+            directedness: DirectednessPolicy::PerEdge,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        graph
+            .write_adjacency_list_with(&mut out, None, &options)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: 2 4\n2: 3\n3: 2\n4: 1 3\n"
+        );
+
+        // Per-edge flags of a directed graph
+        graph.directed = Some(true);
+        assert_eq!(graph.neighbors(1), vec![2]);
+        assert_eq!(
+            graph.neighbors_with(1, DirectednessPolicy::PerEdge),
+            vec![2, 4]
+        );
+        assert_eq!(graph.degrees(), graph.degrees_by(DegreeMode::Out));
+
+        graph.edges[1].set_directed(Some(true));
+        graph.edges[3].set_directed(None);
+        let text = graph.to_gml().unwrap();
+        assert_eq!(text.matches("directed").count(), 4);
+        let read = Graph::from_str(&text).unwrap();
+        assert_eq!(read.edges[1].directed(), Some(true));
+        assert_eq!(read.edges[3].directed(), None);
+    }
+
+    #[test]
+    fn edge_queries() {
+        let mut graph = load("tests/weighted.gml");
+        assert!(graph.has_edge(1, 2));
+        assert!(!graph.has_edge(2, 1));
+        assert_eq!(graph.edge_count_between(1, 2), 2);
+        assert_eq!(graph.edge_count_between(2, 1), 0);
+        assert!(graph.has_edge(3, 3));
+        assert!(!graph.has_edge(1, 1));
+        assert_eq!(graph.edge_count_between(3, 3), 1);
+        assert!(!graph.has_edge(1, 99));
+
+        graph.directed = Some(false);
+        assert!(graph.has_edge(2, 1));
+        assert_eq!(graph.edge_count_between(2, 1), 2);
+        assert_eq!(graph.edge_count_between(1, 3), 1);
+        // A self loop isn't counted once per orientation
+        assert_eq!(graph.edge_count_between(3, 3), 1);
+        assert!(!graph.has_edge(2, 2));
+    }
+}
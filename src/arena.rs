@@ -0,0 +1,230 @@
+//! Parsing into a [Bump] arena owned by the caller, see [ArenaObject::from_str_in].
+//!
+//! Every key, string, object and list of the document is allocated in the arena, so a
+//! parsed document doesn't make any allocation of its own. It borrows the arena, which
+//! frees all of them at once when it is dropped or reset. Nothing in the arena is dropped
+//! individually, which is fine here because the values only hold data the arena owns.
+//!
+//! This suits stores that keep many documents around for a long time: each one lives in
+//! a few large chunks instead of thousands of small allocations spread over the heap.
+
+use bumpalo::{collections::Vec as BumpVec, Bump};
+use pest::iterators::Pairs;
+
+use crate::{
+    check_identifier, check_separator, parse_number, parse_text, string_text, Error, GMLObject,
+    GMLValue, ParseOptions, Result, Rule,
+};
+
+/// A [GMLObject] whose pairs live in an arena, see [ArenaObject::from_str_in].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArenaObject<'a> {
+    pub pairs: &'a [(&'a str, ArenaValue<'a>)],
+}
+
+/// A [GMLValue] whose strings, objects and lists live in an arena.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaValue<'a> {
+    GMLString(&'a str),
+    GMLInt(i64),
+    GMLFloat(f64),
+    GMLObject(ArenaObject<'a>),
+    GMLList(&'a [ArenaValue<'a>]),
+}
+
+impl<'a> ArenaObject<'a> {
+    /// Parse GML text like [GMLObject::from_str], allocating the result in `arena`.
+    ///
+    /// The text can be dropped afterwards, only `arena` is borrowed.
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use gml_parser::{ArenaObject, ArenaValue};
+    ///
+    /// let arena = Bump::new();
+    /// let root = ArenaObject::from_str_in("graph [\n\tlabel \"a\"\n]", &arena).unwrap();
+    /// let ArenaValue::GMLObject(graph) = root.pairs[0].1 else { panic!() };
+    /// assert_eq!(graph.get("label"), Some(&ArenaValue::GMLString("a")));
+    /// ```
+    pub fn from_str_in(text: &str, arena: &'a Bump) -> Result<Self> {
+        Self::from_str_in_with(text, &ParseOptions::default(), arena)
+    }
+    /// Like [ArenaObject::from_str_in], using the given [ParseOptions].
+    ///
+    /// The options that shape the pairs apply as in [GMLObject::from_str_with]: how keys,
+    /// separators and numbers are read, [ParseOptions::skip_keys] and
+    /// [ParseOptions::max_pairs_per_object]. The options about building a [Graph](crate::Graph)
+    /// have no effect.
+    pub fn from_str_in_with(text: &str, options: &ParseOptions, arena: &'a Bump) -> Result<Self> {
+        let file = parse_text(text)?;
+        Self::parse_in(file.into_inner(), options, arena)
+    }
+
+    fn parse_in(obj: Pairs<'_, Rule>, options: &ParseOptions, arena: &'a Bump) -> Result<Self> {
+        let mut current_key: Option<&'a str> = None;
+        let mut pairs = BumpVec::new_in(arena);
+        for entry in obj {
+            match entry.as_rule() {
+                Rule::identifier => {
+                    check_identifier(&entry, options)?;
+                    current_key = Some(arena.alloc_str(entry.into_inner().as_str()));
+                }
+                Rule::value => {
+                    check_separator(&entry, current_key, options)?;
+                    let key = current_key.ok_or_else(|| {
+                        Error::Other("Value: No rule current key. Please report this.".into())
+                    })?;
+                    if !options.skip_keys.is_empty() && options.skip_keys.contains(key) {
+                        continue;
+                    }
+                    let inner = entry.into_inner().next().ok_or_else(|| {
+                        Error::Other("No rule inner value. Please report this.".into())
+                    })?;
+                    let value = match inner.as_rule() {
+                        Rule::object => {
+                            if let Some(limit) = options.limits.max_pairs_per_object {
+                                // Children alternate between keys and values
+                                if inner.clone().into_inner().count() / 2 > limit {
+                                    return Err(Error::TooManyPairs {
+                                        index: pairs.iter().filter(|(k, _)| *k == key).count(),
+                                        element: key.to_owned(),
+                                        line: inner.line_col().0,
+                                        limit,
+                                    });
+                                }
+                            }
+                            ArenaValue::GMLObject(ArenaObject::parse_in(
+                                inner.into_inner(),
+                                options,
+                                arena,
+                            )?)
+                        }
+                        Rule::list => {
+                            let mut items = BumpVec::new_in(arena);
+                            for item in inner.into_inner() {
+                                items.push(scalar_in(item.as_rule(), item, options, arena)?);
+                            }
+                            ArenaValue::GMLList(items.into_bump_slice())
+                        }
+                        rule => scalar_in(rule, inner, options, arena)?,
+                    };
+                    pairs.push((key, value));
+                }
+                Rule::EOI => {}
+                rule => {
+                    return Err(Error::Other(format!(
+                        "Unexpected rule {:?} in an object. Please report this.",
+                        rule
+                    )))
+                }
+            }
+        }
+        Ok(ArenaObject {
+            pairs: pairs.into_bump_slice(),
+        })
+    }
+
+    /// The value of the first pair with this key.
+    pub fn get(&self, key: &str) -> Option<&'a ArenaValue<'a>> {
+        self.pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+    /// Copy the object out of the arena.
+    pub fn to_object(&self) -> GMLObject {
+        GMLObject {
+            pairs: self
+                .pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_value()))
+                .collect(),
+        }
+    }
+}
+
+impl ArenaValue<'_> {
+    /// Copy the value out of the arena.
+    pub fn to_value(&self) -> GMLValue {
+        match self {
+            ArenaValue::GMLString(s) => GMLValue::GMLString(s.to_string()),
+            ArenaValue::GMLInt(i) => GMLValue::GMLInt(*i),
+            ArenaValue::GMLFloat(f) => GMLValue::GMLFloat(*f),
+            ArenaValue::GMLObject(obj) => GMLValue::GMLObject(Box::new(obj.to_object())),
+            ArenaValue::GMLList(items) => {
+                GMLValue::GMLList(items.iter().map(ArenaValue::to_value).collect())
+            }
+        }
+    }
+}
+
+// A string or a number
+fn scalar_in<'a>(
+    rule: Rule,
+    pair: pest::iterators::Pair<'_, Rule>,
+    options: &ParseOptions,
+    arena: &'a Bump,
+) -> Result<ArenaValue<'a>> {
+    if rule == Rule::string {
+        return Ok(ArenaValue::GMLString(arena.alloc_str(string_text(&pair))));
+    }
+    Ok(match parse_number(pair.as_str(), options)? {
+        GMLValue::GMLInt(i) => ArenaValue::GMLInt(i),
+        GMLValue::GMLFloat(f) => ArenaValue::GMLFloat(f),
+        GMLValue::GMLString(s) => ArenaValue::GMLString(arena.alloc_str(&s)),
+        GMLValue::GMLObject(_) | GMLValue::GMLList(_) => unreachable!("numbers are scalars"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use bumpalo::Bump;
+    use std::fs;
+
+    #[test]
+    fn arena_matches_owned_parse() {
+        for file in [
+            "tests/wikipedia.gml",
+            "tests/synoptic.gml",
+            "tests/wrapped.gml",
+        ] {
+            let text = fs::read_to_string(file).unwrap();
+            let arena = Bump::new();
+            let parsed = ArenaObject::from_str_in(&text, &arena).unwrap();
+            assert_eq!(parsed.to_object(), GMLObject::from_str(&text).unwrap());
+        }
+        let arena = Bump::new();
+        let text = "levels [ 1 \"a\" 2.5 ]\nempty [\n]";
+        let parsed = ArenaObject::from_str_in(text, &arena).unwrap();
+        assert_eq!(
+            parsed.get("levels"),
+            Some(&ArenaValue::GMLList(&[
+                ArenaValue::GMLInt(1),
+                ArenaValue::GMLString("a"),
+                ArenaValue::GMLFloat(2.5),
+            ]))
+        );
+        assert_eq!(parsed.to_object(), GMLObject::from_str(text).unwrap());
+        for invalid in ["graph [", "graph [ id 1 ]", "id = 1"] {
+            assert!(ArenaObject::from_str_in(invalid, &arena).is_err());
+        }
+    }
+
+    #[test]
+    fn arena_with_options() {
+        let text = fs::read_to_string("tests/options.gml").unwrap();
+        let arena = Bump::new();
+        for options in [
+            ParseOptions::default().skip_keys(["node", "Creator"]),
+            ParseOptions::default().leading_zeros_as_strings(true),
+        ] {
+            let parsed = ArenaObject::from_str_in_with(&text, &options, &arena).unwrap();
+            let expected = GMLObject::from_str_with(&text, &options).unwrap();
+            assert_eq!(parsed.to_object(), expected);
+        }
+        let options = ParseOptions::default().max_pairs_per_object(3);
+        let err = ArenaObject::from_str_in_with(&text, &options, &arena).unwrap_err();
+        assert_eq!(err, GMLObject::from_str_with(&text, &options).unwrap_err());
+        let equals = ParseOptions::default().allow_equals_separator(true);
+        let parsed = ArenaObject::from_str_in_with("id = 1", &equals, &arena).unwrap();
+        assert_eq!(parsed.get("id"), Some(&ArenaValue::GMLInt(1)));
+    }
+}
@@ -0,0 +1,649 @@
+use std::cmp::Ordering;
+
+use crate::{Edge, GMLObject, GMLValue, Graph, Node};
+
+impl GMLValue {
+    /// A total order over values, used to put graphs in a canonical form.
+    ///
+    /// Numbers come first, then strings, then objects, then lists:
+    /// - Ints and floats are compared by their numeric value, exactly even for ints that
+    ///   don't fit in a float. An int comes before a float of the same value, and `-0.0`
+    ///   and `0.0` are equal. NaNs come after every other number.
+    /// - Strings are compared byte by byte.
+    /// - Objects are compared pair by pair, first by key and then by value, and a shorter
+    ///   object comes before a longer one that starts with the same pairs.
+    /// - Lists are compared item by item in the same way.
+    ///
+    /// Two values are ordered [Ordering::Equal] exactly when they are `==`, except that a NaN
+    /// is equal to itself here. The [PartialOrd] implementation follows the same order but
+    /// leaves that case unordered.
+    ///
+    /// ```
+    /// use gml_parser::GMLValue;
+    ///
+    /// let mut values = vec![
+    ///     GMLValue::GMLString("a".into()),
+    ///     GMLValue::GMLFloat(1.5),
+    ///     GMLValue::GMLInt(2),
+    ///     GMLValue::GMLInt(1),
+    ///     GMLValue::GMLFloat(1.0),
+    /// ];
+    /// values.sort_by(GMLValue::canonical_cmp);
+    /// assert_eq!(
+    ///     values,
+    ///     [
+    ///         GMLValue::GMLInt(1),
+    ///         GMLValue::GMLFloat(1.0),
+    ///         GMLValue::GMLFloat(1.5),
+    ///         GMLValue::GMLInt(2),
+    ///         GMLValue::GMLString("a".into()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn canonical_cmp(&self, other: &GMLValue) -> Ordering {
+        use GMLValue::*;
+        match (self, other) {
+            (GMLInt(a), GMLInt(b)) => a.cmp(b),
+            (GMLFloat(a), GMLFloat(b)) => float_cmp(*a, *b),
+            // Ints come first among equal numbers
+            (GMLInt(a), GMLFloat(b)) => int_float_cmp(*a, *b).then(Ordering::Less),
+            (GMLFloat(a), GMLInt(b)) => int_float_cmp(*b, *a).reverse().then(Ordering::Greater),
+            (GMLString(a), GMLString(b)) => a.cmp(b),
+            (GMLObject(a), GMLObject(b)) => object_cmp(a, b),
+            (GMLList(a), GMLList(b)) => list_cmp(a, b),
+            (a, b) => rank(a).cmp(&rank(b)),
+        }
+    }
+}
+
+/// The order of [GMLValue::canonical_cmp]: numbers compared numerically, strings byte by
+/// byte, and values of different types by the fixed rank numbers, strings, objects, lists.
+///
+/// Values that are not `==` but equal in that order, which only happens when both contain
+/// a NaN at the same place, are unordered. Use [GMLValue::canonical_cmp] where a total order
+/// is needed, e.g. with `sort_by`.
+///
+/// ```
+/// use gml_parser::GMLValue;
+///
+/// assert!(GMLValue::GMLInt(2) < GMLValue::GMLFloat(2.5));
+/// assert!(GMLValue::GMLFloat(1e9) < GMLValue::GMLString("1".into()));
+/// assert!(GMLValue::GMLInt(1) < GMLValue::GMLFloat(1.0));
+/// assert_eq!(
+///     GMLValue::GMLFloat(f64::NAN).partial_cmp(&GMLValue::GMLFloat(f64::NAN)),
+///     None
+/// );
+/// ```
+impl PartialOrd for GMLValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.canonical_cmp(other) {
+            Ordering::Equal if self != other => None,
+            order => Some(order),
+        }
+    }
+}
+
+// Position of each variant in the cross-variant order
+fn rank(value: &GMLValue) -> u8 {
+    match value {
+        GMLValue::GMLInt(_) | GMLValue::GMLFloat(_) => 0,
+        GMLValue::GMLString(_) => 1,
+        GMLValue::GMLObject(_) => 2,
+        GMLValue::GMLList(_) => 3,
+    }
+}
+
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) if a == b => Ordering::Equal,
+        (false, false) | (true, true) => a.total_cmp(&b),
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+    }
+}
+
+// Exact comparison, without rounding `i` to a float
+fn int_float_cmp(i: i64, f: f64) -> Ordering {
+    // 2^63, the first float above every i64
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if f.is_nan() || f >= LIMIT {
+        return Ordering::Less;
+    }
+    if f < -LIMIT {
+        return Ordering::Greater;
+    }
+    // The float is now within the range of i64 so its integer part converts exactly
+    let whole = f.trunc();
+    i.cmp(&(whole as i64))
+        .then_with(|| 0.0.partial_cmp(&(f - whole)).expect("not NaN"))
+}
+
+fn object_cmp(a: &GMLObject, b: &GMLObject) -> Ordering {
+    for ((ka, va), (kb, vb)) in a.pairs.iter().zip(&b.pairs) {
+        let order = ka.cmp(kb).then_with(|| va.canonical_cmp(vb));
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.pairs.len().cmp(&b.pairs.len())
+}
+
+fn list_cmp(a: &[GMLValue], b: &[GMLValue]) -> Ordering {
+    for (va, vb) in a.iter().zip(b) {
+        let order = va.canonical_cmp(vb);
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+impl Graph {
+    /// Store every edge with `source <= target` using [Edge::normalize], unless the graph is
+    /// directed (`directed` is `Some(true)`) in which case it is left untouched.
+    pub fn normalize_edge_orientation(&mut self) {
+        if self.directed != Some(true) {
+            self.edges.iter_mut().for_each(Edge::normalize);
+        }
+    }
+    /// Put the graph in a canonical order: edges of undirected graphs oriented with
+    /// [Graph::normalize_edge_orientation], nodes sorted by id, edges by source, target and label,
+    /// and the attributes of the graph, nodes, edges and nested objects sorted by key, then by
+    /// value with [GMLValue::canonical_cmp] when a key repeats.
+    /// Sorting is stable so records that compare equal keep their relative order.
+    pub fn canonicalize(&mut self) {
+        self.normalize_edge_orientation();
+        self.sort_nodes_by_id();
+        self.sort_edges();
+        sort_attrs(&mut self.attrs);
+        for node in &mut self.nodes {
+            sort_attrs(&mut node.attrs);
+        }
+        for edge in &mut self.edges {
+            sort_attrs(&mut edge.attrs);
+        }
+    }
+    /// A hash of the canonical form of the graph, see [Graph::canonicalize].
+    ///
+    /// The fingerprint does not depend on the order of the nodes, edges or attributes in the
+    /// document, but any change to an id, label, endpoint or attribute value changes it.
+    /// Unless the graph is directed, reversing an edge doesn't change it either.
+    /// Source spans are ignored.
+    ///
+    /// Fingerprints are computed with 64 bit FNV-1a and are stable across runs and platforms.
+    /// Changing how they are computed is treated as a breaking change of this crate.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = Fnv::new();
+        hash.write(b"graph");
+        hash.write_option_i64(self.id);
+        hash.write_option_i64(self.directed.map(i64::from));
+        hash.write_option_str(self.label.as_deref());
+        hash.write_option_str(self.name.as_deref());
+        hash.write_attrs(&self.attrs);
+        // Hashing each record on its own and sorting the results makes the fingerprint
+        // independent of the order records appear in, even when they tie on every sort key.
+        let mut nodes: Vec<u64> = self.nodes.iter().map(node_hash).collect();
+        let directed = self.directed == Some(true);
+        let mut edges: Vec<u64> = self
+            .edges
+            .iter()
+            .map(|edge| edge_hash(edge, directed))
+            .collect();
+        nodes.sort_unstable();
+        edges.sort_unstable();
+        hash.write_u64(nodes.len() as u64);
+        nodes.into_iter().for_each(|h| hash.write_u64(h));
+        hash.write_u64(edges.len() as u64);
+        edges.into_iter().for_each(|h| hash.write_u64(h));
+        hash.finish()
+    }
+}
+
+pub(crate) fn sort_attrs(attrs: &mut [(String, GMLValue)]) {
+    // Nested objects are sorted first so that they compare by their canonical form
+    for (_, value) in attrs.iter_mut() {
+        sort_nested(value);
+    }
+    attrs.sort_by(attr_cmp);
+}
+
+fn sort_nested(value: &mut GMLValue) {
+    match value {
+        GMLValue::GMLObject(obj) => sort_attrs(&mut obj.pairs),
+        GMLValue::GMLList(items) => items.iter_mut().for_each(sort_nested),
+        _ => {}
+    }
+}
+
+fn attr_cmp(a: &(String, GMLValue), b: &(String, GMLValue)) -> Ordering {
+    a.0.cmp(&b.0).then_with(|| a.1.canonical_cmp(&b.1))
+}
+
+fn node_hash(node: &Node) -> u64 {
+    let mut hash = Fnv::new();
+    hash.write(b"node");
+    hash.write_i64(node.id);
+    hash.write_option_str(node.label.as_deref());
+    hash.write_attrs(&node.attrs);
+    hash.finish()
+}
+
+fn edge_hash(edge: &Edge, directed: bool) -> u64 {
+    let (source, target) = if directed {
+        (edge.source, edge.target)
+    } else {
+        (edge.source.min(edge.target), edge.source.max(edge.target))
+    };
+    let mut hash = Fnv::new();
+    hash.write(b"edge");
+    hash.write_i64(source);
+    hash.write_i64(target);
+    hash.write_option_str(edge.label.as_deref());
+    hash.write_attrs(&edge.attrs);
+    hash.finish()
+}
+
+// 64 bit FNV-1a. Every write is tagged or length prefixed so that
+// different values can't produce the same byte stream.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    fn write_u64(&mut self, v: u64) {
+        self.write(&v.to_le_bytes());
+    }
+    fn write_i64(&mut self, v: i64) {
+        self.write(&v.to_le_bytes());
+    }
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+    fn write_option_i64(&mut self, v: Option<i64>) {
+        match v {
+            Some(v) => {
+                self.write(&[1]);
+                self.write_i64(v);
+            }
+            None => self.write(&[0]),
+        }
+    }
+    fn write_option_str(&mut self, s: Option<&str>) {
+        match s {
+            Some(s) => {
+                self.write(&[1]);
+                self.write_str(s);
+            }
+            None => self.write(&[0]),
+        }
+    }
+    fn write_attrs(&mut self, attrs: &[(String, GMLValue)]) {
+        // Sorted like Graph::canonicalize does, nested objects included, so that repeated keys
+        // holding objects are ordered by the canonical form of those objects
+        let mut sorted = attrs.to_vec();
+        sort_attrs(&mut sorted);
+        self.write_sorted_attrs(&sorted);
+    }
+    fn write_sorted_attrs(&mut self, attrs: &[(String, GMLValue)]) {
+        self.write_u64(attrs.len() as u64);
+        for (key, value) in attrs {
+            self.write_str(key);
+            self.write_value(value);
+        }
+    }
+    fn write_value(&mut self, value: &GMLValue) {
+        match value {
+            GMLValue::GMLString(s) => {
+                self.write(b"s");
+                self.write_str(s);
+            }
+            GMLValue::GMLInt(i) => {
+                self.write(b"i");
+                self.write_i64(*i);
+            }
+            GMLValue::GMLFloat(f) => {
+                // canonical_cmp treats -0.0 as 0.0 and every NaN alike, so must the hash
+                let f = if *f == 0.0 {
+                    0.0
+                } else if f.is_nan() {
+                    f64::NAN
+                } else {
+                    *f
+                };
+                self.write(b"f");
+                self.write_u64(f.to_bits());
+            }
+            GMLValue::GMLObject(obj) => {
+                self.write(b"o");
+                self.write_sorted_attrs(&obj.pairs);
+            }
+            GMLValue::GMLList(items) => {
+                self.write(b"l");
+                self.write_u64(items.len() as u64);
+                for item in items {
+                    self.write_value(item);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::{cmp::Ordering, fs};
+
+    const REORDERED: &str = r#"graph [
+	label "Hello, I am a graph"
+	edge [
+		target 1
+		source 3
+		label "Edge from node 3 to node 1"
+	]
+	node [
+		thisIsASampleAttribute 44
+		id 3
+		label "node 3"
+	]
+	node [
+		id 2
+		thisIsASampleAttribute 43
+		label "node 2"
+	]
+	edge [
+		source 1
+		target 2
+		label "Edge from node 1 to node 2"
+	]
+	edge [
+		source 2
+		target 3
+		label "Edge from node 2 to node 3"
+	]
+	node [
+		id 1
+		label "node 1"
+		thisIsASampleAttribute 42
+	]
+	id 42
+	directed 1
+	comment "This is a sample graph"
+]"#;
+
+    #[test]
+    fn fingerprint_ignores_order() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        let reordered = Graph::from_str(REORDERED).unwrap();
+        assert_eq!(graph.fingerprint(), reordered.fingerprint());
+
+        let mut canonical = reordered.clone();
+        canonical.canonicalize();
+        assert_eq!(canonical.fingerprint(), graph.fingerprint());
+        let ids: Vec<i64> = canonical.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(canonical.edges[0].source, 1);
+    }
+
+    #[test]
+    fn fingerprint_detects_changes() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+
+        let mut relabeled = graph.clone();
+        relabeled.nodes[1].label = Some("changed".into());
+        assert_ne!(graph.fingerprint(), relabeled.fingerprint());
+
+        let weighted = Graph::from_str(
+            &file.replace("thisIsASampleAttribute 43", "thisIsASampleAttribute 43.0"),
+        )
+        .unwrap();
+        assert_ne!(graph.fingerprint(), weighted.fingerprint());
+
+        let mut reversed = graph.clone();
+        let edge = &mut reversed.edges[0];
+        std::mem::swap(&mut edge.source, &mut edge.target);
+        assert_ne!(graph.fingerprint(), reversed.fingerprint());
+    }
+
+    #[test]
+    fn undirected_orientation() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.edges[2].set_attribute("w", 3);
+
+        let mut directed = graph.clone();
+        directed.normalize_edge_orientation();
+        assert_eq!(directed.edges, graph.edges);
+
+        graph.directed = Some(false);
+        let mut reversed = graph.clone();
+        let edge = &mut reversed.edges[0];
+        std::mem::swap(&mut edge.source, &mut edge.target);
+        assert_eq!(graph.fingerprint(), reversed.fingerprint());
+
+        graph.normalize_edge_orientation();
+        let edges: Vec<(i64, i64)> = graph.edges.iter().map(|e| (e.source, e.target)).collect();
+        assert_eq!(edges, vec![(1, 2), (2, 3), (1, 3)]);
+        assert_eq!(
+            graph.edges[2].label.as_deref(),
+            Some("Edge from node 3 to node 1")
+        );
+        assert_eq!(graph.edges[2].attr_i64("w"), Some(3));
+
+        reversed.canonicalize();
+        let edges: Vec<(i64, i64)> = reversed
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target))
+            .collect();
+        assert_eq!(edges, vec![(1, 2), (1, 3), (2, 3)]);
+    }
+
+    // Small deterministic LCG so failures are reproducible
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound.max(1)
+        }
+        fn value(&mut self, depth: usize) -> GMLValue {
+            const INTS: [i64; 7] = [i64::MIN, -3, -1, 0, 1, 2, i64::MAX];
+            const FLOATS: [f64; 10] = [
+                f64::NEG_INFINITY,
+                -1.0,
+                -0.0,
+                0.0,
+                0.5,
+                1.0,
+                2.0,
+                9_223_372_036_854_775_808.0,
+                f64::INFINITY,
+                f64::NAN,
+            ];
+            match self.next(if depth == 0 { 3 } else { 4 }) {
+                0 => GMLValue::GMLInt(INTS[self.next(INTS.len())]),
+                1 => GMLValue::GMLFloat(FLOATS[self.next(FLOATS.len())]),
+                2 => GMLValue::GMLString(["", "a", "ab", "b"][self.next(4)].into()),
+                _ => {
+                    let pairs = (0..self.next(3))
+                        .map(|_| (["x", "y"][self.next(2)].to_owned(), self.value(depth - 1)))
+                        .collect();
+                    GMLValue::GMLObject(Box::new(GMLObject { pairs }))
+                }
+            }
+        }
+    }
+
+    fn has_nan(value: &GMLValue) -> bool {
+        match value {
+            GMLValue::GMLFloat(f) => f.is_nan(),
+            GMLValue::GMLObject(obj) => obj.pairs.iter().any(|(_, v)| has_nan(v)),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn canonical_order_is_total() {
+        let mut rng = Lcg(0x2545_f491_4f6c_dd1d);
+        let values: Vec<GMLValue> = (0..60).map(|_| rng.value(2)).collect();
+        for a in &values {
+            assert_eq!(a.canonical_cmp(a), Ordering::Equal);
+            for b in &values {
+                let order = a.canonical_cmp(b);
+                assert_eq!(order, b.canonical_cmp(a).reverse(), "{:?} {:?}", a, b);
+                if !has_nan(a) && !has_nan(b) {
+                    assert_eq!(order == Ordering::Equal, a == b, "{:?} {:?}", a, b);
+                }
+                for c in &values {
+                    if order != Ordering::Greater && b.canonical_cmp(c) != Ordering::Greater {
+                        assert_ne!(
+                            a.canonical_cmp(c),
+                            Ordering::Greater,
+                            "{:?} {:?} {:?}",
+                            a,
+                            b,
+                            c
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_order_of_numbers() {
+        let cmp = |a: GMLValue, b: GMLValue| a.canonical_cmp(&b);
+        use GMLValue::{GMLFloat, GMLInt, GMLString};
+        assert_eq!(
+            cmp(GMLInt(i64::MAX), GMLFloat(9.223372036854776e18)),
+            Ordering::Less
+        );
+        assert_eq!(
+            cmp(GMLInt(i64::MIN), GMLFloat(-9.223372036854776e18)),
+            Ordering::Less
+        );
+        assert_eq!(cmp(GMLInt(i64::MAX - 1), GMLInt(i64::MAX)), Ordering::Less);
+        assert_eq!(cmp(GMLInt(-1), GMLFloat(-0.5)), Ordering::Less);
+        assert_eq!(cmp(GMLInt(-1), GMLFloat(-1.5)), Ordering::Greater);
+        assert_eq!(cmp(GMLFloat(-0.0), GMLFloat(0.0)), Ordering::Equal);
+        assert_eq!(cmp(GMLInt(0), GMLFloat(-0.0)), Ordering::Less);
+        assert_eq!(
+            cmp(GMLFloat(f64::INFINITY), GMLFloat(f64::NAN)),
+            Ordering::Less
+        );
+        assert_eq!(cmp(GMLInt(i64::MAX), GMLFloat(f64::NAN)), Ordering::Less);
+        assert_eq!(
+            cmp(GMLFloat(f64::NAN), GMLString(String::new())),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn partial_order_of_mixed_values() {
+        use GMLValue::{GMLFloat, GMLInt, GMLList, GMLString};
+        let mut values = vec![
+            GMLString("b".into()),
+            GMLList(vec![GMLInt(1)]),
+            GMLFloat(-0.5),
+            GMLString("B".into()),
+            GMLInt(3),
+            GMLFloat(f64::INFINITY),
+            GMLInt(-1),
+            GMLFloat(2.0),
+            GMLInt(2),
+        ];
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            values,
+            vec![
+                GMLInt(-1),
+                GMLFloat(-0.5),
+                GMLInt(2),
+                GMLFloat(2.0),
+                GMLInt(3),
+                GMLFloat(f64::INFINITY),
+                GMLString("B".into()),
+                GMLString("b".into()),
+                GMLList(vec![GMLInt(1)]),
+            ]
+        );
+        assert_eq!(
+            GMLFloat(-0.0).partial_cmp(&GMLFloat(0.0)),
+            Some(Ordering::Equal)
+        );
+        assert!(GMLFloat(f64::NAN) > GMLFloat(f64::INFINITY));
+        assert_eq!(GMLFloat(f64::NAN).partial_cmp(&GMLFloat(f64::NAN)), None);
+        let nan_list = GMLList(vec![GMLFloat(f64::NAN)]);
+        assert_eq!(nan_list.partial_cmp(&nan_list.clone()), None);
+
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        for (node, score) in graph.nodes.iter_mut().zip([2.5, 1.0, 2.0]) {
+            node.set_attribute("score", score);
+        }
+        graph.nodes[1].set_attribute("score", 3);
+        let score = |node: &Node| node.get_attribute("score").map(|(_, value)| value.clone());
+        graph
+            .nodes
+            .sort_by(|a, b| score(a).partial_cmp(&score(b)).unwrap());
+        let ids: Vec<i64> = graph.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn canonicalize_sorts_repeated_keys() {
+        let a =
+            Graph::from_str("graph [\nnode [\nid 1\nx 2\nx \"b\"\nx 1.5\ng [\ny 1\ny 0\n]\n]\n]")
+                .unwrap();
+        let b =
+            Graph::from_str("graph [\nnode [\nid 1\ng [\ny 0\ny 1\n]\nx \"b\"\nx 1.5\nx 2\n]\n]")
+                .unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        // Repeated keys holding objects are ordered by the canonical form of the objects
+        let c =
+            Graph::from_str("graph [\nnode [\nid 1\ng [\nb 1\na 2\n]\ng [\na 3\n]\n]\n]").unwrap();
+        let d =
+            Graph::from_str("graph [\nnode [\nid 1\ng [\na 3\n]\ng [\na 2\nb 1\n]\n]\n]").unwrap();
+        assert_eq!(c.fingerprint(), d.fingerprint());
+        let mut zero = c.clone();
+        zero.nodes[0].set_attribute("z", 0.0);
+        let mut negative_zero = c.clone();
+        negative_zero.nodes[0].set_attribute("z", -0.0);
+        assert_eq!(zero.fingerprint(), negative_zero.fingerprint());
+        let (mut c, mut d) = (c, d);
+        c.canonicalize();
+        d.canonicalize();
+        assert_eq!(c.nodes, d.nodes);
+
+        let (mut a, mut b) = (a, b);
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a.nodes, b.nodes);
+        let values: Vec<_> = a.nodes[0].attrs.iter().map(|(_, v)| v.clone()).collect();
+        assert_eq!(
+            values[1..],
+            [
+                GMLValue::GMLFloat(1.5),
+                GMLValue::GMLInt(2),
+                GMLValue::GMLString("b".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! The conversions between numbers and strings used by every typed accessor of this crate,
+//! such as [GMLValue::as_f64](crate::GMLValue::as_f64) and
+//! [ReadableGMLAttributes::attr_f64](crate::ReadableGMLAttributes::attr_f64).
+//!
+//! None of them truncates: a value that can't be represented in the target type gives None.
+//! The only loss is the rounding of ints beyond ±2^53 to the nearest float, since `f64`
+//! can't hold every integer above that.
+
+use std::borrow::Cow;
+
+use crate::GMLValue;
+
+// 2^63, the first float above every i64. -2^63 is i64::MIN itself.
+const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+/// The value as a float:
+/// - floats as they are, including NaN and the infinities
+/// - ints converted exactly up to ±2^53 and rounded to the nearest float beyond
+/// - None for strings, objects and lists
+pub fn to_f64(value: &GMLValue) -> Option<f64> {
+    match value {
+        GMLValue::GMLFloat(f) => Some(*f),
+        GMLValue::GMLInt(i) => Some(*i as f64),
+        _ => None,
+    }
+}
+
+/// The value as an int:
+/// - ints as they are
+/// - floats without a fractional part in `-2^63..2^63`, where `-0.0` gives `0`
+/// - None for other floats, including NaN and the infinities, and for strings, objects and lists
+pub fn to_i64(value: &GMLValue) -> Option<i64> {
+    match value {
+        GMLValue::GMLInt(i) => Some(*i),
+        GMLValue::GMLFloat(f) => float_to_i64(*f),
+        _ => None,
+    }
+}
+
+/// The value as text:
+/// - strings as they are
+/// - ints in decimal
+/// - floats as the shortest text that parses back to the same float, e.g. `1` for `1.0`,
+///   `NaN` and `inf`. Only the distinction between ints and integral floats is lost.
+/// - None for objects and lists
+pub fn to_string_lossy(value: &GMLValue) -> Option<Cow<'_, str>> {
+    match value {
+        GMLValue::GMLString(s) => Some(Cow::Borrowed(s)),
+        GMLValue::GMLInt(i) => Some(Cow::Owned(i.to_string())),
+        GMLValue::GMLFloat(f) => Some(Cow::Owned(f.to_string())),
+        GMLValue::GMLObject(_) | GMLValue::GMLList(_) => None,
+    }
+}
+
+pub(crate) fn float_to_i64(f: f64) -> Option<i64> {
+    // NaN fails every comparison and the infinities are out of range
+    let in_range = (-I64_LIMIT..I64_LIMIT).contains(&f);
+    (in_range && f.fract() == 0.0).then_some(f as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coerce::*;
+    use crate::*;
+
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0
+        }
+    }
+
+    #[test]
+    fn float_int_edge_cases() {
+        let float = |f| to_i64(&GMLValue::GMLFloat(f));
+        let int = |i| to_f64(&GMLValue::GMLInt(i));
+        let two_53 = 1i64 << 53;
+
+        assert_eq!(int(two_53), Some(9_007_199_254_740_992.0));
+        assert_eq!(int(two_53 + 1), Some(9_007_199_254_740_992.0));
+        assert_eq!(int(-two_53 - 1), Some(-9_007_199_254_740_992.0));
+        assert_eq!(float(two_53 as f64), Some(two_53));
+        assert_eq!(float(-(two_53 as f64)), Some(-two_53));
+        assert_eq!(float(two_53 as f64 + 2.0), Some(two_53 + 2));
+
+        assert_eq!(float(-0.0), Some(0));
+        assert_eq!(float(0.5), None);
+        assert_eq!(float(-1.5), None);
+        assert_eq!(float(f64::NAN), None);
+        assert_eq!(float(f64::INFINITY), None);
+        assert_eq!(float(f64::NEG_INFINITY), None);
+        assert_eq!(float(9_223_372_036_854_775_808.0), None);
+        assert_eq!(float(-9_223_372_036_854_775_808.0), Some(i64::MIN));
+        assert_eq!(float(9_223_372_036_854_774_784.0), Some(i64::MAX - 1023));
+        assert_eq!(int(i64::MAX), Some(9_223_372_036_854_775_808.0));
+        assert!(to_f64(&GMLValue::GMLFloat(f64::NAN)).unwrap().is_nan());
+
+        assert_eq!(to_f64(&GMLValue::GMLString("1".into())), None);
+        assert_eq!(to_i64(&GMLValue::GMLString("1".into())), None);
+        assert_eq!(to_i64(&GMLValue::GMLList(vec![GMLValue::GMLInt(1)])), None);
+
+        let text = |value| to_string_lossy(&value).map(|s| s.into_owned());
+        assert_eq!(text(GMLValue::GMLFloat(1.0)).as_deref(), Some("1"));
+        assert_eq!(text(GMLValue::GMLFloat(-0.0)).as_deref(), Some("-0"));
+        assert_eq!(text(GMLValue::GMLFloat(0.1)).as_deref(), Some("0.1"));
+        assert_eq!(text(GMLValue::GMLFloat(f64::NAN)).as_deref(), Some("NaN"));
+        assert_eq!(text(GMLValue::GMLInt(-7)).as_deref(), Some("-7"));
+        assert_eq!(
+            text(GMLValue::GMLString("a b".into())).as_deref(),
+            Some("a b")
+        );
+        assert_eq!(text(GMLValue::GMLList(vec![GMLValue::GMLInt(1)])), None);
+    }
+
+    #[test]
+    fn float_int_round_trips() {
+        let mut rng = Lcg(0x2545_f491_4f6c_dd1d);
+        for _ in 0..10_000 {
+            // Every int up to 2^53 survives the trip through a float
+            let small = (rng.next() >> 10) as i64 - (1 << 53);
+            let f = to_f64(&GMLValue::GMLInt(small)).unwrap();
+            assert_eq!(to_i64(&GMLValue::GMLFloat(f)), Some(small));
+
+            // Larger ones come back as the closest float, never truncated toward zero
+            let large = rng.next() as i64;
+            let f = to_f64(&GMLValue::GMLInt(large)).unwrap();
+            match to_i64(&GMLValue::GMLFloat(f)) {
+                Some(back) => assert_eq!(back as f64, f),
+                None => assert_eq!(f, 9_223_372_036_854_775_808.0),
+            }
+            assert!((large as i128 - f as i128).abs() <= 1024);
+
+            // Floats with a fractional part never convert
+            let bits = f64::from_bits(rng.next());
+            match to_i64(&GMLValue::GMLFloat(bits)) {
+                Some(i) => assert_eq!(i as f64, bits),
+                None => {
+                    assert!(!bits.is_finite() || bits.fract() != 0.0 || bits.abs() >= 2f64.powi(63))
+                }
+            }
+            let value = GMLValue::GMLFloat(bits);
+            let text = to_string_lossy(&value).unwrap();
+            let parsed: f64 = text.parse().unwrap();
+            assert!(parsed == bits || (parsed.is_nan() && bits.is_nan()));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Removal of repeated attribute keys, see [Graph::compact_attrs].
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{GMLObject, GMLValue, Graph};
+
+/// What [Graph::compact_attrs] does with an attribute key that appears more than once
+/// in the same element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAttrPolicy {
+    /// Keep the first value and drop the later ones
+    KeepFirst,
+    /// Keep the last value, in the position of the first one
+    KeepLast,
+    /// Replace the values with a single object, in the position of the first one, that
+    /// holds every value under the same key: `weight 1 weight 2` becomes
+    /// `weight [ weight 1 weight 2 ]`
+    Collect,
+    /// Leave the values as they are and only count them
+    Report,
+}
+
+/// The number of keys that appeared more than once, summed over the elements of each kind,
+/// see [Graph::compact_attrs].
+///
+/// A key repeated inside a nested object such as `graphics [...]` is counted for the node,
+/// edge or graph the object belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DuplicateAttrCounts {
+    pub graph: usize,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+impl Graph {
+    /// Resolve attribute keys that appear more than once in the same node, edge or graph,
+    /// including inside nested objects, according to `policy`.
+    ///
+    /// Only the [attributes](crate::HasGMLAttributes::attributes) are looked at, so the keys
+    /// that are read into fields such as `id` or `label` are not affected.
+    pub fn compact_attrs(&mut self, policy: DuplicateAttrPolicy) -> DuplicateAttrCounts {
+        DuplicateAttrCounts {
+            graph: compact_pairs(&mut self.attrs, policy),
+            nodes: self
+                .nodes
+                .iter_mut()
+                .map(|node| compact_pairs(&mut node.attrs, policy))
+                .sum(),
+            edges: self
+                .edges
+                .iter_mut()
+                .map(|edge| compact_pairs(&mut edge.attrs, policy))
+                .sum(),
+        }
+    }
+}
+
+/// The keys that appear more than once in `pairs`.
+pub(crate) fn duplicate_keys(pairs: &[(String, GMLValue)]) -> HashSet<&str> {
+    let mut seen = HashSet::with_capacity(pairs.len());
+    pairs
+        .iter()
+        .filter(|(key, _)| !seen.insert(key.as_str()))
+        .map(|(key, _)| key.as_str())
+        .collect()
+}
+
+// Returns the number of duplicated keys in `pairs` and the objects nested in it
+fn compact_pairs(pairs: &mut Vec<(String, GMLValue)>, policy: DuplicateAttrPolicy) -> usize {
+    let mut affected = 0;
+    for (_, value) in pairs.iter_mut() {
+        if let GMLValue::GMLObject(obj) = value {
+            affected += compact_pairs(&mut obj.pairs, policy);
+        }
+    }
+    let duplicated: HashSet<String> = duplicate_keys(pairs)
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+    affected += duplicated.len();
+    if duplicated.is_empty() || policy == DuplicateAttrPolicy::Report {
+        return affected;
+    }
+    // Position of the first value of every duplicated key in the compacted pairs
+    let mut first = HashMap::with_capacity(duplicated.len());
+    let mut compacted = Vec::with_capacity(pairs.len());
+    for (key, value) in std::mem::take(pairs) {
+        if !duplicated.contains(&key) {
+            compacted.push((key, value));
+            continue;
+        }
+        let Some(&index) = first.get(&key) else {
+            first.insert(key.clone(), compacted.len());
+            let value = match policy {
+                DuplicateAttrPolicy::Collect => GMLValue::GMLObject(Box::new(GMLObject {
+                    pairs: vec![(key.clone(), value)],
+                })),
+                _ => value,
+            };
+            compacted.push((key, value));
+            continue;
+        };
+        match (policy, &mut compacted[index].1) {
+            (DuplicateAttrPolicy::KeepLast, first) => *first = value,
+            (DuplicateAttrPolicy::Collect, GMLValue::GMLObject(values)) => {
+                values.pairs.push((key, value))
+            }
+            _ => {}
+        }
+    }
+    *pairs = compacted;
+    affected
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn graph() -> Graph {
+        let file = fs::read_to_string("tests/duplicate_attrs.gml").unwrap();
+        Graph::from_str(&file).unwrap()
+    }
+
+    #[test]
+    fn compact_duplicate_attrs() {
+        let expected = DuplicateAttrCounts {
+            graph: 1,
+            nodes: 2,
+            edges: 2,
+        };
+        let mut reported = graph();
+        assert_eq!(
+            reported.compact_attrs(DuplicateAttrPolicy::Report),
+            expected
+        );
+        assert_eq!(reported.attrs, graph().attrs);
+        assert_eq!(reported.nodes, graph().nodes);
+        assert_eq!(reported.edges, graph().edges);
+
+        let mut first = graph();
+        assert_eq!(
+            first.compact_attrs(DuplicateAttrPolicy::KeepFirst),
+            expected
+        );
+        assert_eq!(first.attr_str("creator"), Some("first"));
+        assert_eq!(first.nodes[0].attrs.len(), 2);
+        assert_eq!(first.nodes[0].attr_i64("weight"), Some(1));
+        assert_eq!(first.nodes[0].attr_at_f64("graphics/x"), Some(1.0));
+        assert_eq!(first.nodes[0].attr_at_f64("graphics/y"), Some(2.0));
+        let keys: Vec<_> = first.edges[0]
+            .attrs
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(keys, ["weight", "color"]);
+        assert_eq!(first.nodes[1], graph().nodes[1]);
+        assert_eq!(
+            first.compact_attrs(DuplicateAttrPolicy::Report),
+            DuplicateAttrCounts::default()
+        );
+
+        let mut last = graph();
+        assert_eq!(last.compact_attrs(DuplicateAttrPolicy::KeepLast), expected);
+        assert_eq!(last.attr_str("creator"), Some("second"));
+        assert_eq!(last.nodes[0].attr_i64("weight"), Some(2));
+        assert_eq!(last.nodes[0].attr_at_f64("graphics/x"), Some(1.5));
+        assert_eq!(last.edges[0].attr_f64("weight"), Some(0.25));
+        assert_eq!(last.edges[0].attr_str("color"), Some("green"));
+        assert_eq!(last.edges[0].attrs[0].0, "weight");
+    }
+
+    #[test]
+    fn collect_duplicate_attrs() {
+        let mut graph = graph();
+        graph.compact_attrs(DuplicateAttrPolicy::Collect);
+        let weights = graph.nodes[0].attr_object("weight").unwrap();
+        assert_eq!(
+            weights.pairs,
+            [
+                ("weight".to_owned(), GMLValue::GMLInt(1)),
+                ("weight".to_owned(), GMLValue::GMLInt(2))
+            ]
+        );
+        assert_eq!(graph.nodes[0].attr_at_f64("graphics/x/x[1]"), Some(1.5));
+        assert_eq!(graph.edges[0].attr_at_str("color/color[1]"), Some("green"));
+        assert_eq!(graph.nodes[1].attr_i64("weight"), Some(4));
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(read.nodes, graph.nodes);
+    }
+}
@@ -0,0 +1,130 @@
+//! Conversion between values and timestamps, see [GMLValue::as_datetime].
+
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::GMLValue;
+
+// Epoch ints with fewer digits are seconds (up to the year 5138), longer ones are milliseconds
+const MAX_EPOCH_SECONDS: i64 = 100_000_000_000;
+// Epoch milliseconds beyond this (the year 5138 again) are treated as ambiguous
+const MAX_EPOCH_MILLIS: i64 = MAX_EPOCH_SECONDS * 1000;
+
+impl GMLValue {
+    /// The value as a timestamp if it is an RFC 3339 string such as
+    /// `"2023-05-01T12:00:00Z"` or a unix epoch int.
+    ///
+    /// Epoch ints are read as seconds when their magnitude is below 10^11 and as
+    /// milliseconds below 10^14. Anything larger, floats, objects and strings that are not
+    /// RFC 3339 give None.
+    ///
+    /// ```
+    /// use gml_parser::GMLValue;
+    ///
+    /// let seconds = GMLValue::GMLInt(1714569600).as_datetime().unwrap();
+    /// let millis = GMLValue::GMLInt(1714569600000).as_datetime().unwrap();
+    /// let text = GMLValue::GMLString("2024-05-01T13:20:00+00:00".into()).as_datetime();
+    /// assert_eq!(seconds, millis);
+    /// assert_eq!(Some(seconds), text);
+    /// ```
+    pub fn as_datetime(&self) -> Option<OffsetDateTime> {
+        match self {
+            GMLValue::GMLString(s) => OffsetDateTime::parse(s, &Rfc3339).ok(),
+            GMLValue::GMLInt(i) if i.unsigned_abs() < MAX_EPOCH_SECONDS as u64 => {
+                OffsetDateTime::from_unix_timestamp(*i).ok()
+            }
+            GMLValue::GMLInt(i) if i.unsigned_abs() < MAX_EPOCH_MILLIS as u64 => {
+                OffsetDateTime::from_unix_timestamp_nanos(*i as i128 * 1_000_000).ok()
+            }
+            _ => None,
+        }
+    }
+    /// The timestamp as an RFC 3339 string, which [GMLValue::as_datetime] reads back.
+    ///
+    /// Years that RFC 3339 can't represent are written as epoch seconds instead.
+    pub fn from_datetime(datetime: OffsetDateTime) -> GMLValue {
+        match datetime.format(&Rfc3339) {
+            Ok(text) => GMLValue::GMLString(text),
+            Err(_) => GMLValue::GMLInt(datetime.unix_timestamp()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use time::{Duration, OffsetDateTime, UtcOffset};
+
+    #[test]
+    fn parse_datetimes() {
+        let noon = OffsetDateTime::from_unix_timestamp(1682942400).unwrap();
+        let string = |s: &str| GMLValue::GMLString(s.into()).as_datetime();
+
+        // Epoch seconds and milliseconds
+        assert_eq!(GMLValue::GMLInt(1682942400).as_datetime(), Some(noon));
+        assert_eq!(
+            GMLValue::GMLInt(0).as_datetime(),
+            Some(OffsetDateTime::UNIX_EPOCH)
+        );
+        assert_eq!(
+            GMLValue::GMLInt(-86400).as_datetime(),
+            Some(OffsetDateTime::UNIX_EPOCH - Duration::DAY)
+        );
+        assert_eq!(GMLValue::GMLInt(1682942400000).as_datetime(), Some(noon));
+        assert_eq!(
+            GMLValue::GMLInt(1682942400123).as_datetime(),
+            Some(noon + Duration::milliseconds(123))
+        );
+
+        // RFC 3339 with offsets
+        assert_eq!(string("2023-05-01T12:00:00Z"), Some(noon));
+        let offset = string("2023-05-01T14:30:00+02:30").unwrap();
+        assert_eq!(offset, noon);
+        assert_eq!(offset.offset(), UtcOffset::from_hms(2, 30, 0).unwrap());
+        assert_eq!(
+            string("2023-05-01T07:00:00.5-05:00"),
+            Some(noon + Duration::milliseconds(500))
+        );
+
+        // Garbage and ambiguous values
+        for value in [
+            GMLValue::GMLString("2023-05-01".into()),
+            GMLValue::GMLString("2023-05-01 12:00:00".into()),
+            GMLValue::GMLString("2023-13-01T12:00:00Z".into()),
+            GMLValue::GMLString("yesterday".into()),
+            GMLValue::GMLString("1682942400".into()),
+            GMLValue::GMLInt(i64::MAX),
+            GMLValue::GMLInt(100_000_000_000_000),
+            GMLValue::GMLFloat(1682942400.0),
+            GMLValue::GMLObject(Box::new(GMLObject { pairs: Vec::new() })),
+        ] {
+            assert_eq!(value.as_datetime(), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn datetime_attributes() {
+        let text =
+            "graph [\nnode [\nid 1\ncreated \"2023-05-01T12:00:00Z\"\nupdated 1714569600\n]\n]";
+        let mut graph = Graph::from_str(text).unwrap();
+        let node = &graph.nodes[0];
+        let created = node.attr_datetime("created").unwrap();
+        let updated = node.attr_datetime("updated").unwrap();
+        assert_eq!(
+            updated - created,
+            Duration::seconds(1714569600 - 1682942400)
+        );
+        assert_eq!(node.attr_datetime("id"), node.attr_datetime("missing"));
+
+        let later = updated + Duration::hours(1);
+        graph.nodes[0].set_attribute("updated", GMLValue::from_datetime(later));
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(
+            read.nodes[0].attr_str("updated"),
+            Some("2024-05-01T14:20:00Z")
+        );
+        assert_eq!(read.nodes[0].attr_datetime("updated"), Some(later));
+
+        let far = OffsetDateTime::UNIX_EPOCH - Duration::days(800_000);
+        assert_eq!(GMLValue::from_datetime(far).as_datetime(), Some(far));
+    }
+}
@@ -0,0 +1,274 @@
+//! Graph-level default values of node and edge attributes, see [Graph::resolve_node_attr].
+
+use crate::{AttrScope, Edge, GMLObject, GMLValue, Graph, HasGMLAttributes};
+
+/// Keys of the graph attributes holding the defaults of node and edge attributes,
+/// see [Graph::resolve_node_attr_with].
+#[derive(Debug, Clone)]
+pub struct AttrDefaults {
+    /// `node_defaults` by default
+    pub node_key: String,
+    /// `edge_defaults` by default
+    pub edge_key: String,
+}
+
+impl Default for AttrDefaults {
+    fn default() -> Self {
+        AttrDefaults {
+            node_key: "node_defaults".to_owned(),
+            edge_key: "edge_defaults".to_owned(),
+        }
+    }
+}
+
+impl AttrDefaults {
+    fn key(&self, scope: AttrScope) -> &str {
+        match scope {
+            AttrScope::Nodes => &self.node_key,
+            AttrScope::Edges => &self.edge_key,
+        }
+    }
+}
+
+impl Graph {
+    /// The attribute `name` of the node with id `node_id`, or the value of `name` in the
+    /// `node_defaults [...]` graph attribute when the node doesn't have it.
+    ///
+    /// Returns None when there is no such node.
+    pub fn resolve_node_attr(&self, node_id: i64, name: &str) -> Option<&GMLValue> {
+        self.resolve_node_attr_with(node_id, name, &AttrDefaults::default())
+    }
+    /// Like [Graph::resolve_node_attr], reading the defaults from [AttrDefaults::node_key].
+    pub fn resolve_node_attr_with(
+        &self,
+        node_id: i64,
+        name: &str,
+        defaults: &AttrDefaults,
+    ) -> Option<&GMLValue> {
+        let node = self.nodes.iter().find(|node| node.id == node_id)?;
+        self.resolve(node.attributes(), AttrScope::Nodes, name, defaults)
+    }
+    /// The attribute `name` of `edge`, or the value of `name` in the `edge_defaults [...]`
+    /// graph attribute when the edge doesn't have it.
+    pub fn resolve_edge_attr<'a>(&'a self, edge: &'a Edge, name: &str) -> Option<&'a GMLValue> {
+        self.resolve_edge_attr_with(edge, name, &AttrDefaults::default())
+    }
+    /// Like [Graph::resolve_edge_attr], reading the defaults from [AttrDefaults::edge_key].
+    pub fn resolve_edge_attr_with<'a>(
+        &'a self,
+        edge: &'a Edge,
+        name: &str,
+        defaults: &AttrDefaults,
+    ) -> Option<&'a GMLValue> {
+        self.resolve(edge.attributes(), AttrScope::Edges, name, defaults)
+    }
+    /// The defaults object of the nodes or edges, if the graph has one.
+    pub fn attr_defaults(&self, scope: AttrScope, defaults: &AttrDefaults) -> Option<&GMLObject> {
+        let key = defaults.key(scope);
+        self.attributes()
+            .iter()
+            .find_map(|(k, value)| value.as_object().filter(|_| k == key))
+    }
+    /// Move the attributes shared by the nodes or edges into the `node_defaults [...]` or
+    /// `edge_defaults [...]` graph attribute, the inverse of resolving them.
+    ///
+    /// An attribute is moved when every record has it and at least a `threshold` fraction
+    /// of the records have the same value, e.g. `0.5` for half of them. That value becomes
+    /// the default and is removed from the records that have it, the others keep theirs as
+    /// an override. Attributes missing from some records are left alone because those records
+    /// would otherwise pick up the default. Records with the attribute more than once are
+    /// only compared on the first value.
+    ///
+    /// Returns the keys that were moved, in the order they first appear.
+    pub fn extract_common_attrs(&mut self, scope: AttrScope, threshold: f64) -> Vec<String> {
+        self.extract_common_attrs_with(scope, threshold, &AttrDefaults::default())
+    }
+    /// Like [Graph::extract_common_attrs], writing the defaults to the key from `defaults`.
+    pub fn extract_common_attrs_with(
+        &mut self,
+        scope: AttrScope,
+        threshold: f64,
+        defaults: &AttrDefaults,
+    ) -> Vec<String> {
+        let records: Vec<&mut Vec<(String, GMLValue)>> = match scope {
+            AttrScope::Nodes => self.nodes.iter_mut().map(|n| n.attributes_mut()).collect(),
+            AttrScope::Edges => self.edges.iter_mut().map(|e| e.attributes_mut()).collect(),
+        };
+        let mut common = Vec::new();
+        if let Some(first) = records.first() {
+            for (key, _) in first.iter() {
+                if common.iter().any(|(k, _)| k == key) {
+                    continue;
+                }
+                let Some(values) = records
+                    .iter()
+                    .map(|attrs| attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v))
+                    .collect::<Option<Vec<&GMLValue>>>()
+                else {
+                    continue;
+                };
+                // The most frequent value, the first one seen on a tie
+                let mut counts: Vec<(&GMLValue, usize)> = Vec::new();
+                for value in values {
+                    match counts.iter_mut().find(|(v, _)| *v == value) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((value, 1)),
+                    }
+                }
+                let (value, count) = counts
+                    .into_iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .expect("every record has the attribute");
+                if count as f64 >= threshold * records.len() as f64 {
+                    common.push((key.clone(), value.clone()));
+                }
+            }
+        }
+        for attrs in records {
+            for (key, value) in &common {
+                if let Some(i) = attrs.iter().position(|(k, _)| k == key) {
+                    if &attrs[i].1 == value {
+                        attrs.remove(i);
+                    }
+                }
+            }
+        }
+        if common.is_empty() {
+            return Vec::new();
+        }
+        let key = defaults.key(scope);
+        let attrs = self.attributes_mut();
+        let object = match attrs
+            .iter()
+            .position(|(k, v)| k == key && v.as_object().is_some())
+        {
+            Some(i) => &mut attrs[i].1,
+            None => {
+                attrs.push((
+                    key.to_owned(),
+                    GMLValue::GMLObject(Box::new(GMLObject { pairs: Vec::new() })),
+                ));
+                &mut attrs.last_mut().expect("just pushed").1
+            }
+        };
+        let GMLValue::GMLObject(object) = object else {
+            unreachable!("checked to be an object above");
+        };
+        for (key, value) in &common {
+            object
+                .entry(key)
+                .and_modify(|v| *v = value.clone())
+                .or_insert(value.clone());
+        }
+        common.into_iter().map(|(key, _)| key).collect()
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        attrs: &'a [(String, GMLValue)],
+        scope: AttrScope,
+        name: &str,
+        defaults: &AttrDefaults,
+    ) -> Option<&'a GMLValue> {
+        match attrs.iter().find(|(k, _)| k == name) {
+            Some((_, value)) => Some(value),
+            None => self.attr_defaults(scope, defaults)?.get(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn resolve_defaults() {
+        let text = "graph [\n\tnode_defaults [\n\t\tcolor \"red\"\n\t\tsize 2\n\t]\n\tedge_defaults [\n\t\tweight 1.5\n\t]\n\tnode [\n\t\tid 1\n\t\tcolor \"blue\"\n\t]\n\tnode [\n\t\tid 2\n\t]\n\tedge [\n\t\tsource 1\n\t\ttarget 2\n\t]\n]";
+        let graph = Graph::from_str(text).unwrap();
+        let color = |id| {
+            graph
+                .resolve_node_attr(id, "color")
+                .and_then(GMLValue::as_str)
+        };
+        assert_eq!(color(1), Some("blue"));
+        assert_eq!(color(2), Some("red"));
+        assert_eq!(color(3), None);
+        assert_eq!(
+            graph
+                .resolve_node_attr(1, "size")
+                .and_then(GMLValue::as_i64),
+            Some(2)
+        );
+        assert_eq!(graph.resolve_node_attr(1, "shape"), None);
+        let edge = &graph.edges[0];
+        assert_eq!(
+            graph
+                .resolve_edge_attr(edge, "weight")
+                .and_then(GMLValue::as_f64),
+            Some(1.5)
+        );
+
+        let custom = AttrDefaults {
+            node_key: "edge_defaults".into(),
+            ..Default::default()
+        };
+        let weight = graph.resolve_node_attr_with(2, "weight", &custom);
+        assert_eq!(weight.and_then(GMLValue::as_f64), Some(1.5));
+    }
+
+    #[test]
+    fn extract_common_attrs() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        for (i, node) in graph.nodes.iter_mut().enumerate() {
+            node.set_attribute("shape", if i == 0 { "box" } else { "circle" });
+            if i > 0 {
+                node.set_attribute("partial", 1);
+            }
+        }
+        let original = graph.clone();
+        let resolved = |graph: &Graph, key| -> Vec<Option<GMLValue>> {
+            graph
+                .nodes
+                .iter()
+                .map(|node| graph.resolve_node_attr(node.id, key).cloned())
+                .collect()
+        };
+
+        assert!(graph
+            .clone()
+            .extract_common_attrs(AttrScope::Nodes, 1.0)
+            .is_empty());
+        assert_eq!(
+            graph.extract_common_attrs(AttrScope::Nodes, 0.5),
+            vec!["graphics", "shape"]
+        );
+        let defaults = graph.attr_defaults(AttrScope::Nodes, &AttrDefaults::default());
+        assert_eq!(
+            defaults.unwrap().get("shape").and_then(GMLValue::as_str),
+            Some("circle")
+        );
+        assert!(graph.nodes[0].has_attribute("shape"));
+        assert!(!graph.nodes[1].has_attribute("shape"));
+        for key in ["graphics", "shape", "partial"] {
+            assert_eq!(resolved(&graph, key), resolved(&original, key));
+        }
+
+        let written = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        for key in ["graphics", "shape", "partial"] {
+            assert_eq!(resolved(&written, key), resolved(&original, key));
+        }
+        for (i, edge) in graph.edges.iter_mut().enumerate() {
+            edge.set_attribute("weight", if i < 4 { 1 } else { 2 });
+        }
+        assert!(graph.extract_common_attrs(AttrScope::Edges, 0.6).is_empty());
+        assert_eq!(
+            graph.extract_common_attrs(AttrScope::Edges, 0.5),
+            vec!["weight"]
+        );
+        let weight = graph.resolve_edge_attr(&graph.edges[7], "weight");
+        assert_eq!(weight.and_then(GMLValue::as_i64), Some(2));
+    }
+}
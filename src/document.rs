@@ -0,0 +1,213 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::{graph_pair_spans, parse_text, Error, GMLObject, GMLValue, Graph, Result};
+
+/// GML text kept together with its parsed [GMLObject] so that small edits can be
+/// applied without re-parsing the whole file.
+///
+/// After any sequence of successful [GMLDocument::apply_edit] calls, [GMLDocument::root]
+/// is equal to what [GMLObject::from_str] returns for [GMLDocument::text].
+#[derive(Debug, Clone)]
+pub struct GMLDocument {
+    text: String,
+    root: GMLObject,
+    // Index of the `graph` pair in root.pairs and the byte ranges of the pairs inside it
+    graph: Option<(usize, Vec<Range<usize>>)>,
+    full_reparses: usize,
+}
+
+impl GMLDocument {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str) -> Result<Self> {
+        let mut doc = GMLDocument {
+            text: String::new(),
+            root: GMLObject { pairs: Vec::new() },
+            graph: None,
+            full_reparses: 0,
+        };
+        doc.reparse(text.to_owned())?;
+        Ok(doc)
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn root(&self) -> &GMLObject {
+        &self.root
+    }
+    /// Convert the current contents into a [Graph].
+    pub fn graph(&self) -> Result<Graph> {
+        Graph::from_gml(self.root.clone())
+    }
+    /// Replace `range` of the text with `replacement` and update the parsed tree.
+    ///
+    /// Edits contained within a single `key [...]` element of the graph only re-parse that
+    /// element. Anything else falls back to parsing the whole text again.
+    /// If the edited text is not valid GML an error is returned and the document is left unchanged.
+    pub fn apply_edit(&mut self, range: Range<usize>, replacement: &str) -> Result<()> {
+        if range.start > range.end
+            || range.end > self.text.len()
+            || !self.text.is_char_boundary(range.start)
+            || !self.text.is_char_boundary(range.end)
+        {
+            return Err(Error::Other(format!(
+                "Edit range {:?} is not valid for a document of {} bytes",
+                range,
+                self.text.len()
+            )));
+        }
+        let mut text = String::with_capacity(self.text.len() + replacement.len());
+        text.push_str(&self.text[..range.start]);
+        text.push_str(replacement);
+        text.push_str(&self.text[range.end..]);
+
+        if self.try_patch(&text, &range, replacement.len()) {
+            self.text = text;
+            Ok(())
+        } else {
+            self.reparse(text)
+        }
+    }
+
+    // Re-parse only the element containing `range`. Returns false when the edit
+    // cannot be handled locally.
+    fn try_patch(&mut self, text: &str, range: &Range<usize>, inserted: usize) -> bool {
+        let Some((graph_index, spans)) = &mut self.graph else {
+            return false;
+        };
+        // The first and last byte of the element must be untouched and the element must
+        // be a bracketed block. This keeps the surrounding separators intact so the
+        // element parses the same way alone as it does in the full text.
+        let Some(index) = spans.iter().position(|span| {
+            span.start < range.start
+                && range.end < span.end
+                && self.text.as_bytes()[span.end - 1] == b']'
+        }) else {
+            return false;
+        };
+        let span = &spans[index];
+        let new_end = span.end - range.len() + inserted;
+        let Ok(file) = parse_text(&text[span.start..new_end]) else {
+            return false;
+        };
+        let Ok(mut fragment) = GMLObject::from_pairs(file.into_inner()) else {
+            return false;
+        };
+        if fragment.pairs.len() != 1 {
+            return false;
+        }
+        let GMLValue::GMLObject(graph) = &mut self.root.pairs[*graph_index].1 else {
+            return false;
+        };
+        graph.pairs[index] = fragment.pairs.remove(0);
+
+        let delta = inserted as isize - range.len() as isize;
+        spans[index].end = new_end;
+        for span in &mut spans[index + 1..] {
+            span.start = span.start.wrapping_add_signed(delta);
+            span.end = span.end.wrapping_add_signed(delta);
+        }
+        true
+    }
+
+    fn reparse(&mut self, text: String) -> Result<()> {
+        let file = parse_text(&text)?;
+        let spans = graph_pair_spans(file.clone().into_inner(), "graph", &HashSet::new());
+        let root = GMLObject::from_pairs(file.into_inner())?;
+        let graph_index = root
+            .pairs
+            .iter()
+            .position(|(key, value)| key == "graph" && matches!(value, GMLValue::GMLObject(_)));
+        self.graph = graph_index.zip(spans);
+        self.root = root;
+        self.text = text;
+        self.full_reparses += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn edit_inside_node_is_local() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut doc = GMLDocument::from_str(&file).unwrap();
+        let at = file.find("node 2").unwrap();
+        doc.apply_edit(at..at + 6, "second node").unwrap();
+        assert_eq!(doc.full_reparses, 1);
+        assert_eq!(doc.root(), &GMLObject::from_str(doc.text()).unwrap());
+        let graph = doc.graph().unwrap();
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.label.as_deref() == Some("second node")));
+
+        // Edits outside of any element fall back to a full parse
+        let at = doc.text().find("label \"Hello").unwrap();
+        doc.apply_edit(at..at, "id2 5\n\t").unwrap();
+        assert_eq!(doc.full_reparses, 2);
+        assert_eq!(doc.root(), &GMLObject::from_str(doc.text()).unwrap());
+    }
+
+    #[test]
+    fn invalid_edit_leaves_document_unchanged() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut doc = GMLDocument::from_str(&file).unwrap();
+        let at = file.find("node [").unwrap();
+        assert!(doc.apply_edit(at + 5..at + 6, "").is_err());
+        assert_eq!(doc.text(), file);
+        assert!(doc.apply_edit(0..file.len() + 1, "").is_err());
+    }
+
+    #[test]
+    fn random_edits_match_full_parse() {
+        let fragments = [
+            "",
+            "a",
+            "7",
+            " ",
+            "\n",
+            "[",
+            "]",
+            "\"",
+            "x 1\n",
+            "\n]",
+            "node [\nid 9\n]\n",
+        ];
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        // Small deterministic LCG so failures are reproducible
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) as usize) % bound.max(1)
+        };
+        let mut doc = GMLDocument::from_str(&file).unwrap();
+        let mut applied = 0;
+        for _ in 0..500 {
+            let len = doc.text().len();
+            let start = next(len + 1);
+            let end = (start + next(4)).min(len);
+            let replacement = fragments[next(fragments.len())];
+            let before = doc.text().to_owned();
+            let mut expected = before.clone();
+            expected.replace_range(start..end, replacement);
+            match doc.apply_edit(start..end, replacement) {
+                Ok(()) => {
+                    applied += 1;
+                    assert_eq!(doc.text(), expected);
+                    assert_eq!(doc.root(), &GMLObject::from_str(&expected).unwrap());
+                }
+                Err(_) => {
+                    assert!(GMLObject::from_str(&expected).is_err());
+                    assert_eq!(doc.text(), before);
+                }
+            }
+        }
+        assert!(applied > 25);
+        assert!(doc.full_reparses < applied);
+    }
+}
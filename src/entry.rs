@@ -0,0 +1,109 @@
+//! Get-or-insert access to the pairs of an object, see [GMLObject::entry].
+
+use crate::{GMLObject, GMLValue};
+
+impl GMLObject {
+    /// The first pair with `key`, or the place where one would be added at the end,
+    /// for in-place updates like those of [HashMap::entry](std::collections::HashMap::entry).
+    ///
+    /// ```
+    /// use gml_parser::{GMLObject, GMLValue};
+    ///
+    /// let mut obj = GMLObject { pairs: Vec::new() };
+    /// for _ in 0..3 {
+    ///     obj.entry("count")
+    ///         .and_modify(|count| {
+    ///             if let GMLValue::GMLInt(n) = count {
+    ///                 *n += 1;
+    ///             }
+    ///         })
+    ///         .or_insert(GMLValue::GMLInt(1));
+    /// }
+    /// assert_eq!(obj.get("count"), Some(&GMLValue::GMLInt(3)));
+    /// ```
+    pub fn entry(&mut self, key: &str) -> Entry<'_> {
+        let index = self.pairs.iter().position(|(k, _)| k == key);
+        Entry {
+            pairs: &mut self.pairs,
+            key: key.to_owned(),
+            index,
+        }
+    }
+}
+
+/// A pair of a [GMLObject] that may not exist yet, see [GMLObject::entry].
+#[derive(Debug)]
+pub struct Entry<'a> {
+    pairs: &'a mut Vec<(String, GMLValue)>,
+    key: String,
+    // Position of the pair when it exists
+    index: Option<usize>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    /// The value of the pair, adding it with `default` first if it doesn't exist.
+    pub fn or_insert(self, default: GMLValue) -> &'a mut GMLValue {
+        self.or_insert_with(|| default)
+    }
+    /// Like [Entry::or_insert], only calling `default` when the pair doesn't exist.
+    pub fn or_insert_with<F: FnOnce() -> GMLValue>(self, default: F) -> &'a mut GMLValue {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                self.pairs.push((self.key, default()));
+                self.pairs.len() - 1
+            }
+        };
+        &mut self.pairs[index].1
+    }
+    /// Call `f` on the value if the pair exists.
+    pub fn and_modify<F: FnOnce(&mut GMLValue)>(self, f: F) -> Self {
+        if let Some(index) = self.index {
+            f(&mut self.pairs[index].1);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn object_entries() {
+        let mut obj = GMLObject::from_str("weight 2\ncount 5\ncount 9").unwrap();
+        let count = obj.entry("count").or_insert(GMLValue::GMLInt(0));
+        assert_eq!(count, &GMLValue::GMLInt(5));
+        *count = GMLValue::GMLInt(6);
+        assert_eq!(obj.pairs[1].1, GMLValue::GMLInt(6));
+        assert_eq!(obj.pairs[2].1, GMLValue::GMLInt(9));
+
+        let mut obj = GMLObject { pairs: Vec::new() };
+        let entry = obj.entry("count");
+        assert_eq!(entry.key(), "count");
+        assert_eq!(entry.or_insert(GMLValue::GMLInt(0)), &GMLValue::GMLInt(0));
+        let increment = |value: &mut GMLValue| {
+            if let GMLValue::GMLInt(n) = value {
+                *n += 1;
+            }
+        };
+        obj.entry("count")
+            .and_modify(increment)
+            .or_insert(GMLValue::GMLInt(0));
+        obj.entry("count").and_modify(increment);
+        assert_eq!(obj.get("count"), Some(&GMLValue::GMLInt(2)));
+
+        // and_modify doesn't add the pair and or_insert_with is lazy
+        obj.entry("label").and_modify(|_| unreachable!());
+        assert_eq!(obj.pairs.len(), 1);
+        obj.entry("count").or_insert_with(|| unreachable!());
+        obj.entry("label").or_insert_with(|| "a".into());
+        assert_eq!(
+            obj.pairs[1],
+            ("label".to_owned(), GMLValue::GMLString("a".into()))
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    fmt::{self, Debug, Display, Write},
+    io,
+    sync::Arc,
+};
+
+use crate::{GMLType, GMLValue};
+
+/// Convenience alias used by every fallible function in this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Deprecated name of [Error], kept so existing code keeps compiling.
+#[deprecated(since = "0.2.0", note = "use `gml_parser::Error` instead")]
+pub type GMLError = Error;
+
+/// A cheap, copyable description of what went wrong.
+///
+/// More kinds may be added in the future so always include a wildcard arm when matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The text is not valid GML
+    Syntax,
+    /// A key required to build a [Graph](crate::Graph), [Node](crate::Node) or [Edge](crate::Edge) is missing
+    MissingKey,
+    /// A key was present but its value had an unexpected type or was out of range
+    WrongType,
+    /// The graph was built but is not consistent, see [Graph::validate](crate::Graph::validate)
+    Validation,
+    /// The document exceeds one of the [ParseLimits](crate::ParseLimits)
+    Limit,
+    /// A key is not expected, see [UnknownKeyPolicy](crate::UnknownKeyPolicy)
+    UnknownKey,
+    /// A key can't be written as GML, see [InvalidKeyPolicy](crate::InvalidKeyPolicy)
+    InvalidKey,
+    /// The input could not be read
+    Io,
+    /// The input looked compressed but could not be decompressed
+    Decompression,
+    /// Anything else
+    Other,
+}
+
+/// Everything that can go wrong in this crate.
+///
+/// Every value and piece of text embedded in the message, such as the value found by
+/// [Error::WrongType], a key or the message of [Error::Syntax], is cut to
+/// [Error::MAX_VALUE_LEN] characters followed by its full length, so a huge string or
+/// object doesn't flood the logs. The precision of the format sets another limit:
+///
+/// ```
+/// use gml_parser::{Error, Graph};
+///
+/// let text = format!("graph [\nnode [\nid \"{}\"\n]\n]", "x".repeat(1000));
+/// let err = Graph::from_str(&text).unwrap_err();
+/// assert!(err.to_string().len() < 300);
+/// assert!(format!("{:.12}", err).contains("GMLString(\"x… (1013 chars)"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The text could not be parsed as GML
+    Syntax(String),
+    /// `element` is missing the required `key`
+    MissingKey { element: String, key: String },
+    /// `key` in `element` was expected to be one of the `expected` types but `found` was provided
+    WrongType {
+        element: String,
+        key: String,
+        expected: &'static [GMLType],
+        found: GMLValue,
+    },
+    /// Several nodes share this id
+    DuplicateNodeId(i64),
+    /// The edge refers to a node that doesn't exist
+    DanglingEdge { source: i64, target: i64 },
+    /// The edge connects two nodes of the same partition
+    NotBipartite { source: i64, target: i64 },
+    /// The `index`-th `element [...]` object of its parent, starting on `line`,
+    /// has more than `limit` pairs
+    TooManyPairs {
+        element: String,
+        index: usize,
+        line: usize,
+        limit: usize,
+    },
+    /// `key` of `element` is a flag but its `value` is neither 0 nor 1,
+    /// see [ParseOptions::strict](crate::ParseOptions::strict)
+    NotABool {
+        element: String,
+        key: String,
+        value: i64,
+    },
+    /// The graph has `count` nodes, more than the `limit` of the operation. When
+    /// [ParseLimits::max_nodes](crate::ParseLimits::max_nodes) is crossed while parsing the
+    /// text, counting stops there and `count` is `limit + 1`.
+    TooManyNodes { count: usize, limit: usize },
+    /// The graph has `count` edges, more than
+    /// [ParseLimits::max_edges](crate::ParseLimits::max_edges). When it is crossed while
+    /// parsing the text, counting stops there and `count` is `limit + 1`.
+    TooManyEdges { count: usize, limit: usize },
+    /// `key` of `element` is not expected, see [UnknownKeyPolicy](crate::UnknownKeyPolicy).
+    /// `suggestion` is the closest expected key, if any is close enough.
+    UnknownKey {
+        element: String,
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// `key` of `element` can't be written as GML, see [InvalidKeyPolicy](crate::InvalidKeyPolicy)
+    InvalidKey { element: String, key: String },
+    /// Reading the input failed
+    Io(IoError),
+    /// The input is not a valid gzip stream
+    Gzip(IoError),
+    /// Anything that doesn't fit the other variants
+    Other(String),
+}
+
+impl Error {
+    /// The number of characters of each embedded value or text kept by [Display] unless the
+    /// format has a precision, see [Error]
+    pub const MAX_VALUE_LEN: usize = 200;
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Syntax(_) => ErrorKind::Syntax,
+            Error::MissingKey { .. } => ErrorKind::MissingKey,
+            Error::WrongType { .. } | Error::NotABool { .. } => ErrorKind::WrongType,
+            Error::DuplicateNodeId(_) | Error::DanglingEdge { .. } | Error::NotBipartite { .. } => {
+                ErrorKind::Validation
+            }
+            Error::TooManyPairs { .. }
+            | Error::TooManyNodes { .. }
+            | Error::TooManyEdges { .. } => ErrorKind::Limit,
+            Error::UnknownKey { .. } => ErrorKind::UnknownKey,
+            Error::InvalidKey { .. } => ErrorKind::InvalidKey,
+            Error::Io(_) => ErrorKind::Io,
+            Error::Gzip(_) => ErrorKind::Decompression,
+            Error::Other(_) => ErrorKind::Other,
+        }
+    }
+    pub fn is_syntax(&self) -> bool {
+        self.kind() == ErrorKind::Syntax
+    }
+    pub fn is_missing_key(&self) -> bool {
+        self.kind() == ErrorKind::MissingKey
+    }
+    pub(crate) fn missing_key(element: &str, key: &str) -> Self {
+        Error::MissingKey {
+            element: element.to_owned(),
+            key: key.to_owned(),
+        }
+    }
+    pub(crate) fn invalid_key(element: &str, key: &str) -> Self {
+        Error::InvalidKey {
+            element: element.to_owned(),
+            key: key.to_owned(),
+        }
+    }
+    pub(crate) fn wrong_type(element: &str, key: &str, expected: GMLType, found: GMLValue) -> Self {
+        Self::wrong_types(element, key, expected.as_slice(), found)
+    }
+    pub(crate) fn wrong_types(
+        element: &str,
+        key: &str,
+        expected: &'static [GMLType],
+        found: GMLValue,
+    ) -> Self {
+        Error::WrongType {
+            element: element.to_owned(),
+            key: key.to_owned(),
+            expected,
+            found,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) | Error::Gzip(err) => Some(err.get_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = f.precision().unwrap_or(Error::MAX_VALUE_LEN);
+        let cut = |text| TruncatedText(text, max);
+        match self {
+            Error::Syntax(msg) => write!(
+                f,
+                "GMLError: Failed to parse GML! (syntactic): {}",
+                cut(msg)
+            ),
+            Error::MissingKey { element, key } => {
+                write!(
+                    f,
+                    "GMLError: Unable to parse {} from {}",
+                    cut(key),
+                    cut(element)
+                )
+            }
+            Error::WrongType {
+                element,
+                key,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "GMLError: Failed to parse {} {}: {}. Expected ",
+                    cut(element),
+                    cut(key),
+                    Truncated(found, max)
+                )?;
+                for (i, gml_type) in expected.iter().enumerate() {
+                    if i + 1 == expected.len() && i > 0 {
+                        write!(f, " or ")?;
+                    } else if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", gml_type)?;
+                }
+                write!(f, " but found invalid type.")
+            }
+            Error::DuplicateNodeId(id) => write!(f, "GMLError: Duplicate node id {}", id),
+            Error::DanglingEdge { source, target } => write!(
+                f,
+                "GMLError: Edge {} -> {} refers to a node that doesn't exist",
+                source, target
+            ),
+            Error::NotBipartite { source, target } => write!(
+                f,
+                "GMLError: Edge {} -> {} connects two nodes of the same partition",
+                source, target
+            ),
+            Error::TooManyPairs {
+                element,
+                index,
+                line,
+                limit,
+            } => write!(
+                f,
+                "GMLError: {} #{} starting on line {} has more than {} pairs",
+                cut(element),
+                index,
+                line,
+                limit
+            ),
+            Error::NotABool {
+                element,
+                key,
+                value,
+            } => write!(
+                f,
+                "GMLError: {} of {} should be 0 or 1 but is {}",
+                cut(key),
+                cut(element),
+                value
+            ),
+            Error::TooManyNodes { count, limit } => write!(
+                f,
+                "GMLError: The graph has {} nodes, more than the limit of {}",
+                count, limit
+            ),
+            Error::TooManyEdges { count, limit } => write!(
+                f,
+                "GMLError: The graph has {} edges, more than the limit of {}",
+                count, limit
+            ),
+            Error::UnknownKey {
+                element,
+                key,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "GMLError: Unknown key `{}` in {}",
+                    cut(key),
+                    cut(element)
+                )?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean `{}`?", cut(suggestion)),
+                    None => Ok(()),
+                }
+            }
+            Error::InvalidKey { element, key } => write!(
+                f,
+                "GMLError: The key {} of {} can't be written as GML",
+                Truncated(key, max),
+                cut(element)
+            ),
+            Error::Io(err) => write!(
+                f,
+                "GMLError: Failed to read GML: {}",
+                TruncatedText(err, max)
+            ),
+            Error::Gzip(err) => write!(
+                f,
+                "GMLError: Corrupt gzip stream: {}",
+                TruncatedText(err, max)
+            ),
+            Error::Other(msg) => write!(f, "GMLError: {}", cut(msg)),
+        }
+    }
+}
+
+// The Debug form of a value, cut after `max` chars
+struct Truncated<'a, T>(&'a T, usize);
+
+impl<T: Debug> Display for Truncated<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_truncated(f, self.1, format_args!("{:?}", self.0))
+    }
+}
+
+// The Display form of a value, cut after `max` chars
+struct TruncatedText<'a, T>(&'a T, usize);
+
+impl<T: Display> Display for TruncatedText<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_truncated(f, self.1, format_args!("{}", self.0))
+    }
+}
+
+// Write the first `max` chars of `args`, followed by the full length if that cut any
+fn write_truncated(f: &mut fmt::Formatter<'_>, max: usize, args: fmt::Arguments) -> fmt::Result {
+    let mut limited = Limited {
+        f,
+        remaining: max,
+        total: 0,
+    };
+    limited.write_fmt(args)?;
+    let total = limited.total;
+    if total > max {
+        write!(f, "… ({} chars)", total)?;
+    }
+    Ok(())
+}
+
+// Passes on the first `remaining` chars and counts all of them
+struct Limited<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    remaining: usize,
+    total: usize,
+}
+
+impl Write for Limited<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let count = s.chars().count();
+        self.total += count;
+        if self.remaining >= count {
+            self.remaining -= count;
+            self.f.write_str(s)
+        } else if self.remaining > 0 {
+            let end = s
+                .char_indices()
+                .nth(self.remaining)
+                .map_or(s.len(), |(i, _)| i);
+            self.remaining = 0;
+            self.f.write_str(&s[..end])
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err.into())
+    }
+}
+
+/// An [io::Error] that can be cloned and compared, held by [Error::Io] and [Error::Gzip].
+///
+/// Two errors are equal when they have the same [io::ErrorKind] and message.
+#[derive(Debug, Clone)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+    pub fn get_ref(&self) -> &io::Error {
+        &self.0
+    }
+}
+
+impl From<io::Error> for IoError {
+    fn from(err: io::Error) -> Self {
+        IoError(Arc::new(err))
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// A problem in the document that doesn't prevent it from being converted,
+/// see [Graph::warnings](crate::Graph::warnings).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// `key` of `element` is a flag but its `value` is neither 0 nor 1
+    NotABool {
+        element: String,
+        key: String,
+        value: i64,
+    },
+    /// `count` values of `key` were left out because of
+    /// [ParseOptions::skip_keys](crate::ParseOptions::skip_keys)
+    SkippedKey { key: String, count: usize },
+    /// `key` of `element` is not expected, see [UnknownKeyPolicy](crate::UnknownKeyPolicy).
+    /// `suggestion` is the closest expected key, if any is close enough.
+    UnknownKey {
+        element: String,
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// The brackets of the document are unbalanced, so only part of it was read, see
+    /// [ParseOptions::recover_brackets](crate::ParseOptions::recover_brackets).
+    /// The text from `truncated_at_line` on was dropped, if any, and `closed` missing `]`
+    /// were added at the end.
+    RecoveredBrackets {
+        closed: usize,
+        truncated_at_line: Option<usize>,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::NotABool {
+                element,
+                key,
+                value,
+            } => write!(
+                f,
+                "GMLWarning: {} of {} should be 0 or 1 but is {}",
+                key, element, value
+            ),
+            Warning::SkippedKey { key, count } => {
+                write!(f, "GMLWarning: Skipped {} values of {}", count, key)
+            }
+            Warning::UnknownKey {
+                element,
+                key,
+                suggestion,
+            } => {
+                write!(f, "GMLWarning: Unknown key `{}` in {}", key, element)?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean `{}`?", suggestion),
+                    None => Ok(()),
+                }
+            }
+            Warning::RecoveredBrackets {
+                closed,
+                truncated_at_line,
+            } => {
+                write!(f, "GMLWarning: Unbalanced brackets")?;
+                if let Some(line) = truncated_at_line {
+                    write!(f, ", dropped the text from line {} on", line)?;
+                }
+                match (closed, truncated_at_line) {
+                    (0, _) => Ok(()),
+                    (_, Some(_)) => write!(f, " and added {} missing `]`", closed),
+                    (_, None) => write!(f, ", added {} missing `]`", closed),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,509 @@
+//! Exporters from a [Graph] into formats understood by other tools.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    io,
+};
+
+use crate::{
+    coerce, DirectednessPolicy, Edge, Error, GMLValue, Graph, Node, ReadableGMLAttributes, Result,
+};
+
+/// Settings for [Graph::write_adjacency_list_with].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdjacencyListOptions {
+    /// Write each edge of an undirected graph only under its source instead of under both endpoints
+    pub single_sided: bool,
+    /// Precede the line of every labeled node with a `# id label` comment
+    pub label_comments: bool,
+    /// Decides which edges are undirected and listed under both endpoints
+    pub directedness: DirectednessPolicy,
+}
+
+impl Graph {
+    /// Render the nodes as CSV with the header `id,label,<attrs...>`.
+    ///
+    /// Only scalar attributes are written. Missing attributes and object values
+    /// are left empty.
+    pub fn to_csv_nodes(&self, attrs: &[&str]) -> String {
+        let mut out = String::new();
+        csv_header(&mut out, &["id", "label"], attrs);
+        for node in &self.nodes {
+            let mut row = vec![node.id.to_string(), node.label.clone().unwrap_or_default()];
+            row.extend(attrs.iter().map(|name| csv_attr(node.get_attribute(name))));
+            csv_row(&mut out, &row);
+        }
+        out
+    }
+    /// Render the edges as CSV with the header `source,target,label,<attrs...>`.
+    ///
+    /// Only scalar attributes are written. Missing attributes and object values
+    /// are left empty.
+    pub fn to_csv_edges(&self, attrs: &[&str]) -> String {
+        let mut out = String::new();
+        csv_header(&mut out, &["source", "target", "label"], attrs);
+        for edge in &self.edges {
+            let mut row = vec![
+                edge.source.to_string(),
+                edge.target.to_string(),
+                edge.label.clone().unwrap_or_default(),
+            ];
+            row.extend(attrs.iter().map(|name| csv_attr(edge.get_attribute(name))));
+            csv_row(&mut out, &row);
+        }
+        out
+    }
+}
+
+impl Graph {
+    /// Write the graph as an adjacency list with one `src: dst1 w1 dst2 w2 ...` line per node.
+    ///
+    /// See [Graph::write_adjacency_list_with].
+    pub fn write_adjacency_list<W: io::Write>(
+        &self,
+        w: W,
+        weight_attr: Option<&str>,
+    ) -> io::Result<()> {
+        self.write_adjacency_list_with(w, weight_attr, &AdjacencyListOptions::default())
+    }
+    /// Write the graph as an adjacency list with one `src: dst1 w1 dst2 w2 ...` line per node.
+    ///
+    /// Nodes are written in id order and every node gets a line, even without edges. The
+    /// destinations of a line are in id order too, with parallel edges in the order of
+    /// [Graph::edges], so the output doesn't depend on the order of the edges otherwise.
+    /// When `weight_attr` is given every destination is followed by the value of that
+    /// edge attribute, or `1` if the edge doesn't have it. Undirected edges, see
+    /// [AdjacencyListOptions::directedness], are listed under both endpoints
+    /// (see [AdjacencyListOptions::single_sided]).
+    pub fn write_adjacency_list_with<W: io::Write>(
+        &self,
+        mut w: W,
+        weight_attr: Option<&str>,
+        options: &AdjacencyListOptions,
+    ) -> io::Result<()> {
+        let mut lists: BTreeMap<i64, Vec<(i64, Option<String>)>> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id, Vec::new()))
+            .collect();
+        for edge in &self.edges {
+            let both_sides =
+                !options.single_sided && !self.is_edge_directed(edge, options.directedness);
+            let weight = weight_attr.map(|name| match edge.get_attribute(name) {
+                Some((_, value)) if value.as_f64().is_some() => coerce::to_string_lossy(value)
+                    .expect("numbers convert")
+                    .into_owned(),
+                _ => "1".into(),
+            });
+            if both_sides && edge.source != edge.target {
+                lists
+                    .entry(edge.target)
+                    .or_default()
+                    .push((edge.source, weight.clone()));
+            }
+            lists
+                .entry(edge.source)
+                .or_default()
+                .push((edge.target, weight));
+        }
+        let labels: HashMap<i64, &str> = self
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node.id, node.label.as_deref()?)))
+            .collect();
+        for (source, mut targets) in lists {
+            // Stable, so parallel edges keep their order
+            targets.sort_by_key(|(target, _)| *target);
+            if options.label_comments {
+                if let Some(label) = labels.get(&source) {
+                    writeln!(w, "# {} {}", source, label)?;
+                }
+            }
+            write!(w, "{}:", source)?;
+            for (target, weight) in targets {
+                write!(w, " {}", target)?;
+                if let Some(weight) = weight {
+                    write!(w, " {}", weight)?;
+                }
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+    /// Read an adjacency list as written by [Graph::write_adjacency_list].
+    ///
+    /// When `weight_attr` is given every destination must be followed by a weight which is
+    /// stored in that edge attribute. `# id label` comments set the label of the node.
+    /// For undirected graphs an edge listed under both of its endpoints is only added once,
+    /// so lists written with or without [AdjacencyListOptions::single_sided] both read back
+    /// to the same edges.
+    pub fn from_adjacency_list(
+        text: &str,
+        weight_attr: Option<&str>,
+        directed: bool,
+    ) -> Result<Graph> {
+        let mut graph = Graph::new();
+        graph.directed = Some(directed);
+        let mut labels: HashMap<i64, String> = HashMap::new();
+        // Undirected edges seen from one side that still expect their mirror entry
+        let mut unmatched: HashMap<(i64, i64), usize> = HashMap::new();
+        let invalid = |line: &str| Error::Syntax(format!("Invalid adjacency list line {:?}", line));
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut parts = comment.trim().splitn(2, ' ');
+                if let (Some(id), Some(label)) = (parts.next(), parts.next()) {
+                    if let Ok(id) = id.parse() {
+                        labels.insert(id, label.to_owned());
+                    }
+                }
+                continue;
+            }
+            let (source, rest) = line.split_once(':').ok_or_else(|| invalid(line))?;
+            let source: i64 = source.trim().parse().map_err(|_| invalid(line))?;
+            graph.nodes.push(Node::new(source));
+            let mut tokens = rest.split_whitespace();
+            while let Some(target) = tokens.next() {
+                let target: i64 = target.parse().map_err(|_| invalid(line))?;
+                let mut edge = Edge::new(source, target);
+                if let Some(name) = weight_attr {
+                    let weight = tokens.next().ok_or_else(|| invalid(line))?;
+                    let weight = match weight.parse::<i64>() {
+                        Ok(i) => GMLValue::GMLInt(i),
+                        Err(_) => GMLValue::GMLFloat(weight.parse().map_err(|_| invalid(line))?),
+                    };
+                    edge.attrs.push((name.to_owned(), weight));
+                }
+                if !directed && source != target {
+                    if let Some(count) = unmatched.get_mut(&(target, source)) {
+                        if *count > 0 {
+                            *count -= 1;
+                            continue;
+                        }
+                    }
+                    *unmatched.entry((source, target)).or_default() += 1;
+                }
+                graph.edges.push(edge);
+            }
+        }
+        for node in &mut graph.nodes {
+            node.label = labels.remove(&node.id);
+        }
+        Ok(graph)
+    }
+}
+
+impl Graph {
+    /// The number of edges between every pair of nodes.
+    ///
+    /// See [Graph::weighted_adjacency_matrix] for the layout of the result.
+    pub fn adjacency_matrix(&self) -> (Vec<i64>, Vec<Vec<usize>>) {
+        let (ids, matrix) = self.matrix_with(|_| 1.0);
+        let matrix = matrix
+            .into_iter()
+            .map(|row| row.into_iter().map(|count| count as usize).collect())
+            .collect();
+        (ids, matrix)
+    }
+    /// The sum of the `weight_attr` of the edges between every pair of nodes.
+    ///
+    /// Returns the node ids in increasing order along with the matrix, where `matrix[i][j]`
+    /// is the total weight of the edges from `ids[i]` to `ids[j]`. Cells without an edge are
+    /// `0.0` and an edge whose attribute is missing or not numeric counts as `1.0`.
+    /// Unless [Graph::directed] is `Some(true)` the matrix is symmetric.
+    /// Edges referring to missing nodes are ignored.
+    pub fn weighted_adjacency_matrix(&self, weight_attr: &str) -> (Vec<i64>, Vec<Vec<f64>>) {
+        self.matrix_with(|edge| edge.attr_f64(weight_attr).unwrap_or(1.0))
+    }
+    /// The edges in the MatrixMarket coordinate format read by numeric tools such as SciPy.
+    ///
+    /// Node ids are numbered from 1 in increasing order, and the matrix has one row and one
+    /// column per node. Every edge is written as its own entry, so parallel edges appear
+    /// several times, which most readers sum up. Unless [Graph::directed] is `Some(true)`
+    /// the matrix is declared `symmetric` and each edge is written in the lower triangle.
+    /// Edges referring to missing nodes are left out.
+    ///
+    /// With `weight_attr` the entries are `real` values of that attribute, with missing or
+    /// non-numeric values written as `1`, otherwise the matrix is a `pattern`. A weight that is
+    /// NaN or infinite can't be read back by most tools and fails with [Error::Other].
+    pub fn to_matrix_market(&self, weight_attr: Option<&str>) -> Result<String> {
+        let mut ids: Vec<i64> = self.nodes.iter().map(|node| node.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let indices: HashMap<i64, usize> =
+            ids.iter().enumerate().map(|(i, id)| (*id, i + 1)).collect();
+        let symmetric = self.directed != Some(true);
+        let mut entries = String::new();
+        let mut count = 0;
+        for edge in &self.edges {
+            let (Some(&source), Some(&target)) =
+                (indices.get(&edge.source), indices.get(&edge.target))
+            else {
+                continue;
+            };
+            let (row, column) = if symmetric {
+                (source.max(target), source.min(target))
+            } else {
+                (source, target)
+            };
+            count += 1;
+            match weight_attr {
+                Some(key) => {
+                    let weight = edge.attr_f64(key).unwrap_or(1.0);
+                    if !weight.is_finite() {
+                        return Err(Error::Other(format!(
+                            "The {} {} of edge {} -> {} can't be written as MatrixMarket",
+                            key, weight, edge.source, edge.target
+                        )));
+                    }
+                    writeln!(entries, "{} {} {}", row, column, weight)
+                }
+                None => writeln!(entries, "{} {}", row, column),
+            }
+            .unwrap();
+        }
+        Ok(format!(
+            "%%MatrixMarket matrix coordinate {} {}\n{} {} {}\n{}",
+            if weight_attr.is_some() {
+                "real"
+            } else {
+                "pattern"
+            },
+            if symmetric { "symmetric" } else { "general" },
+            ids.len(),
+            ids.len(),
+            count,
+            entries
+        ))
+    }
+    fn matrix_with<F: Fn(&Edge) -> f64>(&self, weight: F) -> (Vec<i64>, Vec<Vec<f64>>) {
+        let mut ids: Vec<i64> = self.nodes.iter().map(|node| node.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let indices: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut matrix = vec![vec![0.0; ids.len()]; ids.len()];
+        let symmetric = self.directed != Some(true);
+        for edge in &self.edges {
+            let (Some(&source), Some(&target)) =
+                (indices.get(&edge.source), indices.get(&edge.target))
+            else {
+                continue;
+            };
+            let weight = weight(edge);
+            matrix[source][target] += weight;
+            if symmetric && source != target {
+                matrix[target][source] += weight;
+            }
+        }
+        (ids, matrix)
+    }
+}
+
+fn csv_attr(attr: Option<&(String, GMLValue)>) -> String {
+    attr.and_then(|(_, value)| coerce::to_string_lossy(value))
+        .map_or_else(String::new, |s| s.into_owned())
+}
+
+fn csv_header(out: &mut String, fixed: &[&str], attrs: &[&str]) {
+    let header: Vec<String> = fixed
+        .iter()
+        .chain(attrs.iter())
+        .map(|s| s.to_string())
+        .collect();
+    csv_row(out, &header);
+}
+
+fn csv_row(out: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&csv_escape(field));
+    }
+    out.push('\n');
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn wikipedia() -> Graph {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        Graph::from_gml(GMLObject::from_str(&file).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn csv_nodes_and_edges() {
+        let graph = wikipedia();
+        let nodes = graph.to_csv_nodes(&["thisIsASampleAttribute", "missing"]);
+        let mut lines = nodes.lines();
+        assert_eq!(
+            lines.next(),
+            Some("id,label,thisIsASampleAttribute,missing")
+        );
+        assert_eq!(nodes.lines().count(), 4);
+        assert!(nodes.contains("\n1,node 1,42,\n"));
+        assert!(nodes.contains("\n3,node 3,44,\n"));
+        let edges = graph.to_csv_edges(&[]);
+        let mut lines = edges.lines();
+        assert_eq!(lines.next(), Some("source,target,label"));
+        assert_eq!(edges.lines().count(), 4);
+        assert!(edges.contains("1,2,Edge from node 1 to node 2\n"));
+    }
+
+    fn edge_set(graph: &Graph, weight: &str) -> Vec<(i64, i64, Option<f64>)> {
+        let mut edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|e| {
+                (
+                    e.source.min(e.target),
+                    e.source.max(e.target),
+                    e.attr_f64(weight),
+                )
+            })
+            .collect();
+        edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        edges
+    }
+
+    #[test]
+    fn adjacency_list_round_trip() {
+        let mut graph = wikipedia();
+        graph.directed = None;
+        graph.edges[0]
+            .attrs
+            .push(("w".into(), GMLValue::GMLFloat(0.5)));
+        graph.edges[1].attrs.push(("w".into(), GMLValue::GMLInt(3)));
+        graph.edges[2].attrs.push(("w".into(), GMLValue::GMLInt(1)));
+
+        let mut out = Vec::new();
+        graph.write_adjacency_list(&mut out, Some("w")).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "1: 2 0.5 3 1\n2: 1 0.5 3 3\n3: 1 1 2 3\n");
+        // The order of the edges doesn't show in the output
+        let mut reversed = graph.clone();
+        reversed.edges.reverse();
+        let mut out = Vec::new();
+        reversed.write_adjacency_list(&mut out, Some("w")).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), text);
+        let read = Graph::from_adjacency_list(&text, Some("w"), false).unwrap();
+        assert_eq!(read.nodes.len(), 3);
+        assert_eq!(edge_set(&read, "w"), edge_set(&graph, "w"));
+
+        let options = AdjacencyListOptions {
+            single_sided: true,
+            label_comments: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        graph
+            .write_adjacency_list_with(&mut out, None, &options)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "# 1 node 1\n1: 2\n# 2 node 2\n2: 3\n# 3 node 3\n3: 1\n"
+        );
+        let read = Graph::from_adjacency_list(&text, None, false).unwrap();
+        assert_eq!(read.edges.len(), 3);
+        assert_eq!(read.nodes[2].label, Some("node 3".into()));
+
+        graph.directed = Some(true);
+        let mut out = Vec::new();
+        graph.write_adjacency_list(&mut out, None).unwrap();
+        let read =
+            Graph::from_adjacency_list(std::str::from_utf8(&out).unwrap(), None, true).unwrap();
+        let edges: Vec<_> = read.edges.iter().map(|e| (e.source, e.target)).collect();
+        assert_eq!(edges, vec![(1, 2), (2, 3), (3, 1)]);
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let root =
+            GMLObject::from_str("graph [\nnode [\nid 1\nlabel 'say \"hi\", ok'\n]\n]").unwrap();
+        let graph = Graph::from_gml(root).unwrap();
+        assert_eq!(
+            graph.to_csv_nodes(&[]),
+            "id,label\n1,\"say \"\"hi\"\", ok\"\n"
+        );
+    }
+
+    #[test]
+    fn adjacency_matrices() {
+        let file = fs::read_to_string("tests/weighted.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let (ids, matrix) = graph.weighted_adjacency_matrix("weight");
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(
+            matrix,
+            vec![
+                vec![0.0, 3.5, 0.0],
+                vec![0.0, 0.0, 1.0],
+                vec![1.0, 0.0, 4.0]
+            ]
+        );
+        let (_, counts) = graph.adjacency_matrix();
+        assert_eq!(counts, vec![vec![0, 2, 0], vec![0, 0, 1], vec![1, 0, 1]]);
+
+        graph.directed = Some(false);
+        let (_, matrix) = graph.weighted_adjacency_matrix("weight");
+        assert_eq!(matrix[1][0], 3.5);
+        assert_eq!(matrix[0][2], 1.0);
+        assert_eq!(matrix[2][2], 4.0);
+    }
+
+    #[test]
+    fn matrix_market() {
+        let file = fs::read_to_string("tests/weighted.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let text = graph.to_matrix_market(Some("weight")).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some("%%MatrixMarket matrix coordinate real general")
+        );
+        assert_eq!(lines.next(), Some("3 3 5"));
+        // Missing and non-numeric weights are written as 1
+        assert_eq!(
+            lines.collect::<Vec<_>>(),
+            ["1 2 2.5", "1 2 1", "2 3 1", "3 3 4", "3 1 1"]
+        );
+
+        graph.directed = None;
+        graph.nodes[2].id = 30;
+        for edge in &mut graph.edges {
+            for end in [&mut edge.source, &mut edge.target] {
+                if *end == 3 {
+                    *end = 30;
+                }
+            }
+        }
+        graph.edges.push(Edge::new(30, 99));
+        let text = graph.to_matrix_market(None).unwrap();
+        assert_eq!(
+            text,
+            "%%MatrixMarket matrix coordinate pattern symmetric\n3 3 5\n2 1\n2 1\n3 2\n3 3\n3 1\n"
+        );
+
+        for weight in [f64::NAN, f64::INFINITY] {
+            graph.edges[0].set_attribute("weight", weight);
+            let err = graph.to_matrix_market(Some("weight")).unwrap_err();
+            assert!(matches!(err, Error::Other(_)), "{}", err);
+            assert!(graph.to_matrix_market(None).is_ok());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// Conversion between nested objects and dotted keys, see HasGMLAttributes::flatten
+
+use std::collections::HashSet;
+
+use crate::{GMLObject, GMLValue};
+
+pub(crate) fn flatten_pairs(pairs: Vec<(String, GMLValue)>) -> Vec<(String, GMLValue)> {
+    let mut flat = Vec::with_capacity(pairs.len());
+    for (key, value) in pairs {
+        match value {
+            GMLValue::GMLObject(obj) if !obj.pairs.is_empty() => {
+                for (sub, value) in flatten_pairs(obj.pairs) {
+                    flat.push((format!("{}.{}", key, sub), value));
+                }
+            }
+            value => flat.push((key, value)),
+        }
+    }
+    flat
+}
+
+pub(crate) fn unflatten_pairs(pairs: Vec<(String, GMLValue)>) -> Vec<(String, GMLValue)> {
+    // Keys used by scalars, whose dotted keys must stay as they are
+    let scalars: HashSet<String> = pairs
+        .iter()
+        .filter(|(key, value)| !key.contains('.') && !matches!(value, GMLValue::GMLObject(_)))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let mut nested: Vec<(String, GMLValue)> = Vec::with_capacity(pairs.len());
+    let mut blocked = Vec::new();
+    // Objects built from dotted keys, which plain objects with the same key are merged into
+    let mut from_dots: HashSet<String> = HashSet::new();
+    for (key, value) in pairs {
+        let dotted = key.contains('.');
+        let (prefix, value) = match key.split_once('.') {
+            Some((prefix, _)) if scalars.contains(prefix) => {
+                blocked.push((key, value));
+                continue;
+            }
+            Some((prefix, rest)) => {
+                let pairs = vec![(rest.to_owned(), value)];
+                (prefix, GMLValue::GMLObject(Box::new(GMLObject { pairs })))
+            }
+            None => (key.as_str(), value),
+        };
+        let mergeable = dotted || from_dots.contains(prefix);
+        let existing = nested.iter_mut().find(|(k, v)| {
+            mergeable
+                && k == prefix
+                && matches!(v, GMLValue::GMLObject(_))
+                && matches!(value, GMLValue::GMLObject(_))
+        });
+        if dotted {
+            from_dots.insert(prefix.to_owned());
+        }
+        match (existing, value) {
+            (Some((_, GMLValue::GMLObject(obj))), GMLValue::GMLObject(more)) => {
+                obj.pairs.extend(more.pairs)
+            }
+            (_, value) => nested.push((prefix.to_owned(), value)),
+        }
+    }
+    for (_, value) in &mut nested {
+        if let GMLValue::GMLObject(obj) = value {
+            obj.pairs = unflatten_pairs(std::mem::take(&mut obj.pairs));
+        }
+    }
+    nested.extend(blocked);
+    nested
+}
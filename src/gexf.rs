@@ -0,0 +1,215 @@
+//! Export to GEXF, the native format of Gephi.
+
+use std::fmt::Write;
+
+use crate::{coerce, GMLType, GMLValue, Graph, HasGMLAttributes};
+
+impl Graph {
+    /// Render the graph as a GEXF 1.3 document.
+    ///
+    /// Nodes keep their id and label, edges get their index in [Graph::edges] as id.
+    /// Every scalar attribute becomes an attribute column, typed `long` when all of its
+    /// values are ints, `double` when they are all numbers and `string` otherwise.
+    /// Object attributes such as `graphics` are left out.
+    pub fn to_gexf(&self) -> String {
+        let mut out = String::new();
+        let mode = if self.directed == Some(true) {
+            "directed"
+        } else {
+            "undirected"
+        };
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        writeln!(
+            out,
+            "  <graph mode=\"static\" defaultedgetype=\"{}\">",
+            mode
+        )
+        .unwrap();
+        let node_columns = columns(self.nodes.iter().map(|node| node.attributes()));
+        let edge_columns = columns(self.edges.iter().map(|edge| edge.attributes()));
+        write_columns(&mut out, "node", &node_columns);
+        write_columns(&mut out, "edge", &edge_columns);
+
+        out.push_str("    <nodes>\n");
+        for node in &self.nodes {
+            write!(out, "      <node id=\"{}\"", node.id).unwrap();
+            if let Some(label) = &node.label {
+                write!(out, " label=\"{}\"", escape(label)).unwrap();
+            }
+            write_values(&mut out, "node", node.attributes(), &node_columns);
+        }
+        out.push_str("    </nodes>\n");
+
+        out.push_str("    <edges>\n");
+        for (i, edge) in self.edges.iter().enumerate() {
+            write!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\"",
+                i, edge.source, edge.target
+            )
+            .unwrap();
+            if let Some(label) = &edge.label {
+                write!(out, " label=\"{}\"", escape(label)).unwrap();
+            }
+            write_values(&mut out, "edge", edge.attributes(), &edge_columns);
+        }
+        out.push_str("    </edges>\n");
+        out.push_str("  </graph>\n</gexf>\n");
+        out
+    }
+}
+
+// Title and type of every scalar attribute, in order of first appearance
+fn columns<'a>(
+    records: impl Iterator<Item = &'a Vec<(String, GMLValue)>>,
+) -> Vec<(&'a str, GMLType)> {
+    let mut columns: Vec<(&str, GMLType)> = Vec::new();
+    for attrs in records {
+        for (key, value) in attrs {
+            let gml_type = value.gml_type();
+            if !gml_type.is_scalar() {
+                continue;
+            }
+            match columns.iter_mut().find(|(title, _)| title == key) {
+                Some((_, existing)) => *existing = widen(*existing, gml_type),
+                None => columns.push((key, gml_type)),
+            }
+        }
+    }
+    columns
+}
+
+// The type that can hold values of both types
+fn widen(a: GMLType, b: GMLType) -> GMLType {
+    match (a, b) {
+        _ if a == b => a,
+        _ if a.is_numeric() && b.is_numeric() => GMLType::Float,
+        _ => GMLType::String,
+    }
+}
+
+fn gexf_type(gml_type: GMLType) -> &'static str {
+    match gml_type {
+        GMLType::Int => "long",
+        GMLType::Float => "double",
+        GMLType::String | GMLType::Object | GMLType::List => "string",
+    }
+}
+
+fn write_columns(out: &mut String, class: &str, columns: &[(&str, GMLType)]) {
+    if columns.is_empty() {
+        return;
+    }
+    writeln!(out, "    <attributes class=\"{}\">", class).unwrap();
+    for (i, (title, gml_type)) in columns.iter().enumerate() {
+        writeln!(
+            out,
+            "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+            i,
+            escape(title),
+            gexf_type(*gml_type)
+        )
+        .unwrap();
+    }
+    out.push_str("    </attributes>\n");
+}
+
+// Finishes the opening tag of a node or edge
+fn write_values(
+    out: &mut String,
+    element: &str,
+    attrs: &[(String, GMLValue)],
+    columns: &[(&str, GMLType)],
+) {
+    let mut values = String::new();
+    for (i, (title, _)) in columns.iter().enumerate() {
+        // The first value wins when a key repeats
+        let value = attrs.iter().find(|(key, _)| key == title);
+        let Some(value) = value.and_then(|(_, value)| coerce::to_string_lossy(value)) else {
+            continue;
+        };
+        let value = escape(&value);
+        writeln!(
+            values,
+            "          <attvalue for=\"{}\" value=\"{}\"/>",
+            i, value
+        )
+        .unwrap();
+    }
+    if values.is_empty() {
+        out.push_str("/>\n");
+    } else {
+        out.push_str(">\n        <attvalues>\n");
+        out.push_str(&values);
+        writeln!(out, "        </attvalues>\n      </{}>", element).unwrap();
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn gexf_elements() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.nodes[0]
+            .attrs
+            .push(("size".into(), GMLValue::GMLInt(3)));
+        graph.nodes[1]
+            .attrs
+            .push(("size".into(), GMLValue::GMLFloat(1.5)));
+        graph.nodes[1]
+            .attrs
+            .push(("note".into(), GMLValue::GMLString("a < b & \"c\"".into())));
+        let gexf = graph.to_gexf();
+
+        assert!(gexf.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gexf "));
+        assert!(gexf.ends_with("</graph>\n</gexf>\n"));
+        assert!(gexf.contains("<graph mode=\"static\" defaultedgetype=\"directed\">"));
+        assert!(gexf.contains("<attributes class=\"node\">"));
+        assert!(
+            gexf.contains("<attribute id=\"0\" title=\"thisIsASampleAttribute\" type=\"long\"/>")
+        );
+        assert!(gexf.contains("<attribute id=\"1\" title=\"size\" type=\"double\"/>"));
+        assert!(gexf.contains("<attribute id=\"2\" title=\"note\" type=\"string\"/>"));
+        assert!(!gexf.contains("<attributes class=\"edge\">"));
+        for node in &graph.nodes {
+            let label = node.label.as_deref().unwrap();
+            assert!(gexf.contains(&format!("<node id=\"{}\" label=\"{}\"", node.id, label)));
+        }
+        assert!(gexf.contains("<attvalue for=\"0\" value=\"42\"/>"));
+        assert!(gexf.contains("<attvalue for=\"1\" value=\"3\"/>"));
+        assert!(gexf.contains("value=\"a &lt; b &amp; &quot;c&quot;\""));
+        for (i, edge) in graph.edges.iter().enumerate() {
+            assert!(gexf.contains(&format!(
+                "<edge id=\"{}\" source=\"{}\" target=\"{}\"",
+                i, edge.source, edge.target
+            )));
+        }
+        assert_eq!(gexf.matches("<node ").count(), 3);
+        assert_eq!(gexf.matches("</node>").count(), 3);
+        assert_eq!(gexf.matches("<attvalue ").count(), 6);
+        assert_eq!(gexf.matches("<edge ").count(), graph.edges.len());
+
+        graph.directed = None;
+        assert!(graph.to_gexf().contains("defaultedgetype=\"undirected\""));
+    }
+}
@@ -0,0 +1,117 @@
+//! The type of a value without its contents, see [GMLType].
+
+use std::fmt::Display;
+
+use crate::GMLValue;
+
+/// The type of a [GMLValue] without its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GMLType {
+    Int,
+    Float,
+    String,
+    Object,
+    List,
+}
+
+impl GMLType {
+    /// The name used in error messages: `int`, `float`, `str`, `object` or `list`. Also available
+    /// through [Display].
+    pub fn name(&self) -> &'static str {
+        match self {
+            GMLType::Int => "int",
+            GMLType::Float => "float",
+            GMLType::String => "str",
+            GMLType::Object => "object",
+            GMLType::List => "list",
+        }
+    }
+    /// Whether values of this type are numbers, ints or floats
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, GMLType::Int | GMLType::Float)
+    }
+    /// Whether values of this type are neither objects nor lists
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, GMLType::Object | GMLType::List)
+    }
+    // The type alone, for errors that expect a single type
+    pub(crate) fn as_slice(self) -> &'static [GMLType] {
+        match self {
+            GMLType::Int => &[GMLType::Int],
+            GMLType::Float => &[GMLType::Float],
+            GMLType::String => &[GMLType::String],
+            GMLType::Object => &[GMLType::Object],
+            GMLType::List => &[GMLType::List],
+        }
+    }
+}
+
+impl Display for GMLType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl GMLValue {
+    pub fn gml_type(&self) -> GMLType {
+        match self {
+            GMLValue::GMLInt(_) => GMLType::Int,
+            GMLValue::GMLFloat(_) => GMLType::Float,
+            GMLValue::GMLString(_) => GMLType::String,
+            GMLValue::GMLObject(_) => GMLType::Object,
+            GMLValue::GMLList(_) => GMLType::List,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn value_types() {
+        let values = [
+            (GMLValue::GMLInt(1), GMLType::Int, "int"),
+            (GMLValue::GMLFloat(1.5), GMLType::Float, "float"),
+            (GMLValue::GMLString("a".into()), GMLType::String, "str"),
+            (
+                GMLValue::GMLObject(Box::new(GMLObject { pairs: Vec::new() })),
+                GMLType::Object,
+                "object",
+            ),
+        ];
+        for (value, gml_type, name) in &values {
+            assert_eq!(value.gml_type(), *gml_type);
+            assert_eq!(gml_type.to_string(), *name);
+        }
+        let numeric: Vec<_> = values
+            .iter()
+            .map(|(_, gml_type, _)| gml_type.is_numeric())
+            .collect();
+        assert_eq!(numeric, [true, true, false, false]);
+        assert!(GMLType::String.is_scalar() && !GMLType::Object.is_scalar());
+    }
+
+    #[test]
+    fn wrong_type_errors() {
+        let err = Graph::from_str("graph [\nnode [\nid \"a\"\n]\n]").unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::WrongType { expected: [GMLType::Int], found, .. } if found.gml_type() == GMLType::String
+        ));
+        assert_eq!(
+            err.to_string(),
+            "GMLError: Failed to parse node id: GMLString(\"a\"). Expected int but found invalid type."
+        );
+        let err = GMLVersion::try_from(&GMLValue::GMLObject(Box::new(GMLObject {
+            pairs: Vec::new(),
+        })))
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("Expected int, float or str but found invalid type."));
+        assert!(
+            matches!(err, Error::WrongType { expected, .. } if expected.iter().all(GMLType::is_scalar))
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Reading the graph-level keys of a document without building its nodes and edges,
+//! see [Graph::parse_header].
+
+use pest::iterators::{Pair, Pairs};
+
+use crate::{
+    check_identifier, check_separator, parse_number, parse_text, string_text, Error, GMLObject,
+    GMLType, GMLValue, Graph, ParseOptions, Result, Rule,
+};
+
+/// The graph-level keys of a document and the number of nodes and edges it declares,
+/// returned by [Graph::parse_header].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphHeader {
+    pub id: Option<i64>,
+    /// None when the key is missing or is neither 0 nor 1
+    pub directed: Option<bool>,
+    pub label: Option<String>,
+    pub name: Option<String>,
+    /// `node [...]` blocks directly inside the graph, not counting `node` keys whose value
+    /// isn't an object
+    pub nodes: usize,
+    /// `edge [...]` blocks directly inside the graph, counted like the nodes
+    pub edges: usize,
+}
+
+impl Graph {
+    /// Read the `id`, `directed`, `label` and `name` of the first `graph [...]` object and
+    /// count its nodes and edges, without building them or any other value.
+    ///
+    /// The whole text is still checked to be valid GML, including the checks of keys and
+    /// separators that [GMLObject::from_str] makes. As in [Graph::from_str], the first value of
+    /// a repeated key is used and a key with a value of the wrong type fails with
+    /// [Error::WrongType].
+    ///
+    /// ```
+    /// use gml_parser::Graph;
+    ///
+    /// let header = Graph::parse_header("graph [\nid 3\nnode [\nid 1\n]\n]").unwrap();
+    /// assert_eq!((header.id, header.nodes, header.edges), (Some(3), 1, 0));
+    /// ```
+    pub fn parse_header(text: &str) -> Result<GraphHeader> {
+        Self::parse_header_with(text, &ParseOptions::default())
+    }
+    /// Like [Graph::parse_header], using the given [ParseOptions].
+    ///
+    /// Keys, separators and numbers are read as in [GMLObject::from_str_with], and the graph
+    /// is looked up under [ParseOptions::root_key]. The other options only matter for the
+    /// values that aren't built here and have no effect.
+    pub fn parse_header_with(text: &str, options: &ParseOptions) -> Result<GraphHeader> {
+        let root_key = options.root_key.as_str();
+        let file = parse_text(text)?;
+        check_pairs(file.clone().into_inner(), options)?;
+        let mut root = file.into_inner();
+        let graph = loop {
+            let Some(entry) = root.next() else {
+                return Err(Error::missing_key("root", root_key));
+            };
+            if entry.as_rule() == Rule::identifier
+                && entry.clone().into_inner().as_str() == root_key
+            {
+                break root.next().expect("every key has a value");
+            }
+        };
+        let inner = graph.into_inner().next().expect("values aren't empty");
+        if inner.as_rule() != Rule::object {
+            let found = value_of(inner, options)?;
+            return Err(Error::wrong_type("root", root_key, GMLType::Object, found));
+        }
+
+        let mut header = GraphHeader::default();
+        let mut seen: Vec<&str> = Vec::new();
+        let mut entries = inner.into_inner();
+        while let Some(entry) = entries.next() {
+            let key = entry.into_inner().as_str();
+            let value = entries.next().expect("every key has a value");
+            let inner = value.into_inner().next().expect("values aren't empty");
+            match key {
+                "node" if inner.as_rule() == Rule::object => header.nodes += 1,
+                "edge" if inner.as_rule() == Rule::object => header.edges += 1,
+                "id" | "directed" | "label" | "name" if !seen.contains(&key) => {
+                    seen.push(key);
+                    header.read(key, value_of(inner, options)?)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(header)
+    }
+}
+
+impl GraphHeader {
+    fn read(&mut self, key: &str, value: GMLValue) -> Result<()> {
+        let expected = match key {
+            "id" | "directed" => GMLType::Int,
+            _ => GMLType::String,
+        };
+        match (key, value) {
+            ("id", GMLValue::GMLInt(id)) => self.id = Some(id),
+            ("directed", value @ GMLValue::GMLInt(_)) => self.directed = value.as_bool(),
+            ("label", GMLValue::GMLString(label)) => self.label = Some(label),
+            ("name", GMLValue::GMLString(name)) => self.name = Some(name),
+            (key, value) => return Err(Error::wrong_type("graph", key, expected, value)),
+        }
+        Ok(())
+    }
+}
+
+// The checks GMLObject::parse_with makes on every key and separator, at any depth
+fn check_pairs(pairs: Pairs<'_, Rule>, options: &ParseOptions) -> Result<()> {
+    let mut key = None;
+    for entry in pairs {
+        match entry.as_rule() {
+            Rule::identifier => {
+                check_identifier(&entry, options)?;
+                key = Some(entry.into_inner().as_str());
+            }
+            Rule::value => {
+                check_separator(&entry, key, options)?;
+                let inner = entry.into_inner().next().expect("values aren't empty");
+                if inner.as_rule() == Rule::object {
+                    check_pairs(inner.into_inner(), options)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Objects and lists are only built to be reported in errors
+fn value_of(pair: Pair<'_, Rule>, options: &ParseOptions) -> Result<GMLValue> {
+    match pair.as_rule() {
+        Rule::string => Ok(GMLValue::GMLString(string_text(&pair).to_owned())),
+        Rule::object => Ok(GMLValue::GMLObject(Box::new(GMLObject::from_pairs(
+            pair.into_inner(),
+        )?))),
+        Rule::list => pair
+            .into_inner()
+            .map(|item| value_of(item, options))
+            .collect::<Result<_>>()
+            .map(GMLValue::GMLList),
+        _ => parse_number(pair.as_str(), options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn header_of_wikipedia() {
+        let text = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let header = Graph::parse_header(&text).unwrap();
+        assert_eq!(
+            header,
+            GraphHeader {
+                id: Some(42),
+                directed: Some(true),
+                label: Some("Hello, I am a graph".into()),
+                name: None,
+                nodes: 3,
+                edges: 3,
+            }
+        );
+
+        let text = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let header = Graph::parse_header(&text).unwrap();
+        let graph = Graph::from_str(&text).unwrap();
+        assert_eq!(header.nodes, graph.nodes.len());
+        assert_eq!(header.edges, graph.edges.len());
+        assert_eq!(header.directed, graph.directed);
+
+        let header = Graph::parse_header("Creator \"x\"\ngraph [\ndirected 2\nid 1\nid \"a\"\n]");
+        assert_eq!(header.unwrap().directed, None);
+        let err = Graph::parse_header("graph [\nlabel 1\n]").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+        let err = Graph::parse_header("graph [\nname [\n]\n]").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+        assert!(Graph::parse_header("network [\n]")
+            .unwrap_err()
+            .is_missing_key());
+        assert!(Graph::parse_header("graph [\nid 1")
+            .unwrap_err()
+            .is_syntax());
+
+        // The same checks as a full parse
+        for text in [
+            "graph [\nid = 1\n]",
+            "graph [\nnode [\nid 1\nwert = 2\n]\n]",
+            "graph [\nedge [\nsource 1\ntarget = 1\n]\n]",
+        ] {
+            assert!(Graph::from_str(text).unwrap_err().is_syntax());
+            assert!(
+                Graph::parse_header(text).unwrap_err().is_syntax(),
+                "{:?}",
+                text
+            );
+        }
+        let header = Graph::parse_header("graph [\nnode \"a\"\nnode [\nid 1\n]\nedge 2\n]");
+        assert_eq!(
+            (header.as_ref().unwrap().nodes, header.unwrap().edges),
+            (1, 0)
+        );
+
+        let options = ParseOptions {
+            root_key: "network".into(),
+            allow_equals_separator: true,
+            leading_zeros_as_strings: true,
+            ..Default::default()
+        };
+        let text = "graph [\n]\nnetwork [\nid = 7\nlabel 007\nnode [\nid 1\n]\n]";
+        let header = Graph::parse_header_with(text, &options).unwrap();
+        assert_eq!(
+            (header.id, header.label, header.nodes),
+            (Some(7), Some("007".into()), 1)
+        );
+        assert!(Graph::parse_header(text).unwrap_err().is_syntax());
+    }
+}
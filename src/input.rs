@@ -0,0 +1,197 @@
+//! Reading GML from files and other [io::Read] sources.
+
+use std::{fs, io, path::Path};
+
+use crate::{Error, GMLObject, Graph, ParseOptions, Result};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+impl GMLObject {
+    /// Read all of `reader` and parse it as GML text.
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with(reader, &ParseOptions::default())
+    }
+    /// Like [GMLObject::from_reader], using the given [ParseOptions].
+    pub fn from_reader_with<R: io::Read>(reader: R, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_text(reader)?, options)
+    }
+    /// Decompress a gzip stream and parse its contents as GML text.
+    #[cfg(feature = "gzip")]
+    pub fn from_gzip_reader<R: io::Read>(reader: R) -> Result<Self> {
+        Self::from_gzip_reader_with(reader, &ParseOptions::default())
+    }
+    /// Like [GMLObject::from_gzip_reader], using the given [ParseOptions].
+    #[cfg(feature = "gzip")]
+    pub fn from_gzip_reader_with<R: io::Read>(reader: R, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_gzip(reader)?, options)
+    }
+    /// Read and parse the GML file at `path`, see [Graph::from_path].
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_path_with(path, &ParseOptions::default())
+    }
+    /// Like [GMLObject::from_path], using the given [ParseOptions].
+    pub fn from_path_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_path(path.as_ref())?, options)
+    }
+}
+
+impl Graph {
+    /// Read all of `reader` and parse it as GML text, see [Graph::from_str].
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with(reader, &ParseOptions::default())
+    }
+    /// Like [Graph::from_reader], using the given [ParseOptions].
+    pub fn from_reader_with<R: io::Read>(reader: R, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_text(reader)?, options)
+    }
+    /// Decompress a gzip stream and parse its contents as GML text.
+    ///
+    /// Fails with [Error::Gzip] when the stream is not valid gzip.
+    #[cfg(feature = "gzip")]
+    pub fn from_gzip_reader<R: io::Read>(reader: R) -> Result<Self> {
+        Self::from_gzip_reader_with(reader, &ParseOptions::default())
+    }
+    /// Like [Graph::from_gzip_reader], using the given [ParseOptions].
+    #[cfg(feature = "gzip")]
+    pub fn from_gzip_reader_with<R: io::Read>(reader: R, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_gzip(reader)?, options)
+    }
+    /// Read and parse the GML file at `path`.
+    ///
+    /// With the `gzip` feature, files ending in `.gz` or starting with the gzip magic bytes are
+    /// decompressed first. Without it, compressed files fail with an [Error::Other] naming the
+    /// feature.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_path_with(path, &ParseOptions::default())
+    }
+    /// Like [Graph::from_path], using the given [ParseOptions].
+    pub fn from_path_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self> {
+        Self::from_str_with(&read_path(path.as_ref())?, options)
+    }
+}
+
+pub(crate) fn read_path(path: &Path) -> Result<String> {
+    let bytes = fs::read(path)?;
+    let compressed =
+        bytes.starts_with(&GZIP_MAGIC) || path.extension().is_some_and(|ext| ext == "gz");
+    if !compressed {
+        return into_text(bytes);
+    }
+    #[cfg(feature = "gzip")]
+    {
+        read_gzip(bytes.as_slice())
+    }
+    #[cfg(not(feature = "gzip"))]
+    {
+        Err(Error::Other(format!(
+            "{} is gzip compressed, enable the gzip feature to read it",
+            path.display()
+        )))
+    }
+}
+
+fn read_text<R: io::Read>(mut reader: R) -> Result<String> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    into_text(bytes)
+}
+
+#[cfg(feature = "gzip")]
+fn read_gzip<R: io::Read>(reader: R) -> Result<String> {
+    let mut bytes = Vec::new();
+    io::Read::read_to_end(&mut flate2::read::MultiGzDecoder::new(reader), &mut bytes).map_err(
+        |err| match err.kind() {
+            // flate2 reports bad headers, bad data and checksum mismatches with these
+            io::ErrorKind::InvalidInput
+            | io::ErrorKind::InvalidData
+            | io::ErrorKind::UnexpectedEof => Error::Gzip(err.into()),
+            _ => Error::from(err),
+        },
+    )?;
+    into_text(bytes)
+}
+
+fn into_text(bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes)
+        .map_err(|err| Error::from(io::Error::new(io::ErrorKind::InvalidData, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn wikipedia() -> Graph {
+        Graph::from_str(&fs::read_to_string("tests/wikipedia.gml").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn read_plain_files() {
+        let graph = Graph::from_path("tests/wikipedia.gml").unwrap();
+        assert_eq!(graph.nodes, wikipedia().nodes);
+        let file = fs::File::open("tests/wikipedia.gml").unwrap();
+        assert_eq!(Graph::from_reader(file).unwrap().edges, wikipedia().edges);
+        assert_eq!(
+            GMLObject::from_path("tests/synoptic.gml").unwrap(),
+            GMLObject::from_str(&fs::read_to_string("tests/synoptic.gml").unwrap()).unwrap()
+        );
+
+        let missing = Graph::from_path("tests/missing.gml").unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::Io);
+        let not_utf8 = Graph::from_reader(&[b'g', 0xff][..]).unwrap_err();
+        assert_eq!(not_utf8.kind(), ErrorKind::Io);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn gzip_needs_feature() {
+        let err = Graph::from_path("tests/wikipedia.gml.gz").unwrap_err();
+        assert!(err.to_string().contains("gzip feature"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn read_gzip_files() {
+        let graph = Graph::from_path("tests/wikipedia.gml.gz").unwrap();
+        assert_eq!(graph.nodes, wikipedia().nodes);
+        assert_eq!(graph.edges, wikipedia().edges);
+        let file = fs::File::open("tests/wikipedia.gml.gz").unwrap();
+        assert_eq!(GMLObject::from_gzip_reader(file).unwrap().pairs.len(), 1);
+
+        // Detected by the magic bytes without the extension
+        let path = std::env::temp_dir().join(format!("gml_parser_{}.gml", std::process::id()));
+        fs::copy("tests/wikipedia.gml.gz", &path).unwrap();
+        let read = Graph::from_path(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap().nodes, wikipedia().nodes);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_errors() {
+        let bytes = fs::read("tests/wikipedia.gml.gz").unwrap();
+        let truncated = Graph::from_gzip_reader(&bytes[..bytes.len() / 2]).unwrap_err();
+        assert_eq!(truncated.kind(), ErrorKind::Decompression);
+        assert!(truncated.to_string().contains("Corrupt gzip stream"));
+        let not_gzip = Graph::from_gzip_reader(&b"graph [\n]"[..]).unwrap_err();
+        assert_eq!(not_gzip.kind(), ErrorKind::Decompression);
+
+        let mut corrupt = bytes.clone();
+        let middle = corrupt.len() / 2;
+        corrupt[middle] ^= 0xff;
+        assert_eq!(
+            Graph::from_gzip_reader(corrupt.as_slice())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Decompression
+        );
+
+        // A valid stream of invalid GML is a syntax error
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, b"graph [").unwrap();
+        let invalid = encoder.finish().unwrap();
+        assert!(Graph::from_gzip_reader(invalid.as_slice())
+            .unwrap_err()
+            .is_syntax());
+    }
+}
@@ -0,0 +1,334 @@
+//! Graph isomorphism, see [Graph::is_isomorphic_to].
+
+use std::collections::HashMap;
+
+use crate::{Edge, Error, Graph, Node, Result};
+
+impl Graph {
+    /// Whether the two graphs have the same structure up to a renaming of the node ids.
+    ///
+    /// Both graphs must be directed (`directed` is `Some(true)`) or both undirected, and have
+    /// the same number of edges between every pair of matched nodes, so parallel edges and self
+    /// loops are taken into account. Labels and attributes are ignored, see
+    /// [Graph::is_isomorphic_to_by] to compare them. Edges to missing nodes are ignored.
+    ///
+    /// The search backtracks and can take exponential time, so it fails with
+    /// [Error::TooManyNodes] when either graph has more than `max_nodes` nodes.
+    pub fn is_isomorphic_to(&self, other: &Graph, max_nodes: usize) -> Result<bool> {
+        self.is_isomorphic_to_by(other, max_nodes, |_, _| true, |_, _| true)
+    }
+    /// Like [Graph::is_isomorphic_to], but matched nodes must also satisfy `node_match` and the
+    /// edges between matched nodes must pair up such that every pair satisfies `edge_match`.
+    pub fn is_isomorphic_to_by<N, E>(
+        &self,
+        other: &Graph,
+        max_nodes: usize,
+        node_match: N,
+        edge_match: E,
+    ) -> Result<bool>
+    where
+        N: Fn(&Node, &Node) -> bool,
+        E: Fn(&Edge, &Edge) -> bool,
+    {
+        for graph in [self, other] {
+            if graph.nodes.len() > max_nodes {
+                return Err(Error::TooManyNodes {
+                    count: graph.nodes.len(),
+                    limit: max_nodes,
+                });
+            }
+        }
+        let directed = self.directed == Some(true);
+        if directed != (other.directed == Some(true)) || self.nodes.len() != other.nodes.len() {
+            return Ok(false);
+        }
+        let a = Structure::new(self, directed);
+        let b = Structure::new(other, directed);
+        if a.edge_count != b.edge_count || a.degree_sequence() != b.degree_sequence() {
+            return Ok(false);
+        }
+        let mut matcher = Matcher {
+            a: &a,
+            b: &b,
+            order: a.match_order(),
+            mapping: vec![None; a.nodes.len()],
+            used: vec![false; b.nodes.len()],
+            node_match: &node_match,
+            edge_match: &edge_match,
+        };
+        Ok(matcher.extend(0))
+    }
+}
+
+struct Structure<'g> {
+    nodes: &'g [Node],
+    directed: bool,
+    // Edges between each pair of node indices, the smaller index first when undirected
+    edges: HashMap<(usize, usize), Vec<&'g Edge>>,
+    edge_count: usize,
+    // Outgoing and incoming edges per node, the same number twice when undirected
+    degrees: Vec<(usize, usize)>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl<'g> Structure<'g> {
+    fn new(graph: &'g Graph, directed: bool) -> Self {
+        let mut indices = HashMap::with_capacity(graph.nodes.len());
+        for (i, node) in graph.nodes.iter().enumerate() {
+            indices.entry(node.id).or_insert(i);
+        }
+        let mut structure = Structure {
+            nodes: &graph.nodes,
+            directed,
+            edges: HashMap::new(),
+            edge_count: 0,
+            degrees: vec![(0, 0); graph.nodes.len()],
+            neighbors: vec![Vec::new(); graph.nodes.len()],
+        };
+        for edge in &graph.edges {
+            let (Some(&s), Some(&t)) = (indices.get(&edge.source), indices.get(&edge.target))
+            else {
+                continue;
+            };
+            structure.edge_count += 1;
+            structure
+                .edges
+                .entry(structure.key(s, t))
+                .or_default()
+                .push(edge);
+            if directed {
+                structure.degrees[s].0 += 1;
+                structure.degrees[t].1 += 1;
+            } else {
+                for end in [s, t] {
+                    structure.degrees[end].0 += 1;
+                    structure.degrees[end].1 += 1;
+                }
+            }
+            structure.neighbors[s].push(t);
+            structure.neighbors[t].push(s);
+        }
+        structure
+    }
+
+    fn key(&self, s: usize, t: usize) -> (usize, usize) {
+        if self.directed {
+            (s, t)
+        } else {
+            (s.min(t), s.max(t))
+        }
+    }
+
+    fn between(&self, s: usize, t: usize) -> &[&'g Edge] {
+        self.edges
+            .get(&self.key(s, t))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn degree_sequence(&self) -> Vec<(usize, usize)> {
+        let mut degrees = self.degrees.clone();
+        degrees.sort_unstable();
+        degrees
+    }
+
+    // Nodes ordered so that each one is connected to as many earlier ones as possible,
+    // which lets inconsistent partial matches fail early
+    fn match_order(&self) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut order = Vec::with_capacity(n);
+        let mut placed = vec![false; n];
+        let mut links = vec![0usize; n];
+        while order.len() < n {
+            let next = (0..n)
+                .filter(|&v| !placed[v])
+                .max_by_key(|&v| (links[v], self.degrees[v], std::cmp::Reverse(v)))
+                .expect("an unplaced node remains");
+            placed[next] = true;
+            order.push(next);
+            for &w in &self.neighbors[next] {
+                links[w] += 1;
+            }
+        }
+        order
+    }
+}
+
+struct Matcher<'a, 'g, N, E> {
+    a: &'a Structure<'g>,
+    b: &'a Structure<'g>,
+    order: Vec<usize>,
+    // Node of b matched to every node of a
+    mapping: Vec<Option<usize>>,
+    used: Vec<bool>,
+    node_match: &'a N,
+    edge_match: &'a E,
+}
+
+impl<N, E> Matcher<'_, '_, N, E>
+where
+    N: Fn(&Node, &Node) -> bool,
+    E: Fn(&Edge, &Edge) -> bool,
+{
+    fn extend(&mut self, depth: usize) -> bool {
+        let Some(&u) = self.order.get(depth) else {
+            return true;
+        };
+        for v in 0..self.b.nodes.len() {
+            if self.used[v] || !self.feasible(u, v) {
+                continue;
+            }
+            self.mapping[u] = Some(v);
+            self.used[v] = true;
+            if self.extend(depth + 1) {
+                return true;
+            }
+            self.mapping[u] = None;
+            self.used[v] = false;
+        }
+        false
+    }
+
+    // Whether u can be matched to v given the nodes matched so far
+    fn feasible(&self, u: usize, v: usize) -> bool {
+        if self.a.degrees[u] != self.b.degrees[v]
+            || !(self.node_match)(&self.a.nodes[u], &self.b.nodes[v])
+            || !self.edges_match(self.a.between(u, u), self.b.between(v, v))
+        {
+            return false;
+        }
+        self.order.iter().all(|&w| {
+            let Some(x) = self.mapping[w] else {
+                return true;
+            };
+            self.edges_match(self.a.between(u, w), self.b.between(v, x))
+                && (!self.a.directed
+                    || self.edges_match(self.a.between(w, u), self.b.between(x, v)))
+        })
+    }
+
+    // Whether the edges can be paired up one to one with edge_match holding for every pair
+    fn edges_match(&self, a: &[&Edge], b: &[&Edge]) -> bool {
+        fn pair<E: Fn(&Edge, &Edge) -> bool>(
+            a: &[&Edge],
+            b: &[&Edge],
+            taken: &mut [bool],
+            edge_match: &E,
+        ) -> bool {
+            let Some((first, rest)) = a.split_first() else {
+                return true;
+            };
+            for i in 0..b.len() {
+                if !taken[i] && edge_match(first, b[i]) {
+                    taken[i] = true;
+                    if pair(rest, b, taken, edge_match) {
+                        return true;
+                    }
+                    taken[i] = false;
+                }
+            }
+            false
+        }
+        a.len() == b.len() && pair(a, b, &mut vec![false; b.len()], self.edge_match)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn graph(directed: bool, nodes: &[i64], edges: &[(i64, i64)]) -> Graph {
+        let mut graph = Graph::new();
+        graph.directed = Some(directed);
+        graph.nodes.extend(nodes.iter().copied().map(Node::new));
+        graph.edges.extend(
+            edges
+                .iter()
+                .map(|&(source, target)| Edge::new(source, target)),
+        );
+        graph
+    }
+
+    #[test]
+    fn isomorphic_graphs() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let original = Graph::from_str(&file).unwrap();
+        // Renumber and shuffle the nodes and edges
+        let mut renamed = original.clone();
+        let rename = |id: i64| 1000 - id * 3;
+        for node in &mut renamed.nodes {
+            node.id = rename(node.id);
+        }
+        for edge in &mut renamed.edges {
+            edge.source = rename(edge.source);
+            edge.target = rename(edge.target);
+        }
+        renamed.nodes.reverse();
+        renamed.edges.rotate_left(3);
+        assert!(original.is_isomorphic_to(&renamed, 100).unwrap());
+
+        renamed.edges.pop();
+        assert!(!original.is_isomorphic_to(&renamed, 100).unwrap());
+
+        // Directed cycle, reversed and renumbered
+        let a = graph(true, &[1, 2, 3], &[(1, 2), (2, 3), (3, 1)]);
+        let b = graph(true, &[7, 8, 9], &[(9, 8), (8, 7), (7, 9)]);
+        assert!(a.is_isomorphic_to(&b, 3).unwrap());
+        let path = graph(true, &[7, 8, 9], &[(7, 8), (8, 9), (7, 9)]);
+        assert!(!a.is_isomorphic_to(&path, 3).unwrap());
+        let mut undirected = b.clone();
+        undirected.directed = None;
+        assert!(!a.is_isomorphic_to(&undirected, 3).unwrap());
+
+        // Parallel edges and self loops
+        let a = graph(false, &[1, 2, 3], &[(1, 2), (2, 1), (3, 3)]);
+        let b = graph(false, &[4, 5, 6], &[(5, 5), (6, 4), (4, 6)]);
+        let c = graph(false, &[4, 5, 6], &[(5, 5), (6, 4), (4, 5)]);
+        assert!(a.is_isomorphic_to(&b, 3).unwrap());
+        assert!(!a.is_isomorphic_to(&c, 3).unwrap());
+    }
+
+    #[test]
+    fn same_degrees_different_structure() {
+        // Two triangles and a hexagon: every node has degree 2
+        let triangles = graph(
+            false,
+            &[1, 2, 3, 4, 5, 6],
+            &[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4)],
+        );
+        let hexagon = graph(
+            false,
+            &[1, 2, 3, 4, 5, 6],
+            &[(1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 1)],
+        );
+        assert_eq!(triangles.degree_histogram(), hexagon.degree_histogram());
+        assert!(!triangles.is_isomorphic_to(&hexagon, 6).unwrap());
+        assert!(hexagon.is_isomorphic_to(&hexagon.clone(), 6).unwrap());
+    }
+
+    #[test]
+    fn isomorphism_callbacks_and_limit() {
+        let mut a = graph(false, &[1, 2, 3], &[(1, 2), (2, 3)]);
+        let mut b = graph(false, &[7, 8, 9], &[(8, 9), (9, 7)]);
+        a.nodes[0].label = Some("end".into());
+        b.nodes[0].label = Some("end".into());
+        a.edges[0].set_attribute("weight", 5);
+        b.edges[1].set_attribute("weight", 5);
+        let labels = |x: &Node, y: &Node| x.label == y.label;
+        let weights = |x: &Edge, y: &Edge| x.attr_i64("weight") == y.attr_i64("weight");
+        assert!(a.is_isomorphic_to_by(&b, 3, labels, weights).unwrap());
+
+        b.nodes[0].label = None;
+        b.nodes[2].label = Some("end".into());
+        assert!(a.is_isomorphic_to(&b, 3).unwrap());
+        assert!(!a.is_isomorphic_to_by(&b, 3, labels, weights).unwrap());
+        assert!(!a
+            .is_isomorphic_to_by(&b, 3, |_, _| true, |_, _| false)
+            .unwrap());
+
+        let err = a.is_isomorphic_to(&b, 2).unwrap_err();
+        assert!(matches!(err, Error::TooManyNodes { count: 3, limit: 2 }));
+        assert_eq!(err.kind(), ErrorKind::Limit);
+    }
+}
@@ -0,0 +1,229 @@
+//! Node layouts written into the `graphics` object of every node, the way yEd and Gephi
+//! store positions.
+
+use std::{collections::HashMap, f64::consts::PI};
+
+use crate::{rng::SplitMix64, GMLObject, GMLValue, Graph, HasGMLAttributes, Node};
+
+impl Graph {
+    /// Place the nodes evenly on a circle of `radius` around the origin, in declaration order.
+    ///
+    /// See [Graph::layout_fruchterman_reingold] for how positions are stored.
+    pub fn layout_circular(&mut self, radius: f64) {
+        let count = self.nodes.len() as f64;
+        for (i, node) in self.nodes.iter_mut().enumerate() {
+            let angle = 2.0 * PI * i as f64 / count;
+            set_position(node, radius * angle.cos(), radius * angle.sin());
+        }
+    }
+    /// Position the nodes with the force directed algorithm of Fruchterman and Reingold,
+    /// ignoring the direction of the edges.
+    ///
+    /// Nodes start at random positions derived from `seed`, so the same graph and seed always
+    /// produce the same layout. The nodes stay within a square of `area` centered on the origin.
+    /// Repulsion is only computed between nearby nodes, keeping each iteration close to linear
+    /// in the size of the graph.
+    ///
+    /// The position is written to the `x` and `y` floats of the `graphics` object of every node,
+    /// which is created if needed. Other keys of `graphics` are left untouched.
+    pub fn layout_fruchterman_reingold(&mut self, iterations: usize, seed: u64, area: f64) {
+        let n = self.nodes.len();
+        if n == 0 {
+            return;
+        }
+        let side = area.sqrt();
+        let half = side / 2.0;
+        // Ideal distance between nodes
+        let k = (area / n as f64).sqrt();
+        let mut rng = SplitMix64(seed);
+        let mut pos: Vec<(f64, f64)> = (0..n)
+            .map(|_| ((rng.next_f64() - 0.5) * side, (rng.next_f64() - 0.5) * side))
+            .collect();
+
+        let indices: HashMap<i64, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter_map(|edge| Some((*indices.get(&edge.source)?, *indices.get(&edge.target)?)))
+            .filter(|(source, target)| source != target)
+            .collect();
+
+        let cell = 2.0 * k;
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        let mut disp = vec![(0.0, 0.0); n];
+        for iteration in 0..iterations {
+            // Linear cooling from a tenth of the width down to zero
+            let temperature = side / 10.0 * (1.0 - iteration as f64 / iterations as f64);
+
+            grid.clear();
+            for (i, &(x, y)) in pos.iter().enumerate() {
+                grid.entry(cell_of(x, y, cell)).or_default().push(i);
+            }
+            for (i, &(x, y)) in pos.iter().enumerate() {
+                let (cx, cy) = cell_of(x, y, cell);
+                let mut d = (0.0, 0.0);
+                for gx in cx - 1..=cx + 1 {
+                    for gy in cy - 1..=cy + 1 {
+                        for &j in grid.get(&(gx, gy)).into_iter().flatten() {
+                            if i == j {
+                                continue;
+                            }
+                            let (dx, dy, dist) = delta(pos[i], pos[j], i, j);
+                            if dist < cell {
+                                let force = k * k / dist;
+                                d.0 += dx / dist * force;
+                                d.1 += dy / dist * force;
+                            }
+                        }
+                    }
+                }
+                disp[i] = d;
+            }
+            for &(source, target) in &edges {
+                let (dx, dy, dist) = delta(pos[source], pos[target], source, target);
+                let force = dist * dist / k;
+                let (fx, fy) = (dx / dist * force, dy / dist * force);
+                disp[source].0 -= fx;
+                disp[source].1 -= fy;
+                disp[target].0 += fx;
+                disp[target].1 += fy;
+            }
+            for (p, d) in pos.iter_mut().zip(&disp) {
+                let length = (d.0 * d.0 + d.1 * d.1).sqrt();
+                if length > 0.0 {
+                    let step = length.min(temperature);
+                    p.0 = (p.0 + d.0 / length * step).clamp(-half, half);
+                    p.1 = (p.1 + d.1 / length * step).clamp(-half, half);
+                }
+            }
+        }
+        for (node, (x, y)) in self.nodes.iter_mut().zip(pos) {
+            set_position(node, x, y);
+        }
+    }
+}
+
+fn cell_of(x: f64, y: f64, cell: f64) -> (i64, i64) {
+    ((x / cell).floor() as i64, (y / cell).floor() as i64)
+}
+
+// Vector from b to a and its length. Nodes at the same position are nudged apart
+// in a direction derived from their indices so the result stays deterministic.
+fn delta(a: (f64, f64), b: (f64, f64), i: usize, j: usize) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist > 1e-9 {
+        (dx, dy, dist)
+    } else {
+        let angle = (i * 31 + j * 17) as f64;
+        (angle.cos() * 1e-9, angle.sin() * 1e-9, 1e-9)
+    }
+}
+
+fn set_position(node: &mut Node, x: f64, y: f64) {
+    let attrs = node.attributes_mut();
+    let graphics = match attrs.iter().position(|(key, _)| key == "graphics") {
+        Some(i) => &mut attrs[i].1,
+        None => {
+            attrs.push((
+                "graphics".to_owned(),
+                GMLValue::GMLObject(Box::new(GMLObject { pairs: Vec::new() })),
+            ));
+            &mut attrs.last_mut().unwrap().1
+        }
+    };
+    if !matches!(graphics, GMLValue::GMLObject(_)) {
+        *graphics = GMLValue::GMLObject(Box::new(GMLObject { pairs: Vec::new() }));
+    }
+    let GMLValue::GMLObject(graphics) = graphics else {
+        unreachable!()
+    };
+    for (key, value) in [("x", x), ("y", y)] {
+        match graphics.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some(pair) => pair.1 = GMLValue::GMLFloat(value),
+            None => graphics
+                .pairs
+                .push((key.to_owned(), GMLValue::GMLFloat(value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn positions(graph: &Graph) -> Vec<(f64, f64)> {
+        graph
+            .nodes
+            .iter()
+            .map(|node| {
+                let graphics = node.attr_object("graphics").unwrap();
+                let get = |key: &str| {
+                    let (_, value) = graphics.pairs.iter().find(|(k, _)| k == key).unwrap();
+                    value.as_f64().unwrap()
+                };
+                (get("x"), get("y"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn layouts_are_written_to_graphics() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let GMLValue::GMLObject(graphics) = &mut graph.nodes[0].attrs[0].1 else {
+            panic!("synoptic nodes have graphics");
+        };
+        graphics
+            .pairs
+            .push(("fill".into(), GMLValue::GMLString("#ff0000".into())));
+        graphics.pairs.push(("x".into(), GMLValue::GMLInt(7)));
+
+        graph.layout_circular(10.0);
+        let circle = positions(&graph);
+        for (x, y) in &circle {
+            assert!(((x * x + y * y).sqrt() - 10.0).abs() < 1e-9);
+        }
+
+        graph.layout_fruchterman_reingold(50, 42, 10_000.0);
+        let layout = positions(&graph);
+        assert!(layout.iter().all(|(x, y)| x.is_finite() && y.is_finite()));
+        assert!(layout
+            .iter()
+            .all(|(x, y)| x.abs() <= 50.0 && y.abs() <= 50.0));
+        assert_ne!(layout, circle);
+        let graphics = graph.nodes[0].attr_object("graphics").unwrap();
+        let keys: Vec<&str> = graphics.pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["type", "fill", "x", "y"]);
+
+        let mut again = Graph::from_str(&file).unwrap();
+        again.layout_fruchterman_reingold(50, 42, 10_000.0);
+        assert_eq!(positions(&again), layout);
+        again.layout_fruchterman_reingold(50, 43, 10_000.0);
+        assert_ne!(positions(&again), layout);
+
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(positions(&read), layout);
+    }
+
+    #[test]
+    fn layout_large_graph() {
+        let mut graph = Graph::new();
+        for id in 0..10_000 {
+            graph.nodes.push(Node::new(id));
+            if id > 0 {
+                graph.edges.push(Edge::new(id, (id * 7919) % id));
+            }
+        }
+        graph.layout_fruchterman_reingold(5, 1, 1e6);
+        assert!(positions(&graph)
+            .iter()
+            .all(|(x, y)| x.is_finite() && y.is_finite()));
+    }
+}
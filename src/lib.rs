@@ -3,6 +3,7 @@
 //!
 //! This crate first parses the GML into [GMLObject]s and [GMLValue]s. Then the root GMLObject can
 //! be transformed into a [Graph] containing [Node]s and [Edge]s.
+//! Both can be written back as GML text with [Graph::to_gml] and [GMLObject::to_gml].
 //!
 //! # Examples
 //! ```
@@ -35,76 +36,231 @@
 //! - We duplicate the data when parsing which can have performance impacts on very large graphs
 //!
 
-use std::{error::Error, fmt::Display};
 extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use pest::{iterators::Pairs, Parser};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
-#[derive(Debug)]
-pub struct GMLError(String);
+use pest::{
+    iterators::{Pair, Pairs},
+    Parser,
+};
 
-impl Error for GMLError {
-    fn description(&self) -> &str {
-        &self.0
-    }
-}
-
-impl Display for GMLError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "GMLError: {}", self.0)
-    }
-}
+mod algorithms;
+pub use algorithms::{DegreeMode, DirectednessPolicy, ProjectionWeight, UnionFind};
+#[cfg(feature = "arena")]
+mod arena;
+mod canonical;
+pub mod coerce;
+mod compact;
+#[cfg(feature = "time")]
+mod datetime;
+mod defaults;
+mod document;
+mod entry;
+mod error;
+mod export;
+mod flatten;
+#[cfg(feature = "gexf")]
+mod gexf;
+mod gml_type;
+mod header;
+mod input;
+mod isomorphism;
+mod layout;
+mod load;
+mod memory;
+mod options;
+#[cfg(feature = "pajek")]
+mod pajek;
+mod pointer;
+pub mod raw;
+mod recover;
+mod relabel;
+mod report;
+mod rewrite;
+mod rng;
+mod sample;
+mod schema;
+mod stats;
+#[cfg(feature = "async")]
+mod stream;
+mod unknown_keys;
+pub mod util;
+mod version;
+mod writer;
+#[cfg(feature = "arena")]
+pub use arena::{ArenaObject, ArenaValue};
+pub use compact::{DuplicateAttrCounts, DuplicateAttrPolicy};
+pub use defaults::AttrDefaults;
+pub use document::GMLDocument;
+pub use entry::Entry;
+#[allow(deprecated)]
+pub use error::GMLError;
+pub use error::{Error, ErrorKind, IoError, Result, Warning};
+pub use export::AdjacencyListOptions;
+pub use gml_type::GMLType;
+pub use header::GraphHeader;
+pub use load::{load_dir, LoadDirOptions};
+pub use options::{
+    AllowedKeys, DuplicatePolicy, EdgeEndpointParser, ParseLimits, ParseOptions, UnknownKeyPolicy,
+};
+pub use relabel::LabelCollision;
+pub use report::ParseReport;
+use report::ParseState;
+pub use rewrite::{rewrite_file, rewrite_file_with, RewriteOptions, RewriteReport};
+pub use schema::Schema;
+pub use stats::{AttrScope, AttrStats, GraphStats, OversizedElement, PartitionKey};
+use util::KeyMatch;
+pub use version::GMLVersion;
+pub use writer::{
+    format, graph_file_name, write_graphs, write_graphs_to_dir, write_graphs_with,
+    InvalidKeyPolicy, Provenance, SerializeOptions,
+};
 
+/// The pest parser for GML. Most users want [GMLObject::from_str] instead.
 #[derive(Parser, Debug)]
 #[grammar = "grammar.pest"]
-struct GMLParser;
+pub struct GMLParser;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct GMLObject {
     pub pairs: Vec<(String, GMLValue)>,
 }
 impl GMLObject {
-    fn parse(obj: Pairs<'_, Rule>) -> Result<Self, Box<dyn Error>> {
+    /// Build an object from the children of a pest [Rule::text] or [Rule::object] pair.
+    ///
+    /// This is useful when working with the output of [GMLParser] directly.
+    /// ```
+    /// use gml_parser::{GMLObject, GMLParser, Rule};
+    /// use pest::Parser;
+    ///
+    /// let text = "graph [\n\tid 3\n]";
+    /// let file = GMLParser::parse(Rule::text, text).unwrap().next().unwrap();
+    /// let root = GMLObject::from_pairs(file.into_inner()).unwrap();
+    /// assert_eq!(root.pairs[0].0, "graph");
+    /// ```
+    pub fn from_pairs(obj: Pairs<'_, Rule>) -> Result<Self> {
+        Self::parse_with(obj, &ParseOptions::default(), &mut ParseState::default())
+    }
+    fn parse_with(
+        obj: Pairs<'_, Rule>,
+        options: &ParseOptions,
+        state: &mut ParseState,
+    ) -> Result<Self> {
         let mut current_key = None;
         let mut pairs = Vec::new();
         for entry in obj {
             match entry.as_rule() {
                 Rule::identifier => {
+                    check_identifier(&entry, options)?;
                     current_key = Some(entry.into_inner().as_str().to_owned());
                 }
                 Rule::value => {
-                    let inner_value = entry
-                        .into_inner()
-                        .next()
-                        .ok_or(GMLError("No rule inner value. Please report this.".into()))?;
+                    check_separator(&entry, current_key.as_deref(), options)?;
+                    if let Some(key) = current_key.as_ref() {
+                        if !options.skip_keys.is_empty() && options.skip_keys.contains(key) {
+                            *state.skipped.entry(key.clone()).or_default() += 1;
+                            continue;
+                        }
+                        state.report.pairs += 1;
+                        if state.in_graph || state.in_wrapper {
+                            let limits = &options.limits;
+                            match key.as_str() {
+                                "node" => {
+                                    state.report.nodes += 1;
+                                    state.graph_nodes += 1;
+                                    let count = state.graph_nodes;
+                                    if let Some(limit) = limits.max_nodes.filter(|l| count > *l) {
+                                        return Err(Error::TooManyNodes { count, limit });
+                                    }
+                                }
+                                "edge" => {
+                                    state.report.edges += 1;
+                                    state.graph_edges += 1;
+                                    let count = state.graph_edges;
+                                    if let Some(limit) = limits.max_edges.filter(|l| count > *l) {
+                                        return Err(Error::TooManyEdges { count, limit });
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    let inner_value = entry.into_inner().next().ok_or(Error::Other(
+                        "No rule inner value. Please report this.".into(),
+                    ))?;
                     match inner_value.as_rule() {
                         Rule::string => {
+                            let string = string_text(&inner_value);
+                            state.report.string_bytes += string.len();
                             pairs.push((
-                                current_key.clone().ok_or(GMLError(
+                                current_key.clone().ok_or(Error::Other(
                                     "String: No rule current key. Please report this.".into(),
                                 ))?,
-                                GMLValue::GMLString(inner_value.into_inner().as_str().to_string()),
+                                GMLValue::GMLString(string.to_owned()),
                             ));
                         }
                         Rule::number => {
                             pairs.push((
-                                current_key.clone().ok_or(GMLError(
+                                current_key.clone().ok_or(Error::Other(
                                     "Number: No rule current key. Please report this".into(),
                                 ))?,
-                                GMLValue::GMLInt(inner_value.as_str().parse()?),
+                                parse_number(inner_value.as_str(), options)?,
                             ));
                         }
                         Rule::object => {
-                            pairs.push((
-                                current_key.clone().ok_or(GMLError(
-                                    "Object: No rule current key. Please report this".into(),
-                                ))?,
-                                GMLValue::GMLObject(Box::new(GMLObject::parse(
-                                    inner_value.into_inner(),
-                                )?)),
-                            ));
+                            let key = current_key.clone().ok_or(Error::Other(
+                                "Object: No rule current key. Please report this".into(),
+                            ))?;
+                            if let Some(limit) = options.limits.max_pairs_per_object {
+                                // Children alternate between keys and values
+                                if inner_value.clone().into_inner().count() / 2 > limit {
+                                    return Err(Error::TooManyPairs {
+                                        index: pairs.iter().filter(|(k, _)| *k == key).count(),
+                                        element: key,
+                                        line: inner_value.line_col().0,
+                                        limit,
+                                    });
+                                }
+                            }
+                            let outer = (state.in_graph, state.in_wrapper);
+                            state.in_wrapper = state.in_graph
+                                && options.flatten_wrapper
+                                && key != "node"
+                                && key != "edge";
+                            state.in_graph = state.depth == 0 && key == options.root_key;
+                            if state.in_graph {
+                                (state.graph_nodes, state.graph_edges) = (0, 0);
+                            }
+                            state.depth += 1;
+                            state.report.max_depth = state.report.max_depth.max(state.depth);
+                            let obj =
+                                GMLObject::parse_with(inner_value.into_inner(), options, state)?;
+                            state.depth -= 1;
+                            (state.in_graph, state.in_wrapper) = outer;
+                            pairs.push((key, GMLValue::GMLObject(Box::new(obj))));
+                        }
+                        Rule::list => {
+                            let key = current_key.clone().ok_or(Error::Other(
+                                "List: No rule current key. Please report this".into(),
+                            ))?;
+                            let mut items = Vec::new();
+                            for item in inner_value.into_inner() {
+                                match item.as_rule() {
+                                    Rule::string => {
+                                        let string = string_text(&item);
+                                        state.report.string_bytes += string.len();
+                                        items.push(GMLValue::GMLString(string.to_owned()));
+                                    }
+                                    _ => items.push(parse_number(item.as_str(), options)?),
+                                }
+                            }
+                            pairs.push((key, GMLValue::GMLList(items)));
                         }
                         _ => {
                             dbg!(inner_value.as_rule());
@@ -121,141 +277,730 @@ impl GMLObject {
         }
         Ok(GMLObject { pairs })
     }
-    pub fn from_str(text: &str) -> Result<GMLObject, GMLError> {
-        let file = match GMLParser::parse(Rule::text, text) {
-            Ok(k) => Ok(k),
-            Err(e) => Err(GMLError(format!(
-                "Failed to parse GML! (syntactic): {:?}",
-                e
-            ))),
-        }?
-        .next()
-        .unwrap();
-        match GMLObject::parse(file.into_inner()) {
-            Ok(k) => Ok(k),
-            Err(e) => Err(GMLError(format!(
-                "Failed to parse GML! (semantic): {:?}",
-                e
-            ))),
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str) -> Result<GMLObject> {
+        Self::from_str_with(text, &ParseOptions::default())
+    }
+    /// Parse GML text using the given [ParseOptions].
+    pub fn from_str_with(text: &str, options: &ParseOptions) -> Result<GMLObject> {
+        let file = parse_text(text)?;
+        GMLObject::parse_with(file.into_inner(), options, &mut ParseState::default())
+    }
+    /// Parse a piece of GML that isn't necessarily a whole document: a list of pairs as
+    /// accepted by [GMLObject::from_str], a bare `[...]` object whose pairs are returned, or
+    /// only whitespace, which gives an empty object.
+    ///
+    /// ```
+    /// use gml_parser::GMLObject;
+    ///
+    /// let bare = GMLObject::from_fragment_str("[\nid 1\nlabel \"a\"\n]").unwrap();
+    /// assert_eq!(bare, GMLObject::from_str("id 1\nlabel \"a\"").unwrap());
+    /// ```
+    pub fn from_fragment_str(text: &str) -> Result<GMLObject> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(GMLObject { pairs: Vec::new() });
+        }
+        if !text.starts_with('[') {
+            return Self::from_str(text);
+        }
+        let object = match GMLParser::parse(Rule::object, text) {
+            Ok(mut object) => object.next().unwrap(),
+            Err(e) => return Err(Error::Syntax(format!("{:?}", e))),
+        };
+        let end = object.as_span().end();
+        if end != text.len() {
+            return Err(Error::Syntax(format!(
+                "Unexpected text after the object: {:?}",
+                &text[end..]
+            )));
+        }
+        Self::parse_with(
+            object.into_inner(),
+            &ParseOptions::default(),
+            &mut ParseState::default(),
+        )
+    }
+    /// The value of the first pair with `key`.
+    pub fn get(&self, key: &str) -> Option<&GMLValue> {
+        self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    /// Remove the first pair with `key` and return its value. The other pairs keep their order.
+    pub fn remove(&mut self, key: &str) -> Option<GMLValue> {
+        let index = self.pairs.iter().position(|(k, _)| k == key)?;
+        Some(self.pairs.remove(index).1)
+    }
+    /// Remove every pair with `key` and return their values in document order.
+    pub fn remove_all(&mut self, key: &str) -> Vec<GMLValue> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::with_capacity(self.pairs.len());
+        for (k, v) in self.pairs.drain(..) {
+            if k == key {
+                removed.push(v);
+            } else {
+                kept.push((k, v));
+            }
+        }
+        self.pairs = kept;
+        removed
+    }
+}
+
+// Keys written without quotes must be ASCII unless the options allow otherwise
+fn check_identifier(entry: &Pair<'_, Rule>, options: &ParseOptions) -> Result<()> {
+    let text = entry.as_str();
+    if !options.unicode_identifiers && !text.starts_with('"') && !text.is_ascii() {
+        return Err(Error::Syntax(format!(
+            "Key {:?} on line {} is not ASCII",
+            text,
+            entry.line_col().0
+        )));
+    }
+    Ok(())
+}
+fn check_separator(
+    entry: &Pair<'_, Rule>,
+    key: Option<&str>,
+    options: &ParseOptions,
+) -> Result<()> {
+    if !options.allow_equals_separator && entry.as_str().starts_with('=') {
+        return Err(Error::Syntax(format!(
+            "Unexpected `=` before the value of {:?} on line {}",
+            key.unwrap_or_default(),
+            entry.line_col().0
+        )));
+    }
+    Ok(())
+}
+// The text of a string between its quotes. The inner pairs only start at the first char
+// they cover, which for a string starting with an escape is past the `\`.
+fn string_text<'i>(string: &Pair<'i, Rule>) -> &'i str {
+    let text = string.as_str();
+    &text[1..text.len() - 1]
+}
+fn parse_text(text: &str) -> Result<Pair<'_, Rule>> {
+    match GMLParser::parse(Rule::text, text) {
+        Ok(k) => Ok(k),
+        Err(e) => Err(Error::Syntax(format!("{:?}", e))),
+    }
+    .map(|mut file| file.next().unwrap())
+}
+
+// Byte ranges of each pair directly inside the first `<root_key> [...]` object,
+// in the same order as GMLObject::parse produces them.
+fn graph_pair_spans(
+    root: Pairs<'_, Rule>,
+    root_key: &str,
+    skip_keys: &HashSet<String>,
+) -> Option<Vec<Range<usize>>> {
+    let mut root = root;
+    while let Some(key) = root.next() {
+        if key.as_rule() != Rule::identifier {
+            continue;
+        }
+        let value = root.next()?.into_inner().next()?;
+        if key.into_inner().as_str() != root_key || value.as_rule() != Rule::object {
+            continue;
         }
+        let mut spans = Vec::new();
+        let mut inner = value.into_inner();
+        while let (Some(key), Some(value)) = (inner.next(), inner.next()) {
+            if !skip_keys.contains(key.clone().into_inner().as_str()) {
+                spans.push(key.as_span().start()..value.as_span().end());
+            }
+        }
+        return Some(spans);
     }
+    None
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum GMLValue {
     GMLString(String),
     GMLInt(i64),
+    GMLFloat(f64),
     GMLObject(Box<GMLObject>),
+    /// An anonymous list on a single line such as `levels [ 1 2 3 ]`, with at least one item.
+    /// Only ints, floats and strings can be written inside one, see [GMLObject::write_gml].
+    GMLList(Vec<GMLValue>),
 }
 
-#[derive(Debug, Clone)]
+impl GMLValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            GMLValue::GMLString(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// Only ints, see [coerce::to_i64] to also accept floats without a fractional part.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            GMLValue::GMLInt(i) => Some(*i),
+            _ => None,
+        }
+    }
+    /// Returns the value of floats and ints, converting the latter to `f64`,
+    /// see [coerce::to_f64].
+    pub fn as_f64(&self) -> Option<f64> {
+        coerce::to_f64(self)
+    }
+    pub fn as_object(&self) -> Option<&GMLObject> {
+        match self {
+            GMLValue::GMLObject(obj) => Some(obj),
+            _ => None,
+        }
+    }
+    pub fn as_list(&self) -> Option<&[GMLValue]> {
+        match self {
+            GMLValue::GMLList(items) => Some(items),
+            _ => None,
+        }
+    }
+    /// GML writes flags such as `directed` as ints, so only `0` and `1` are booleans.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            GMLValue::GMLInt(0) => Some(false),
+            GMLValue::GMLInt(1) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for GMLValue {
+    /// Flags are written as `0` or `1`
+    fn from(value: bool) -> Self {
+        GMLValue::GMLInt(value as i64)
+    }
+}
+impl From<i64> for GMLValue {
+    fn from(value: i64) -> Self {
+        GMLValue::GMLInt(value)
+    }
+}
+impl From<f64> for GMLValue {
+    fn from(value: f64) -> Self {
+        GMLValue::GMLFloat(value)
+    }
+}
+impl From<String> for GMLValue {
+    fn from(value: String) -> Self {
+        GMLValue::GMLString(value)
+    }
+}
+impl From<&str> for GMLValue {
+    fn from(value: &str) -> Self {
+        GMLValue::GMLString(value.to_owned())
+    }
+}
+impl From<GMLObject> for GMLValue {
+    fn from(value: GMLObject) -> Self {
+        GMLValue::GMLObject(Box::new(value))
+    }
+}
+
+// Numbers may carry an explicit sign (`x +3`). The leading `+` is stripped so
+// that the int and float parsers see the same digits regardless of the generator.
+// Tokens made of digits only are ints, unless they don't fit in an i64. Anything
+// else (`1.5`, `1e3`, `inf`) is a float.
+fn parse_number(text: &str, options: &ParseOptions) -> Result<GMLValue> {
+    let digits = text.strip_prefix('+').unwrap_or(text);
+    let unsigned = digits.strip_prefix('-').unwrap_or(digits);
+    let integer = unsigned.bytes().all(|b| b.is_ascii_digit());
+    if options.leading_zeros_as_strings
+        && integer
+        && unsigned.len() > 1
+        && unsigned.starts_with('0')
+    {
+        return Ok(GMLValue::GMLString(text.to_owned()));
+    }
+    if integer {
+        if let Ok(i) = digits.parse() {
+            return Ok(GMLValue::GMLInt(i));
+        }
+    }
+    digits
+        .parse()
+        .map(GMLValue::GMLFloat)
+        .map_err(|e| Error::Syntax(format!("Invalid number {:?}: {}", text, e)))
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Graph {
     pub directed: Option<bool>,
     pub id: Option<i64>,
     pub label: Option<String>,
+    /// The identity of the graph when the document distinguishes it from the display `label`
+    pub name: Option<String>,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
     attrs: Vec<(String, GMLValue)>,
+    warnings: Vec<Warning>,
 }
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Node {
     pub id: i64,
     pub label: Option<String>,
     attrs: Vec<(String, GMLValue)>,
+    // Boxed so that graphs parsed without spans only pay for a null pointer
+    span: Option<Box<Range<usize>>>,
 }
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Edge {
     pub source: i64,
     pub target: i64,
     pub label: Option<String>,
     attrs: Vec<(String, GMLValue)>,
+    span: Option<Box<Range<usize>>>,
 }
 
 impl Graph {
+    /// An empty graph without any nodes, edges or attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // A graph with the same graph-level keys and attributes but no nodes or edges
+    pub(crate) fn empty_like(&self) -> Graph {
+        Graph {
+            directed: self.directed,
+            id: self.id,
+            label: self.label.clone(),
+            name: self.name.clone(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            attrs: self.attrs.clone(),
+            warnings: Vec::new(),
+        }
+    }
+    /// Problems found while converting the document that didn't prevent building the graph.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
     // This turns the data into the object.
     // The other function is a wrapper to deal with the
     // outer graph[...] nonsense
-    fn int_from_gml(mut obj: GMLObject) -> Result<Self, GMLError> {
-        let id = int_take_attribute(&mut obj.pairs, "id");
+    fn int_from_gml(
+        obj: GMLObject,
+        spans: Option<Vec<Range<usize>>>,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        // A single pass keeps nodes and edges in document order
+        let mut spans = spans.map(Vec::into_iter);
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut pairs = Vec::with_capacity(obj.pairs.len());
+        for (key, value) in obj.pairs {
+            let span = spans.as_mut().and_then(Iterator::next).map(Box::new);
+            match key.as_str() {
+                "node" => nodes.push(int_node_from_value(value, span)?),
+                "edge" => edges.push(int_edge_from_value(value, span, options)?),
+                _ => match value {
+                    GMLValue::GMLObject(wrapper)
+                        if options.flatten_wrapper && contains_elements(&wrapper) =>
+                    {
+                        let mut rest = Vec::new();
+                        for (key, value) in wrapper.pairs {
+                            match key.as_str() {
+                                "node" => nodes.push(int_node_from_value(value, None)?),
+                                "edge" => edges.push(int_edge_from_value(value, None, options)?),
+                                _ => rest.push((key, value)),
+                            }
+                        }
+                        if !rest.is_empty() {
+                            let rest = GMLObject { pairs: rest };
+                            pairs.push((key, GMLValue::GMLObject(Box::new(rest))));
+                        }
+                    }
+                    value => pairs.push((key, value)),
+                },
+            }
+        }
+        let mut obj = GMLObject { pairs };
+
+        let id = int_take_attribute(&mut obj.pairs, "id", KeyMatch::Exact);
         let id = if let Some(id) = id {
             let GMLValue::GMLInt(id) = id.1 else {
-                return Err(GMLError(format!("Failed to parse graph id: {:?}. Expected int but found invalid type.", id.1)));
+                return Err(Error::wrong_type("graph", "id", GMLType::Int, id.1));
             };
             Some(id)
         } else {
             None
         };
-        let directed = int_take_attribute(&mut obj.pairs, "directed");
+        let directed = int_take_attribute(&mut obj.pairs, "directed", KeyMatch::Exact);
+        let mut warnings = Vec::new();
         let directed = if let Some(directed) = directed {
-            let GMLValue::GMLInt(directed) = directed.1 else {
-                return Err(GMLError(format!("Failed to parse graph directed: {:?}. Expected int but found invalid type.", directed.1)));
+            let GMLValue::GMLInt(value) = directed.1 else {
+                return Err(Error::wrong_type(
+                    "graph",
+                    "directed",
+                    GMLType::Int,
+                    directed.1,
+                ));
             };
-            Some(directed == 1)
+            read_flag("graph", "directed", value, options, &mut warnings)?
         } else {
             None
         };
+        for edge in &edges {
+            if let Some((key, GMLValue::GMLInt(value))) = edge.get_attribute("directed") {
+                read_flag("edge", key, *value, options, &mut warnings)?;
+            }
+        }
 
-        let label = int_take_attribute(&mut obj.pairs, "label");
+        let label = int_take_attribute(&mut obj.pairs, "label", KeyMatch::Exact);
         let label = if let Some(label) = label {
             let GMLValue::GMLString(label) = label.1 else {
-                return Err(GMLError(format!("Failed to parse edge label: {:?}. Expected str but found invalid type.", label.1)));
+                return Err(Error::wrong_type(
+                    "graph",
+                    "label",
+                    GMLType::String,
+                    label.1,
+                ));
             };
             Some(label)
         } else {
             None
         };
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
-        while let Some((_, node)) = int_take_attribute(&mut obj.pairs, "node") {
-            let GMLValue::GMLObject(node) = node else {
-                return Err(GMLError(format!("Failed to parse node: {:?}. Expected object but found invalid type.", node)));
-            };
-            nodes.push(Node::from_gml(*node)?);
-        }
-        while let Some((_, edge)) = int_take_attribute(&mut obj.pairs, "edge") {
-            let GMLValue::GMLObject(edge) = edge else {
-                return Err(GMLError(format!("Failed to parse edge: {:?}. Expected object but found invalid type.", edge)));
+        let name = int_take_attribute(&mut obj.pairs, "name", KeyMatch::Exact);
+        let name = if let Some(name) = name {
+            let GMLValue::GMLString(name) = name.1 else {
+                return Err(Error::wrong_type("graph", "name", GMLType::String, name.1));
             };
-            edges.push(Edge::from_gml(*edge)?);
-        }
+            Some(name)
+        } else {
+            None
+        };
+        unknown_keys::check_unknown_keys(options, &obj.pairs, &nodes, &edges, &mut warnings)?;
+        let nodes = resolve_duplicate_nodes(nodes, options.on_duplicate_node_id)?;
         Ok(Graph {
             directed,
             id,
             label,
+            name,
             nodes,
             edges,
             attrs: obj.pairs,
+            warnings,
         })
     }
     /// Transform a [GMLObject] into a graph. This expects the root node
     /// of the graph.
     ///
-    /// Note: This does not currently accept multiple graphs in a single file
-    pub fn from_gml(mut obj: GMLObject) -> Result<Self, GMLError> {
-        let graph = int_take_attribute(&mut obj.pairs, "graph");
+    /// Only the first `graph` pair is converted, see [Graph::all_from_gml] for files with several.
+    pub fn from_gml(obj: GMLObject) -> Result<Self> {
+        Self::from_gml_with(obj, &ParseOptions::default())
+    }
+    /// Transform every `graph [...]` pair of the root object into a graph, in order.
+    ///
+    /// Files with several graphs can be written with [write_graphs]. A root object without
+    /// any `graph` pair gives an empty list.
+    pub fn all_from_gml(obj: GMLObject) -> Result<Vec<Self>> {
+        let options = ParseOptions::default();
+        obj.pairs
+            .into_iter()
+            .filter(|(key, _)| *key == options.root_key)
+            .map(|(key, value)| match value {
+                GMLValue::GMLObject(graph) => Self::int_from_gml(*graph, None, &options),
+                value => Err(Error::wrong_type("root", &key, GMLType::Object, value)),
+            })
+            .collect()
+    }
+    /// Same as [Graph::from_gml] using the conversion settings of the given [ParseOptions].
+    pub fn from_gml_with(obj: GMLObject, options: &ParseOptions) -> Result<Self> {
+        Self::int_from_root(obj, None, options, false).map(|(graph, _)| graph)
+    }
+    /// Same as [Graph::from_gml] but also returns the rest of the root object, such as a
+    /// `Creator` key, with the `graph` pair removed and the other pairs in their original order.
+    pub fn from_gml_with_root(obj: GMLObject) -> Result<(Self, GMLObject)> {
+        Self::int_from_root(obj, None, &ParseOptions::default(), false)
+    }
+    // `parsed` tells that GMLObject::parse_with built `obj` and already checked the limits
+    fn int_from_root(
+        mut obj: GMLObject,
+        spans: Option<Vec<Range<usize>>>,
+        options: &ParseOptions,
+        parsed: bool,
+    ) -> Result<(Self, GMLObject)> {
+        let root_key = options.root_key.as_str();
+        let graph = int_take_attribute(&mut obj.pairs, root_key, KeyMatch::Exact);
         let Some(graph) = graph else {
-            return Err(GMLError(format!("Unable to parse graph from GMLObject")));
+            return Err(Error::missing_key("root", root_key));
         };
         let GMLValue::GMLObject(graph) = graph.1 else {
-            return Err(GMLError(format!("Failed to parse graph: {:?}. Expected graph but found invalid type.", graph.1)));
+            return Err(Error::wrong_type(
+                "root",
+                root_key,
+                GMLType::Object,
+                graph.1,
+            ));
         };
-        Self::int_from_gml(*graph)
+        if !parsed {
+            check_element_limits(&graph, options)?;
+        }
+        Ok((Self::int_from_gml(*graph, spans, options)?, obj))
+    }
+    /// Parse GML text directly into a graph.
+    ///
+    /// This is equivalent to [GMLObject::from_str] followed by [Graph::from_gml].
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str) -> Result<Self> {
+        Self::from_str_with(text, &ParseOptions::default())
     }
+    /// Parse GML text directly into a graph using the given [ParseOptions].
+    pub fn from_str_with(text: &str, options: &ParseOptions) -> Result<Self> {
+        Self::parse_str(text, options, &mut ParseState::default())
+    }
+    fn parse_str(text: &str, options: &ParseOptions, state: &mut ParseState) -> Result<Self> {
+        Self::parse_str_with_root(text, options, state).map(|(graph, _)| graph)
+    }
+    // Like parse_str, also returning the rest of the root object as from_gml_with_root does
+    fn parse_str_with_root(
+        text: &str,
+        options: &ParseOptions,
+        state: &mut ParseState,
+    ) -> Result<(Self, GMLObject)> {
+        let repaired;
+        let mut recovery = None;
+        let file = match parse_text(text) {
+            Ok(file) => file,
+            Err(err) if options.recover_brackets => {
+                let Some((text, warning)) = recover::recover_brackets(text) else {
+                    return Err(err);
+                };
+                repaired = text;
+                recovery = Some(warning);
+                parse_text(&repaired)?
+            }
+            Err(err) => return Err(err),
+        };
+        let spans = if options.spans {
+            graph_pair_spans(
+                file.clone().into_inner(),
+                &options.root_key,
+                &options.skip_keys,
+            )
+            // Records closed by recover_brackets end in the `]` added past the text
+            .map(|spans| {
+                spans
+                    .into_iter()
+                    .map(|span| span.start.min(text.len())..span.end.min(text.len()))
+                    .collect()
+            })
+        } else {
+            None
+        };
+        let root = GMLObject::parse_with(file.into_inner(), options, state)?;
+        let (mut graph, rest) = Self::int_from_root(root, spans, options, true)?;
+        graph.warnings.extend(recovery);
+        if options.record_skipped_keys {
+            graph.warnings.extend(
+                std::mem::take(&mut state.skipped)
+                    .into_iter()
+                    .map(|(key, count)| Warning::SkippedKey { key, count }),
+            );
+        }
+        Ok((graph, rest))
+    }
+    /// Sort the nodes by id. Nodes sharing an id keep their relative order.
+    ///
+    /// Without this, [Graph::nodes] is in the order the nodes were declared in the document.
+    pub fn sort_nodes_by_id(&mut self) {
+        self.nodes.sort_by_key(|node| node.id);
+    }
+    /// Sort the edges by source, then target, then label (edges without a label first).
+    ///
+    /// Without this, [Graph::edges] is in the order the edges were declared in the document.
+    pub fn sort_edges(&mut self) {
+        self.edges
+            .sort_by(|a, b| (a.source, a.target, &a.label).cmp(&(b.source, b.target, &b.label)));
+    }
+    /// Keep only the nodes for which `f` returns true, in their original order, along with
+    /// the edges between them.
+    ///
+    /// Edges are only removed when one of their endpoints was dropped, so edges that were
+    /// already dangling are left alone.
+    pub fn retain_nodes<F: FnMut(&Node) -> bool>(&mut self, mut f: F) {
+        let mut dropped = HashSet::new();
+        self.nodes.retain(|node| {
+            let keep = f(node);
+            if !keep {
+                dropped.insert(node.id);
+            }
+            keep
+        });
+        if dropped.is_empty() {
+            return;
+        }
+        // Another node may still carry a dropped id
+        for node in &self.nodes {
+            dropped.remove(&node.id);
+        }
+        self.edges
+            .retain(|edge| !dropped.contains(&edge.source) && !dropped.contains(&edge.target));
+    }
+    /// Keep only the edges for which `f` returns true, in their original order.
+    pub fn retain_edges<F: FnMut(&Edge) -> bool>(&mut self, f: F) {
+        self.edges.retain(f);
+    }
+    /// Drop every attribute whose key is not listed, using `node_keys` for the nodes,
+    /// `edge_keys` for the edges and `graph_keys` for the graph itself.
+    ///
+    /// Ids, labels, endpoints and the other keys that are read into fields are kept.
+    pub fn retain_attrs(&mut self, node_keys: &[&str], edge_keys: &[&str], graph_keys: &[&str]) {
+        let retain = |attrs: &mut Vec<(String, GMLValue)>, keys: &[&str]| {
+            attrs.retain(|(key, _)| keys.contains(&key.as_str()))
+        };
+        retain(&mut self.attrs, graph_keys);
+        for node in &mut self.nodes {
+            retain(&mut node.attrs, node_keys);
+        }
+        for edge in &mut self.edges {
+            retain(&mut edge.attrs, edge_keys);
+        }
+    }
+    /// The first edge whose `id` attribute is `id`, see [Edge::id].
+    pub fn edge_by_id(&self, id: i64) -> Option<&Edge> {
+        self.edges.iter().find(|edge| edge.id() == Some(id))
+    }
+    /// The source and target of every edge, in the order of [Graph::edges], including edges
+    /// whose endpoints are not in [Graph::nodes].
+    ///
+    /// ```
+    /// use gml_parser::Graph;
+    ///
+    /// let graph = Graph::from_str("graph [\nedge [\nsource 1\ntarget 2\n]\n]").unwrap();
+    /// for (source, target) in graph.edge_pairs() {
+    ///     println!("{} {}", source, target);
+    /// }
+    /// ```
+    pub fn edge_pairs(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.edges.iter().map(|edge| (edge.source, edge.target))
+    }
+    /// Check that node ids are unique and that every edge refers to existing nodes.
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            if !ids.insert(node.id) {
+                return Err(Error::DuplicateNodeId(node.id));
+            }
+        }
+        for edge in &self.edges {
+            if !ids.contains(&edge.source) || !ids.contains(&edge.target) {
+                return Err(Error::DanglingEdge {
+                    source: edge.source,
+                    target: edge.target,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn resolve_duplicate_nodes(nodes: Vec<Node>, policy: DuplicatePolicy) -> Result<Vec<Node>> {
+    if policy == DuplicatePolicy::KeepAll {
+        return Ok(nodes);
+    }
+    let mut next_id = nodes.iter().map(|node| node.id).max().unwrap_or(0);
+    // Position in `kept` of the first node declared with each id
+    let mut seen: HashMap<i64, usize> = HashMap::with_capacity(nodes.len());
+    let mut kept: Vec<Node> = Vec::with_capacity(nodes.len());
+    for mut node in nodes {
+        let Some(&index) = seen.get(&node.id) else {
+            seen.insert(node.id, kept.len());
+            kept.push(node);
+            continue;
+        };
+        match policy {
+            DuplicatePolicy::KeepAll => unreachable!(),
+            DuplicatePolicy::Error => return Err(Error::DuplicateNodeId(node.id)),
+            DuplicatePolicy::KeepFirst => {}
+            DuplicatePolicy::KeepLast => kept[index] = node,
+            DuplicatePolicy::MergeAttrs => {
+                let first = &mut kept[index];
+                if first.label.is_none() {
+                    first.label = node.label;
+                }
+                for (key, value) in node.attrs {
+                    if int_get_attribute(&first.attrs, &key, KeyMatch::Exact).is_none() {
+                        first.attrs.push((key, value));
+                    }
+                }
+            }
+            DuplicatePolicy::MergeLastWins => {
+                let first = &mut kept[index];
+                if node.label.is_some() {
+                    first.label = node.label;
+                }
+                // The values of each key, in the order the keys first appear
+                let mut groups: Vec<(String, Vec<GMLValue>)> = Vec::new();
+                for (key, value) in node.attrs {
+                    match groups.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, values)) => values.push(value),
+                        None => groups.push((key, vec![value])),
+                    }
+                }
+                for (key, values) in groups {
+                    let at = first.attrs.iter().position(|(k, _)| *k == key);
+                    let at = at.unwrap_or(first.attrs.len());
+                    first.attrs.retain(|(k, _)| *k != key);
+                    let pairs = values.into_iter().map(|value| (key.clone(), value));
+                    first.attrs.splice(at..at, pairs);
+                }
+            }
+            DuplicatePolicy::Renumber => {
+                next_id = next_id.checked_add(1).ok_or_else(|| {
+                    Error::Other(format!(
+                        "No id is left above {} to renumber a duplicate of node {}",
+                        i64::MAX,
+                        node.id
+                    ))
+                })?;
+                node.id = next_id;
+                kept.push(node);
+            }
+        }
+    }
+    Ok(kept)
+}
+
+// The pairs of a `key [...]` fragment, or the whole fragment when it isn't wrapped
+fn fragment_element(text: &str, key: &str) -> Result<GMLObject> {
+    let mut fragment = GMLObject::from_fragment_str(text)?;
+    if matches!(fragment.pairs.as_slice(), [(k, GMLValue::GMLObject(_))] if k == key) {
+        if let Some((_, GMLValue::GMLObject(obj))) = fragment.pairs.pop() {
+            return Ok(*obj);
+        }
+    }
+    Ok(fragment)
 }
 
 impl Node {
-    fn from_gml(mut obj: GMLObject) -> Result<Self, GMLError> {
-        let id = int_take_attribute(&mut obj.pairs, "id");
+    /// A node with the given id, no label and no attributes.
+    pub fn new(id: i64) -> Self {
+        Node {
+            id,
+            label: None,
+            attrs: Vec::new(),
+            span: None,
+        }
+    }
+    /// Parse a single node from text such as `node [\nid 1\n]`, or from its pairs with or
+    /// without the surrounding brackets, see [GMLObject::from_fragment_str].
+    pub fn from_gml_str(text: &str) -> Result<Self> {
+        Node::from_gml(fragment_element(text, "node")?)
+    }
+    fn from_gml(mut obj: GMLObject) -> Result<Self> {
+        let id = int_take_attribute(&mut obj.pairs, "id", KeyMatch::Exact);
         let Some(id) = id else {
-            return Err(GMLError(format!("Unable to parse id from node")));
+            return Err(Error::missing_key("node", "id"));
         };
         let GMLValue::GMLInt(id) = id.1 else {
-            return Err(GMLError(format!("Failed to parse node id: {:?}. Expected int but found invalid type.", id.1)));
+            return Err(Error::wrong_type("node", "id", GMLType::Int, id.1));
         };
-        let label = int_take_attribute(&mut obj.pairs, "label");
+        let label = int_take_attribute(&mut obj.pairs, "label", KeyMatch::Exact);
         let label = if let Some(label) = label {
             let GMLValue::GMLString(label) = label.1 else {
-                return Err(GMLError(format!("Failed to parse edge label: {:?}. Expected str but found invalid type.", label.1)));
+                return Err(Error::wrong_type("node", "label", GMLType::String, label.1));
             };
             Some(label)
         } else {
@@ -265,29 +1010,85 @@ impl Node {
             id,
             label,
             attrs: obj.pairs,
+            span: None,
         })
     }
+    /// The byte range of this node's `node [...]` block in the text it was parsed from.
+    ///
+    /// Only available when parsed with [ParseOptions::spans] set.
+    pub fn source_span(&self) -> Option<Range<usize>> {
+        self.span.as_deref().cloned()
+    }
 }
 impl Edge {
-    fn from_gml(mut obj: GMLObject) -> Result<Self, GMLError> {
-        let source = int_take_attribute(&mut obj.pairs, "source");
+    /// An edge between the given nodes with no label and no attributes.
+    pub fn new(source: i64, target: i64) -> Self {
+        Edge {
+            source,
+            target,
+            label: None,
+            attrs: Vec::new(),
+            span: None,
+        }
+    }
+    /// Swap the endpoints if needed so that `source <= target`. Only meaningful for edges of
+    /// undirected graphs, see [Graph::normalize_edge_orientation].
+    pub fn normalize(&mut self) {
+        if self.source > self.target {
+            std::mem::swap(&mut self.source, &mut self.target);
+        }
+    }
+    /// The `id` attribute, if it exists and is an int.
+    ///
+    /// Unlike node ids, edge ids are optional in GML and are kept with the other attributes.
+    pub fn id(&self) -> Option<i64> {
+        self.attr_i64("id")
+    }
+    /// The `directed` attribute of mixed graphs, which overrides [Graph::directed] for this
+    /// edge under [DirectednessPolicy::PerEdge]. None unless it exists and is `0` or `1`.
+    pub fn directed(&self) -> Option<bool> {
+        self.get_attribute("directed")
+            .and_then(|(_, value)| value.as_bool())
+    }
+    /// Set or, with None, remove the `directed` attribute, see [Edge::directed].
+    /// It is only written when set.
+    pub fn set_directed(&mut self, directed: Option<bool>) {
+        match directed {
+            Some(directed) => self.set_attribute("directed", directed),
+            None => {
+                self.attrs.retain(|(key, _)| key != "directed");
+            }
+        }
+    }
+    /// The number carried by the edge, taken from the first of these that has one:
+    /// 1. the `weight` attribute, if it is an int or a float
+    /// 2. the `value` attribute, likewise
+    /// 3. the first number written in the label, such as `1.00` in `P: 1.00`
+    pub fn numeric_value(&self) -> Option<f64> {
+        self.attr_f64("weight")
+            .or_else(|| self.attr_f64("value"))
+            .or_else(|| first_number(self.label.as_deref()?))
+    }
+    /// Parse a single edge from text such as `edge [\nsource 1\ntarget 2\n]`, or from its
+    /// pairs with or without the surrounding brackets, see [GMLObject::from_fragment_str].
+    pub fn from_gml_str(text: &str) -> Result<Self> {
+        Edge::from_gml(fragment_element(text, "edge")?)
+    }
+    fn from_gml(mut obj: GMLObject) -> Result<Self> {
+        let source = int_take_attribute(&mut obj.pairs, "source", KeyMatch::Exact);
         let Some(source) = source else {
-            return Err(GMLError(format!("Unable to parse source from edge")));
-        };
-        let GMLValue::GMLInt(source) = source.1 else {
-            return Err(GMLError(format!("Failed to parse edge source id: {:?}. Expected int but found invalid type.", source.1)));
+            return Err(Error::missing_key("edge", "source"));
         };
-        let target = int_take_attribute(&mut obj.pairs, "target");
+        let source = endpoint("source", source.1)?;
+        let target = int_take_attribute(&mut obj.pairs, "target", KeyMatch::Exact);
         let Some(target) = target else {
-            return Err(GMLError(format!("Unable to parse target from edge")));
+            return Err(Error::missing_key("edge", "target"));
         };
-        let GMLValue::GMLInt(target) = target.1 else {
-            return Err(GMLError(format!("Failed to parse edge source id: {:?}. Expected int but found invalid type.", target.1)));
-        };
-        let label = int_take_attribute(&mut obj.pairs, "label");
+        let target = endpoint("target", target.1)?;
+        let label = int_take_attribute(&mut obj.pairs, "label", KeyMatch::Exact);
         let label = if let Some(label) = label {
             let GMLValue::GMLString(label) = label.1 else {
-                return Err(GMLError(format!("Failed to parse edge label: {:?}. Expected str but found invalid type.", label.1)));
+                return Err(Error::wrong_type("edge", "label", GMLType::String, label.1));
             };
             Some(label)
         } else {
@@ -299,12 +1100,113 @@ impl Edge {
             target,
             label,
             attrs: obj.pairs,
+            span: None,
         })
     }
+    /// The byte range of this edge's `edge [...]` block in the text it was parsed from.
+    ///
+    /// Only available when parsed with [ParseOptions::spans] set.
+    pub fn source_span(&self) -> Option<Range<usize>> {
+        self.span.as_deref().cloned()
+    }
 }
 pub trait HasGMLAttributes {
     fn attributes(&self) -> &Vec<(String, GMLValue)>;
     fn attributes_mut(&mut self) -> &mut Vec<(String, GMLValue)>;
+    /// Whether the record has an attribute with this key
+    fn has_attribute(&self, key: &str) -> bool {
+        int_get_attribute(self.attributes(), key, KeyMatch::Exact).is_some()
+    }
+    /// References to the attributes sorted by key, without reordering the record.
+    /// Attributes sharing a key keep their relative order.
+    fn attributes_sorted(&self) -> Vec<(&str, &GMLValue)> {
+        let mut sorted: Vec<(&str, &GMLValue)> = self
+            .attributes()
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+            .collect();
+        sorted.sort_by_key(|(key, _)| *key);
+        sorted
+    }
+    /// The value at `path` within the attributes, with the path syntax of
+    /// [GMLObject::pointer]: `graphics/x` is the `x` of the first `graphics` object.
+    fn attr_at(&self, path: &str) -> Option<&GMLValue> {
+        pointer::lookup(self.attributes(), path)
+    }
+    /// The value at `path` as a float if it exists and is a float or an int,
+    /// see [HasGMLAttributes::attr_at]
+    fn attr_at_f64(&self, path: &str) -> Option<f64> {
+        self.attr_at(path).and_then(GMLValue::as_f64)
+    }
+    /// The value at `path` as a string if it exists and is a string,
+    /// see [HasGMLAttributes::attr_at]
+    fn attr_at_str(&self, path: &str) -> Option<&str> {
+        self.attr_at(path).and_then(GMLValue::as_str)
+    }
+    /// The attributes with keys of the form `prefix.rest`, as `rest` and the value.
+    fn attrs_with_prefix<'s>(
+        &'s self,
+        prefix: &'s str,
+    ) -> impl Iterator<Item = (&'s str, &'s GMLValue)> + 's {
+        self.attributes().iter().filter_map(move |(key, value)| {
+            let rest = key.strip_prefix(prefix)?.strip_prefix('.')?;
+            Some((rest, value))
+        })
+    }
+    /// Replace every non-empty object attribute with one attribute per scalar it contains,
+    /// joining the keys with dots: `graphics [ x 1 ]` becomes `graphics.x 1`.
+    ///
+    /// The attributes keep their relative order. Empty objects are left as they are.
+    fn flatten(&mut self) {
+        let attrs = self.attributes_mut();
+        *attrs = flatten::flatten_pairs(std::mem::take(attrs));
+    }
+    /// The inverse of [HasGMLAttributes::flatten]: group dotted keys into nested objects,
+    /// placed where the first key of the group was.
+    ///
+    /// Dotted keys are added to an existing object attribute with the same prefix. When the
+    /// prefix is already used by a scalar, the scalar is kept and the dotted keys are left
+    /// as they are, after the other attributes.
+    fn unflatten(&mut self) {
+        let attrs = self.attributes_mut();
+        *attrs = flatten::unflatten_pairs(std::mem::take(attrs));
+    }
+    /// Set the attribute `key`, replacing the value of the first attribute with that key or
+    /// adding it at the end. Bools are stored as `0` or `1`.
+    fn set_attribute<V: Into<GMLValue>>(&mut self, key: &str, value: V) {
+        let value = value.into();
+        let attrs = self.attributes_mut();
+        match attrs.iter_mut().find(|(k, _)| k == key) {
+            Some(attr) => attr.1 = value,
+            None => attrs.push((key.to_owned(), value)),
+        }
+    }
+    /// Like [HasGMLAttributes::set_attribute], but fails with [Error::InvalidKey] instead of
+    /// setting a key the writer can't write, see [InvalidKeyPolicy].
+    fn try_set_attribute<V: Into<GMLValue>>(&mut self, key: &str, value: V) -> Result<()> {
+        if !writer::is_writable_key(key) {
+            return Err(Error::invalid_key("attributes", key));
+        }
+        self.set_attribute(key, value);
+        Ok(())
+    }
+    /// Convert the attribute `key` to the type `to` in place.
+    ///
+    /// Ints and floats convert into each other (floats only when they have no fractional part)
+    /// and numbers convert to and from strings. Errors if the attribute is missing or its value
+    /// cannot be represented as `to`, in which case it is left unchanged.
+    fn coerce_attribute(&mut self, key: &str, to: GMLType) -> Result<()> {
+        let Some((_, value)) = self.attributes_mut().iter_mut().find(|(k, _)| k == key) else {
+            return Err(Error::missing_key("attributes", key));
+        };
+        match value.coerced(to) {
+            Some(coerced) => {
+                *value = coerced;
+                Ok(())
+            }
+            None => Err(Error::wrong_type("attributes", key, to, value.clone())),
+        }
+    }
 }
 
 pub trait ReadableGMLAttributes<'a> {
@@ -312,36 +1214,175 @@ pub trait ReadableGMLAttributes<'a> {
     fn take_attribute(&mut self, name: &str) -> Option<(String, GMLValue)>;
     /// Return a reference to the object if the key == name
     fn get_attribute(&'a self, name: &str) -> Option<&'a (String, GMLValue)>;
+    /// The attribute as a string if it exists and is a string
+    fn attr_str(&'a self, name: &str) -> Option<&'a str> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_str())
+    }
+    /// The attribute as an int if it exists and is an int
+    fn attr_i64(&'a self, name: &str) -> Option<i64> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_i64())
+    }
+    /// The attribute as a float if it exists and is a float or an int
+    fn attr_f64(&'a self, name: &str) -> Option<f64> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_f64())
+    }
+    /// The attribute as a timestamp if it exists and is one, see [GMLValue::as_datetime]
+    #[cfg(feature = "time")]
+    fn attr_datetime(&'a self, name: &str) -> Option<time::OffsetDateTime> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_datetime())
+    }
+    /// The attribute as a bool if it exists and is `0` or `1`
+    fn attr_bool(&'a self, name: &str) -> Option<bool> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_bool())
+    }
+    /// The attribute as an object if it exists and is an object
+    fn attr_object(&'a self, name: &str) -> Option<&'a GMLObject> {
+        self.get_attribute(name)
+            .and_then(|(_, value)| value.as_object())
+    }
+}
+fn int_node_from_value(value: GMLValue, span: Option<Box<Range<usize>>>) -> Result<Node> {
+    let GMLValue::GMLObject(node) = value else {
+        return Err(Error::wrong_type("graph", "node", GMLType::Object, value));
+    };
+    let mut node = Node::from_gml(*node)?;
+    node.span = span;
+    Ok(node)
+}
+fn int_edge_from_value(
+    value: GMLValue,
+    span: Option<Box<Range<usize>>>,
+    options: &ParseOptions,
+) -> Result<Edge> {
+    let GMLValue::GMLObject(mut edge) = value else {
+        return Err(Error::wrong_type("graph", "edge", GMLType::Object, value));
+    };
+    if let Some(parser) = &options.edge_endpoint_parser {
+        let has_endpoints = edge.get("source").is_some() && edge.get("target").is_some();
+        if let Some((source, target)) = (!has_endpoints).then(|| parser.endpoints(&edge)).flatten()
+        {
+            edge.pairs
+                .retain(|(key, _)| key != "source" && key != "target");
+            edge.pairs
+                .insert(0, ("source".to_owned(), GMLValue::GMLInt(source)));
+            edge.pairs
+                .insert(1, ("target".to_owned(), GMLValue::GMLInt(target)));
+        }
+    }
+    let mut edge = Edge::from_gml(*edge)?;
+    edge.span = span;
+    Ok(edge)
+}
+// Some exporters write endpoints as floats such as `1.0`, which are accepted when whole
+fn endpoint(key: &str, value: GMLValue) -> Result<i64> {
+    coerce::to_i64(&value).ok_or_else(|| Error::wrong_type("edge", key, GMLType::Int, value))
+}
+// The first run of text that reads as a decimal number, with an optional sign and exponent
+fn first_number(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+    let start = (0..bytes.len()).find(|&i| match bytes[i] {
+        b'+' | b'-' => digit_at(i + 1) || (bytes.get(i + 1) == Some(&b'.') && digit_at(i + 2)),
+        b'.' => digit_at(i + 1),
+        b => b.is_ascii_digit(),
+    })?;
+    let run = text[start..]
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')))
+        .map_or(&text[start..], |end| &text[start..start + end]);
+    // The run may end in text that isn't part of the number, such as the `-` of `1.5-2`
+    (1..=run.len())
+        .rev()
+        .find_map(|end| run[..end].parse().ok())
+}
+// Flags are 0 or 1, anything else fails in strict mode and is read as missing otherwise
+fn read_flag(
+    element: &str,
+    key: &str,
+    value: i64,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Option<bool>> {
+    match value {
+        0 => Ok(Some(false)),
+        1 => Ok(Some(true)),
+        value if options.strict => Err(Error::NotABool {
+            element: element.to_owned(),
+            key: key.to_owned(),
+            value,
+        }),
+        value => {
+            warnings.push(Warning::NotABool {
+                element: element.to_owned(),
+                key: key.to_owned(),
+                value,
+            });
+            Ok(None)
+        }
+    }
+}
+// Counts the elements of a prebuilt graph object, as GMLObject::parse_with does while parsing
+fn check_element_limits(obj: &GMLObject, options: &ParseOptions) -> Result<()> {
+    let limits = &options.limits;
+    if limits.max_nodes.is_none() && limits.max_edges.is_none() {
+        return Ok(());
+    }
+    let (mut nodes, mut edges) = (0, 0);
+    let mut count = |key: &str| match key {
+        "node" => nodes += 1,
+        "edge" => edges += 1,
+        _ => {}
+    };
+    for (key, value) in &obj.pairs {
+        count(key);
+        match value {
+            GMLValue::GMLObject(wrapper)
+                if options.flatten_wrapper && key != "node" && key != "edge" =>
+            {
+                wrapper.pairs.iter().for_each(|(key, _)| count(key));
+            }
+            _ => {}
+        }
+    }
+    if let Some(limit) = limits.max_nodes.filter(|limit| nodes > *limit) {
+        return Err(Error::TooManyNodes {
+            count: nodes,
+            limit,
+        });
+    }
+    if let Some(limit) = limits.max_edges.filter(|limit| edges > *limit) {
+        return Err(Error::TooManyEdges {
+            count: edges,
+            limit,
+        });
+    }
+    Ok(())
+}
+fn contains_elements(obj: &GMLObject) -> bool {
+    obj.pairs
+        .iter()
+        .any(|(key, _)| key == "node" || key == "edge")
 }
 fn int_take_attribute(
     attrs: &mut Vec<(String, GMLValue)>,
     name: &str,
+    mode: KeyMatch,
 ) -> Option<(String, GMLValue)> {
-    let mut index = None;
-    for (i, attr) in attrs.iter().enumerate() {
-        if attr.0 == name {
-            index = Some(i);
-            break;
-        }
-    }
-    if let Some(index) = index {
-        // remove is O(n) which would make
-        // building the graph O(n^2)
-        Some(attrs.swap_remove(index))
-    } else {
-        None
-    }
+    let index = attrs.iter().position(|attr| mode.matches(&attr.0, name));
+    // Nodes and edges are split off before any attribute is taken so the lists
+    // stay short and removing in order is cheap
+    index.map(|index| attrs.remove(index))
 }
 fn int_get_attribute<'a>(
-    attrs: &'a Vec<(String, GMLValue)>,
+    attrs: &'a [(String, GMLValue)],
     name: &str,
+    mode: KeyMatch,
 ) -> Option<&'a (String, GMLValue)> {
-    for attr in attrs {
-        if attr.0 == name {
-            return Some(&attr);
-        }
-    }
-    None
+    attrs.iter().find(|attr| mode.matches(&attr.0, name))
 }
 // Blanket impl is far better but it doesn't show up in the docs.
 // impl<'a, T> ReadableGMLAttributes<'a> for T
@@ -350,66 +1391,66 @@ fn int_get_attribute<'a>(
 // {
 //     fn take_attribute(&mut self, name: &str) -> Option<(String, GMLValue)> {
 //         let attrs = self.attributes_mut();
-//         int_take_attribute(attrs, name)
+//         int_take_attribute(attrs, name, KeyMatch::Exact)
 //     }
 //     fn get_attribute(&'a self, name: &str) -> Option<&'a (String, GMLValue)> {
 //         let attrs = self.attributes();
-//         int_get_attribute(attrs, name)
+//         int_get_attribute(attrs, name, KeyMatch::Exact)
 //     }
 // }
 impl<'a> ReadableGMLAttributes<'a> for Node {
     fn take_attribute(&mut self, name: &str) -> Option<(String, GMLValue)> {
         let attrs = self.attributes_mut();
-        int_take_attribute(attrs, name)
+        int_take_attribute(attrs, name, KeyMatch::Exact)
     }
     fn get_attribute(&'a self, name: &str) -> Option<&'a (String, GMLValue)> {
         let attrs = self.attributes();
-        int_get_attribute(attrs, name)
+        int_get_attribute(attrs, name, KeyMatch::Exact)
     }
 }
 impl<'a> ReadableGMLAttributes<'a> for Edge {
     fn take_attribute(&mut self, name: &str) -> Option<(String, GMLValue)> {
         let attrs = self.attributes_mut();
-        int_take_attribute(attrs, name)
+        int_take_attribute(attrs, name, KeyMatch::Exact)
     }
     fn get_attribute(&'a self, name: &str) -> Option<&'a (String, GMLValue)> {
         let attrs = self.attributes();
-        int_get_attribute(attrs, name)
+        int_get_attribute(attrs, name, KeyMatch::Exact)
     }
 }
 impl<'a> ReadableGMLAttributes<'a> for Graph {
     fn take_attribute(&mut self, name: &str) -> Option<(String, GMLValue)> {
         let attrs = self.attributes_mut();
-        int_take_attribute(attrs, name)
+        int_take_attribute(attrs, name, KeyMatch::Exact)
     }
     fn get_attribute(&'a self, name: &str) -> Option<&'a (String, GMLValue)> {
         let attrs = self.attributes();
-        int_get_attribute(attrs, name)
+        int_get_attribute(attrs, name, KeyMatch::Exact)
     }
 }
 
 impl HasGMLAttributes for Node {
     fn attributes(&self) -> &Vec<(String, GMLValue)> {
-        return &self.attrs;
+        &self.attrs
     }
     fn attributes_mut(&mut self) -> &mut Vec<(String, GMLValue)> {
-        return &mut self.attrs;
+        &mut self.attrs
     }
 }
 impl HasGMLAttributes for Edge {
     fn attributes(&self) -> &Vec<(String, GMLValue)> {
-        return &self.attrs;
+        &self.attrs
     }
     fn attributes_mut(&mut self) -> &mut Vec<(String, GMLValue)> {
-        return &mut self.attrs;
+        &mut self.attrs
     }
 }
 impl HasGMLAttributes for Graph {
     fn attributes(&self) -> &Vec<(String, GMLValue)> {
-        return &self.attrs;
+        &self.attrs
     }
     fn attributes_mut(&mut self) -> &mut Vec<(String, GMLValue)> {
-        return &mut self.attrs;
+        &mut self.attrs
     }
 }
 
@@ -421,14 +1462,14 @@ mod tests {
     fn parse_empty() {
         let file = fs::read_to_string("tests/empty.gml").unwrap();
         let file = GMLParser::parse(Rule::text, &file).unwrap().next().unwrap();
-        let root = GMLObject::parse(file.into_inner()).unwrap();
+        let root = GMLObject::from_pairs(file.into_inner()).unwrap();
         assert!(Graph::from_gml(root).is_ok());
     }
     #[test]
     fn parse_single() {
         let file = fs::read_to_string("tests/single.gml").unwrap();
         let file = GMLParser::parse(Rule::text, &file).unwrap().next().unwrap();
-        let root = GMLObject::parse(file.into_inner()).unwrap();
+        let root = GMLObject::from_pairs(file.into_inner()).unwrap();
         let expected = GMLObject {
             pairs: vec![(
                 "graph".into(),
@@ -444,14 +1485,14 @@ mod tests {
     fn parse_simple() {
         let file = fs::read_to_string("tests/simple.gml").unwrap();
         let file = GMLParser::parse(Rule::text, &file).unwrap().next().unwrap();
-        let root = GMLObject::parse(file.into_inner()).unwrap();
+        let root = GMLObject::from_pairs(file.into_inner()).unwrap();
         assert!(Graph::from_gml(root).is_ok());
     }
     #[test]
     fn parse_wikipedia() {
         let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
         let file = GMLParser::parse(Rule::text, &file).unwrap().next().unwrap();
-        let root = GMLObject::parse(file.into_inner()).unwrap();
+        let root = GMLObject::from_pairs(file.into_inner()).unwrap();
         let graph = Graph::from_gml(root).unwrap();
         assert_eq!(graph.id, Some(42));
         assert_eq!(graph.directed, Some(true));
@@ -464,7 +1505,7 @@ mod tests {
     fn parse_synoptic() {
         let file = fs::read_to_string("tests/synoptic.gml").unwrap();
         let file = GMLParser::parse(Rule::text, &file).unwrap().next().unwrap();
-        let root = GMLObject::parse(file.into_inner()).unwrap();
+        let root = GMLObject::from_pairs(file.into_inner()).unwrap();
         let graph = Graph::from_gml(root).unwrap();
         assert_eq!(graph.nodes.len(), 7);
         assert_eq!(graph.nodes[0].id, 0);
@@ -474,7 +1515,968 @@ mod tests {
         dbg!(&graph);
         assert_eq!(graph.edges.len(), 8);
         assert_eq!(graph.edges[0].label, Some("P: 1.00".into()));
-        assert_eq!(graph.edges[0].source, 6);
-        assert_eq!(graph.edges[0].target, 0);
+        assert_eq!(graph.edges[0].source, 0);
+        assert_eq!(graph.edges[0].target, 2);
+        assert_eq!(graph.edges[7].source, 6);
+        assert_eq!(graph.edges[7].target, 0);
+    }
+
+    #[test]
+    fn error_kinds() {
+        let err = GMLObject::from_str("graph [ ").unwrap_err();
+        assert!(err.is_syntax());
+        assert_eq!(err.kind(), ErrorKind::Syntax);
+
+        let root = GMLObject::from_str("graph [\nnode [\nlabel \"a\"\n]\n]").unwrap();
+        let err = Graph::from_gml(root).unwrap_err();
+        assert!(err.is_missing_key());
+        assert!(!err.is_syntax());
+
+        let root = GMLObject::from_str("graph [\nid \"a\"\n]").unwrap();
+        let err = Graph::from_gml(root).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+    }
+
+    #[test]
+    fn truncated_error_values() {
+        let label = "é".repeat(200_000);
+        let text = format!("graph [\nnode [\nid \"{}\"\n]\n]", label);
+        let err = Graph::from_str(&text).unwrap_err();
+        let message = err.to_string();
+        assert!(message.starts_with("GMLError: Failed to parse node id: GMLString(\"éé"));
+        assert!(message.contains("é… (200013 chars). Expected int but found invalid type."));
+        assert_eq!(message.matches('é').count(), Error::MAX_VALUE_LEN - 11);
+        assert!(format!("{:.4}", err).contains("node id: GMLS… (200013 chars)."));
+
+        let points = "point [\nx 1\ny 2\n]\n".repeat(10_000);
+        let text = format!(
+            "graph [\nnode [\nid 1\n]\nedge [\nsource [\n{}]\ntarget 1\n]\n]",
+            points
+        );
+        let message = Graph::from_str(&text).unwrap_err().to_string();
+        assert!(message.len() < 400, "{}", message);
+
+        let short = Graph::from_str("graph [\nid \"a\"\n]").unwrap_err();
+        assert!(!short.to_string().contains('…'));
+
+        // Text outside of values is cut as well
+        let line = format!("graph [\nid 1 {}\n]", "2 ".repeat(100_000));
+        let syntax = Graph::from_str(&line).unwrap_err();
+        assert!(syntax.is_syntax());
+        assert!(syntax.to_string().len() < 400);
+        let key = "k".repeat(1000);
+        let errors = [
+            Error::Other(key.clone()),
+            Error::InvalidKey {
+                element: "node".into(),
+                key: key.clone(),
+            },
+            Error::UnknownKey {
+                element: "node".into(),
+                key: key.clone(),
+                suggestion: Some(key),
+            },
+        ];
+        for err in errors {
+            let message = err.to_string();
+            assert!(message.len() < 600, "{}", message);
+            assert!(message.contains("k… (10"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn errors_compare_and_clone() {
+        let err = GMLObject::from_str("graph [ ").unwrap_err();
+        assert_eq!(err, GMLObject::from_str("graph [ ").unwrap_err());
+        assert_ne!(err, GMLObject::from_str("graph ]").unwrap_err());
+        assert_eq!(err.clone(), err);
+        assert_eq!(
+            Error::Other("message".into()),
+            Error::Other("message".into())
+        );
+
+        let missing = || Graph::from_path("tests/missing.gml").unwrap_err();
+        assert_eq!(missing(), missing());
+        assert_eq!(missing().clone().to_string(), missing().to_string());
+        let other = std::io::Error::new(std::io::ErrorKind::NotFound, "elsewhere");
+        assert_ne!(missing(), Error::from(other));
+        let errors = vec![missing(); 2];
+        assert_eq!(errors[0], errors[1]);
+    }
+
+    #[test]
+    fn parse_signed_numbers() {
+        let root = GMLObject::from_str("graph [\nvalue +7\nneg -3\nratio +1.5\n]").unwrap();
+        let graph = Graph::from_gml(root).unwrap();
+        assert_eq!(
+            graph.get_attribute("value"),
+            Some(&("value".into(), GMLValue::GMLInt(7)))
+        );
+        assert_eq!(
+            graph.get_attribute("neg"),
+            Some(&("neg".into(), GMLValue::GMLInt(-3)))
+        );
+        assert_eq!(
+            graph.get_attribute("ratio"),
+            Some(&("ratio".into(), GMLValue::GMLFloat(1.5)))
+        );
+    }
+
+    #[test]
+    fn parse_spans() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let options = ParseOptions {
+            spans: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(&file, &options).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        for node in &graph.nodes {
+            let span = node.source_span().unwrap();
+            let slice = &file[span];
+            assert!(slice.starts_with("node"));
+            assert!(slice.ends_with(']'));
+            let mut root = GMLObject::from_str(slice).unwrap();
+            let (key, value) = root.pairs.remove(0);
+            assert_eq!(key, "node");
+            let GMLValue::GMLObject(obj) = value else {
+                panic!("expected object")
+            };
+            let reparsed = Node::from_gml(*obj).unwrap();
+            assert_eq!(reparsed.id, node.id);
+            assert_eq!(reparsed.label, node.label);
+            assert_eq!(reparsed.attributes(), node.attributes());
+        }
+        for edge in &graph.edges {
+            let slice = &file[edge.source_span().unwrap()];
+            let mut root = GMLObject::from_str(slice).unwrap();
+            let (key, value) = root.pairs.remove(0);
+            assert_eq!(key, "edge");
+            let GMLValue::GMLObject(obj) = value else {
+                panic!("expected object")
+            };
+            let reparsed = Edge::from_gml(*obj).unwrap();
+            assert_eq!(
+                (reparsed.source, reparsed.target),
+                (edge.source, edge.target)
+            );
+            assert_eq!(reparsed.label, edge.label);
+        }
+
+        let graph = Graph::from_str(&file).unwrap();
+        assert!(graph.nodes.iter().all(|n| n.source_span().is_none()));
+        assert!(graph.edges.iter().all(|e| e.source_span().is_none()));
+    }
+
+    #[test]
+    fn parse_leading_zeros() {
+        let text = "graph [\nzip 00501\nzero 0\n]";
+        let graph = Graph::from_str(text).unwrap();
+        assert_eq!(
+            graph.get_attribute("zip"),
+            Some(&("zip".into(), GMLValue::GMLInt(501)))
+        );
+
+        let options = ParseOptions {
+            leading_zeros_as_strings: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(text, &options).unwrap();
+        assert_eq!(
+            graph.get_attribute("zip"),
+            Some(&("zip".into(), GMLValue::GMLString("00501".into())))
+        );
+        assert_eq!(
+            graph.get_attribute("zero"),
+            Some(&("zero".into(), GMLValue::GMLInt(0)))
+        );
+    }
+
+    #[test]
+    fn duplicate_node_policies() {
+        let file = fs::read_to_string("tests/duplicates.gml").unwrap();
+        let with = |policy| {
+            let options = ParseOptions {
+                on_duplicate_node_id: policy,
+                ..Default::default()
+            };
+            Graph::from_str_with(&file, &options)
+        };
+        let graph = with(DuplicatePolicy::KeepAll).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(matches!(graph.validate(), Err(Error::DuplicateNodeId(1))));
+
+        assert!(matches!(
+            with(DuplicatePolicy::Error),
+            Err(Error::DuplicateNodeId(1))
+        ));
+
+        let graph = with(DuplicatePolicy::KeepFirst).unwrap();
+        assert!(graph.validate().is_ok());
+        assert_eq!(graph.nodes.len(), 2);
+        let node = graph.nodes.iter().find(|n| n.id == 1).unwrap();
+        assert_eq!(node.label, Some("a".into()));
+        assert!(node.get_attribute("size").is_none());
+
+        let graph = with(DuplicatePolicy::KeepLast).unwrap();
+        assert!(graph.validate().is_ok());
+        let node = graph.nodes.iter().find(|n| n.id == 1).unwrap();
+        assert_eq!(node.label, Some("c".into()));
+        assert!(node.get_attribute("color").is_none());
+
+        let graph = with(DuplicatePolicy::MergeAttrs).unwrap();
+        assert!(graph.validate().is_ok());
+        assert_eq!(graph.nodes.len(), 2);
+        let node = graph.nodes.iter().find(|n| n.id == 1).unwrap();
+        assert_eq!(node.label, Some("a".into()));
+        assert!(node.get_attribute("color").is_some());
+        assert!(node.get_attribute("size").is_some());
+
+        let graph = with(DuplicatePolicy::MergeLastWins).unwrap();
+        assert!(graph.validate().is_ok());
+        let node = graph.nodes.iter().find(|n| n.id == 1).unwrap();
+        assert_eq!(node.label, Some("c".into()));
+        assert!(node.get_attribute("color").is_some());
+        assert!(node.get_attribute("size").is_some());
+
+        let graph = with(DuplicatePolicy::Renumber).unwrap();
+        assert!(graph.validate().is_ok());
+        assert_eq!(graph.nodes.len(), 3);
+        let renumbered = graph.nodes.iter().find(|n| n.id == 3).unwrap();
+        assert_eq!(renumbered.label, Some("c".into()));
+        // Edges keep pointing at the original id which is now the first declared node
+        let edge = &graph.edges[0];
+        assert_eq!((edge.source, edge.target), (1, 2));
+        let source = graph.nodes.iter().find(|n| n.id == edge.source).unwrap();
+        assert_eq!(source.label, Some("a".into()));
+
+        let full = format!(
+            "graph [\nnode [\nid {}\n]\nnode [\nid 1\n]\nnode [\nid 1\n]\n]",
+            i64::MAX
+        );
+        let options = ParseOptions::default().on_duplicate_node_id(DuplicatePolicy::Renumber);
+        let err = Graph::from_str_with(&full, &options).unwrap_err();
+        assert!(matches!(err, Error::Other(_)), "{}", err);
+    }
+    #[test]
+    fn merge_duplicate_nodes() {
+        let text = "graph [\nnode [\nid 1\nx 1\ny 2\n]\nnode [\nid 2\n]\nnode [\nid 1\nlabel \"one\"\ny 5\nz 3\n]\n]";
+        let options = ParseOptions {
+            on_duplicate_node_id: DuplicatePolicy::MergeLastWins,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(text, &options).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].id, 1);
+        assert_eq!(graph.nodes[0].label, Some("one".into()));
+        let attrs: Vec<(&str, i64)> = graph.nodes[0]
+            .attrs
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_i64().unwrap()))
+            .collect();
+        assert_eq!(attrs, vec![("x", 1), ("y", 5), ("z", 3)]);
+
+        // Repeated keys are replaced as a group
+        let text = "graph [\nnode [\nid 1\ny 1\nx 1\ny 2\n]\nnode [\nid 1\ny 5\nz 3\ny 6\n]\n]";
+        let graph = Graph::from_str_with(text, &options).unwrap();
+        let attrs: Vec<(&str, i64)> = graph.nodes[0]
+            .attrs
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_i64().unwrap()))
+            .collect();
+        assert_eq!(attrs, vec![("y", 5), ("y", 6), ("x", 1), ("z", 3)]);
+    }
+    #[test]
+    fn validate_dangling_edge() {
+        let graph =
+            Graph::from_str("graph [\nnode [\nid 1\n]\nedge [\nsource 1\ntarget 2\n]\n]").unwrap();
+        assert!(matches!(
+            graph.validate(),
+            Err(Error::DanglingEdge {
+                source: 1,
+                target: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_order_is_preserved() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let expected_nodes: Vec<i64> = (0..7).collect();
+        let expected_edges = vec![
+            (0, 2),
+            (1, 2),
+            (1, 4),
+            (2, 3),
+            (3, 1),
+            (3, 3),
+            (4, 5),
+            (6, 0),
+        ];
+        let spanned = ParseOptions {
+            spans: true,
+            ..Default::default()
+        };
+        let graphs = [
+            Graph::from_gml(GMLObject::from_str(&file).unwrap()).unwrap(),
+            Graph::from_str(&file).unwrap(),
+            Graph::from_str_with(&file, &spanned).unwrap(),
+            GMLDocument::from_str(&file).unwrap().graph().unwrap(),
+        ];
+        for graph in graphs {
+            let nodes: Vec<i64> = graph.nodes.iter().map(|n| n.id).collect();
+            let edges: Vec<(i64, i64)> = graph.edges.iter().map(|e| (e.source, e.target)).collect();
+            assert_eq!(nodes, expected_nodes);
+            assert_eq!(edges, expected_edges);
+        }
+    }
+    #[test]
+    fn sort_nodes_and_edges() {
+        let text = "graph [\nnode [\nid 3\n]\nnode [\nid 1\n]\nnode [\nid 2\n]\n\
+            edge [\nsource 3\ntarget 1\nlabel \"b\"\n]\nedge [\nsource 1\ntarget 2\n]\n\
+            edge [\nsource 3\ntarget 1\nlabel \"a\"\n]\n]";
+        let mut graph = Graph::from_str(text).unwrap();
+        graph.sort_nodes_by_id();
+        graph.sort_edges();
+        let nodes: Vec<i64> = graph.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(nodes, vec![1, 2, 3]);
+        let edges: Vec<(i64, i64, Option<&str>)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.label.as_deref()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![(1, 2, None), (3, 1, Some("a")), (3, 1, Some("b"))]
+        );
+    }
+
+    #[test]
+    fn parse_name_and_label() {
+        let text = "graph [\nname \"g1\"\nlabel \"My graph\"\n]";
+        let graph = Graph::from_str(text).unwrap();
+        assert_eq!(graph.name, Some("g1".into()));
+        assert_eq!(graph.label, Some("My graph".into()));
+        assert!(graph.get_attribute("name").is_none());
+
+        let graph = Graph::from_str("graph [\nlabel \"only\"\n]").unwrap();
+        assert_eq!(graph.name, None);
+        assert_eq!(graph.label, Some("only".into()));
+    }
+
+    #[test]
+    fn parse_custom_root_key() {
+        let file = fs::read_to_string("tests/network.gml").unwrap();
+        let err = Graph::from_str(&file).unwrap_err();
+        assert!(err.is_missing_key());
+        let options = ParseOptions {
+            root_key: "network".into(),
+            spans: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(&file, &options).unwrap();
+        assert_eq!(graph.directed, Some(false));
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+        let span = graph.edges[0].source_span().unwrap();
+        assert!(file[span].starts_with("edge ["));
+    }
+
+    #[test]
+    fn coerce_attributes() {
+        let mut edge = Edge::new(1, 2);
+        edge.attrs.push(("weight".into(), GMLValue::GMLInt(3)));
+        edge.attrs
+            .push(("code".into(), GMLValue::GMLString(" 12 ".into())));
+        edge.attrs.push(("ratio".into(), GMLValue::GMLFloat(0.5)));
+        edge.coerce_attribute("weight", GMLType::Float).unwrap();
+        assert_eq!(edge.attrs[0].1, GMLValue::GMLFloat(3.0));
+        edge.coerce_attribute("weight", GMLType::Int).unwrap();
+        assert_eq!(edge.attrs[0].1, GMLValue::GMLInt(3));
+        edge.coerce_attribute("code", GMLType::Int).unwrap();
+        assert_eq!(edge.attrs[1].1, GMLValue::GMLInt(12));
+        edge.coerce_attribute("ratio", GMLType::String).unwrap();
+        assert_eq!(edge.attrs[2].1, GMLValue::GMLString("0.5".into()));
+
+        edge.attrs[2].1 = GMLValue::GMLFloat(0.5);
+        let err = edge.coerce_attribute("ratio", GMLType::Int).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+        assert_eq!(edge.attrs[2].1, GMLValue::GMLFloat(0.5));
+        let err = edge.coerce_attribute("ratio", GMLType::Object).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+        assert!(edge
+            .coerce_attribute("missing", GMLType::Int)
+            .unwrap_err()
+            .is_missing_key());
+    }
+
+    #[test]
+    fn parse_edge_endpoint_parser() {
+        let text = "graph [\nedge [\npair \"1 2\"\nweight 3\n]\nedge [\nsource 4\ntarget 5\n]\n]";
+        assert!(Graph::from_str(text).unwrap_err().is_missing_key());
+        let options = ParseOptions {
+            edge_endpoint_parser: Some(EdgeEndpointParser::new(|edge| {
+                let GMLValue::GMLString(pair) = edge.get("pair")? else {
+                    return None;
+                };
+                let (source, target) = pair.split_once(' ')?;
+                Some((source.parse().ok()?, target.parse().ok()?))
+            })),
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(text, &options).unwrap();
+        let edges: Vec<(i64, i64)> = graph.edges.iter().map(|e| (e.source, e.target)).collect();
+        assert_eq!(edges, vec![(1, 2), (4, 5)]);
+        assert_eq!(graph.edges[0].attr_i64("weight"), Some(3));
+        assert!(graph.edges[0].has_attribute("pair"));
+
+        let bad = "graph [\nedge [\npair \"1\"\n]\n]";
+        assert!(Graph::from_str_with(bad, &options)
+            .unwrap_err()
+            .is_missing_key());
+    }
+    #[test]
+    fn parse_unicode_identifiers() {
+        let text = "graph [\n\tnœud 1\n\tключ \"значение\"\n\t\"clé\" 2\n]";
+        let root = GMLObject::from_str(text).unwrap();
+        let GMLValue::GMLObject(graph) = &root.pairs[0].1 else {
+            panic!("graph is an object");
+        };
+        let keys: Vec<&str> = graph.pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["nœud", "ключ", "clé"]);
+
+        let ascii = ParseOptions {
+            unicode_identifiers: false,
+            ..Default::default()
+        };
+        let err = GMLObject::from_str_with(text, &ascii).unwrap_err();
+        assert!(err.is_syntax());
+        assert!(err.to_string().contains("line 2"));
+        let quoted = "graph [\n\t\"clé\" 2\n\tlabel \"é\"\n]";
+        assert!(Graph::from_str_with(quoted, &ascii).is_ok());
+    }
+    #[test]
+    fn sorted_attributes() {
+        let mut node = Node::new(1);
+        node.set_attribute("weight", 2);
+        node.set_attribute("color", "red");
+        node.attrs.push(("alpha".into(), GMLValue::GMLInt(1)));
+        node.attrs
+            .push(("color".into(), GMLValue::GMLString("blue".into())));
+        let sorted = node.attributes_sorted();
+        let keys: Vec<&str> = sorted.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, vec!["alpha", "color", "color", "weight"]);
+        assert_eq!(sorted[1].1, &GMLValue::GMLString("red".into()));
+        assert_eq!(sorted[2].1, &GMLValue::GMLString("blue".into()));
+        assert_eq!(node.attrs[0].0, "weight");
+    }
+    #[test]
+    fn parse_quoted_keys() {
+        let root = GMLObject::from_str("graph [\n\"label\" \"hi\"\n\"my key\" 5\n]").unwrap();
+        let graph = root.pairs[0].1.as_object().unwrap();
+        assert_eq!(graph.pairs[0].0, "label");
+        assert_eq!(graph.pairs[0].1, GMLValue::GMLString("hi".into()));
+        assert_eq!(graph.pairs[1].0, "my key");
+
+        let graph = Graph::from_str("\"graph\" [\n\"node\" [\n\"id\" 5\n]\n]").unwrap();
+        assert_eq!(graph.nodes[0].id, 5);
+        assert!(GMLObject::from_str("graph [\n\"\" 5\n]").is_err());
+    }
+
+    #[test]
+    fn retain_nodes_and_edges() {
+        let text = "graph [\nnode [\nid 1\nkeep 1\n]\nnode [\nid 2\n]\nnode [\nid 3\nkeep 1\n]\n\
+                    edge [\nsource 1\ntarget 2\n]\nedge [\nsource 3\ntarget 1\nw 5\n]\n\
+                    edge [\nsource 2\ntarget 3\n]\nedge [\nsource 1\ntarget 9\n]\n]";
+        let mut graph = Graph::from_str(text).unwrap();
+        graph.retain_nodes(|node| node.attr_i64("keep") == Some(1));
+        let ids: Vec<i64> = graph.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        let edges: Vec<(i64, i64)> = graph.edges.iter().map(|e| (e.source, e.target)).collect();
+        assert_eq!(edges, vec![(3, 1), (1, 9)]);
+
+        graph.retain_edges(|edge| edge.attr_i64("w").is_some());
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].source, 3);
+    }
+
+    #[test]
+    fn parse_root_siblings() {
+        let text = "Creator \"exporter\"\nsource_file \"a.gml\"\n\ngraph [\nnode [\nid 1\n]\n]\ngenerated_at 1700000000\n";
+        let root = GMLObject::from_str(text).unwrap();
+        let (graph, rest) = Graph::from_gml_with_root(root).unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        let keys: Vec<&str> = rest.pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["Creator", "source_file", "generated_at"]);
+        assert_eq!(rest.pairs[0].1, GMLValue::GMLString("exporter".into()));
+
+        let root = GMLObject::from_str("Creator \"exporter\"").unwrap();
+        assert!(Graph::from_gml_with_root(root)
+            .unwrap_err()
+            .is_missing_key());
+    }
+
+    #[test]
+    fn parse_flatten_wrapper() {
+        let file = fs::read_to_string("tests/wrapped.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.is_empty());
+        assert!(graph.get_attribute("nodes").is_some());
+
+        let options = ParseOptions {
+            flatten_wrapper: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(&file, &options).unwrap();
+        let ids: Vec<i64> = graph.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(graph.edges.len(), 2);
+        assert!(graph.validate().is_ok());
+        // Other keys of the wrapper are kept
+        assert!(graph.get_attribute("nodes").is_none());
+        assert_eq!(graph.attr_object("edges").unwrap().pairs[0].0, "count");
+        assert_eq!(graph.attr_object("graphics").unwrap().pairs.len(), 1);
+    }
+
+    #[test]
+    fn bool_attributes() {
+        let mut node = Node::new(1);
+        node.set_attribute("hidden", true);
+        node.set_attribute("isGroup", false);
+        node.set_attribute("size", 3);
+        assert_eq!(node.attr_bool("hidden"), Some(true));
+        assert_eq!(node.attr_bool("isGroup"), Some(false));
+        assert_eq!(node.attr_bool("size"), None);
+        node.set_attribute("hidden", false);
+        assert_eq!(node.attributes().len(), 3);
+        assert_eq!(node.attributes()[0].1, GMLValue::GMLInt(0));
+
+        let graph = Graph::from_str("graph [\ndirected 1\n]").unwrap();
+        assert_eq!(graph.directed, Some(true));
+        assert!(graph.warnings().is_empty());
+        let graph = Graph::from_str("graph [\ndirected 2\n]").unwrap();
+        assert_eq!(graph.directed, None);
+        assert_eq!(
+            graph.warnings(),
+            &[Warning::NotABool {
+                element: "graph".into(),
+                key: "directed".into(),
+                value: 2
+            }]
+        );
+        assert_eq!(
+            graph.warnings()[0].to_string(),
+            "GMLWarning: directed of graph should be 0 or 1 but is 2"
+        );
+        let strict = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let err = Graph::from_str_with("graph [\ndirected 2\n]", &strict).unwrap_err();
+        assert_eq!(
+            err,
+            Error::NotABool {
+                element: "graph".into(),
+                key: "directed".into(),
+                value: 2
+            }
+        );
+        assert_eq!(err.kind(), ErrorKind::WrongType);
+        assert_eq!(
+            err.to_string(),
+            "GMLError: directed of graph should be 0 or 1 but is 2"
+        );
+        let text =
+            "graph [\ndirected 0\nnode [\nid 1\n]\nedge [\nsource 1\ntarget 1\ndirected -1\n]\n]";
+        let graph = Graph::from_str(text).unwrap();
+        assert_eq!(graph.directed, Some(false));
+        assert_eq!(graph.edges[0].directed(), None);
+        assert_eq!(
+            graph.warnings()[0].to_string(),
+            "GMLWarning: directed of edge should be 0 or 1 but is -1"
+        );
+        assert!(Graph::from_str_with(text, &strict).is_err());
+    }
+
+    #[test]
+    fn remove_object_pairs() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut root = GMLObject::from_str(&file).unwrap();
+        let Some(GMLValue::GMLObject(mut graph)) = root.remove("graph") else {
+            panic!("graph is an object");
+        };
+        assert!(root.pairs.is_empty());
+        assert_eq!(root.remove("graph"), None);
+
+        let Some(GMLValue::GMLObject(node)) = graph.remove("node") else {
+            panic!("node is an object");
+        };
+        assert_eq!(
+            node.get("label"),
+            Some(&GMLValue::GMLString("node 1".into()))
+        );
+        let edges = graph.remove_all("edge");
+        assert_eq!(edges.len(), 3);
+        assert_eq!(edges[2].as_object().unwrap().get("source"), Some(&3.into()));
+        let keys: Vec<&str> = graph.pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["comment", "directed", "id", "label", "node", "node"]
+        );
+        assert!(graph.remove_all("edge").is_empty());
+    }
+
+    #[test]
+    fn flatten_attributes() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        let mut node = graph.nodes[0].clone();
+        let GMLValue::GMLObject(graphics) = &mut node.attributes_mut()[0].1 else {
+            panic!("synoptic nodes have graphics");
+        };
+        graphics.pairs.push((
+            "center".into(),
+            GMLObject::from_str("x 1\ny 2").unwrap().into(),
+        ));
+        graphics
+            .pairs
+            .push(("empty".into(), GMLObject { pairs: vec![] }.into()));
+        node.set_attribute("size", 3);
+        let original = node.clone();
+
+        node.flatten();
+        let keys: Vec<&str> = node.attributes().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "graphics.type",
+                "graphics.center.x",
+                "graphics.center.y",
+                "graphics.empty",
+                "size"
+            ]
+        );
+        let center: Vec<(&str, &GMLValue)> = node.attrs_with_prefix("graphics.center").collect();
+        assert_eq!(center, vec![("x", &1.into()), ("y", &2.into())]);
+        assert_eq!(node.attrs_with_prefix("graphic").count(), 0);
+        let flat = node.clone();
+
+        node.unflatten();
+        assert_eq!(node, original);
+        node.flatten();
+        assert_eq!(node, flat);
+
+        // A scalar sharing the prefix wins over the dotted keys
+        let mut node = Node::new(1);
+        node.set_attribute("a.x", 1);
+        node.set_attribute("a", 2);
+        node.set_attribute("b", 3);
+        node.unflatten();
+        let keys: Vec<&str> = node.attributes().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b", "a.x"]);
+    }
+
+    #[test]
+    fn has_attribute() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        let node = &graph.nodes[0];
+        assert!(node.has_attribute("thisIsASampleAttribute"));
+        assert!(!node.has_attribute("thisisasampleattribute"));
+        // Keys stored in fields aren't attributes
+        assert!(!node.has_attribute("label"));
+        assert!(graph.has_attribute("comment"));
+    }
+
+    #[test]
+    fn parse_skip_keys() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let full = Graph::from_str(&file).unwrap();
+        let options = ParseOptions {
+            skip_keys: ["graphics".to_owned()].into(),
+            record_skipped_keys: true,
+            spans: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(&file, &options).unwrap();
+        assert_eq!(graph.nodes.len(), full.nodes.len());
+        assert_eq!(graph.edges.len(), full.edges.len());
+        for (node, full) in graph.nodes.iter().zip(&full.nodes) {
+            assert_eq!((node.id, &node.label), (full.id, &full.label));
+            assert!(node.attributes().is_empty());
+            assert!(file[node.source_span().unwrap()].starts_with("node"));
+        }
+        assert!(graph.estimated_heap_size() < full.estimated_heap_size());
+        assert_eq!(
+            graph.warnings(),
+            &[Warning::SkippedKey {
+                key: "graphics".into(),
+                count: 7
+            }]
+        );
+
+        // Skipping is recursive and applies to scalars too
+        let options = ParseOptions {
+            skip_keys: ["type".to_owned(), "label".to_owned()].into(),
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(&file, &options).unwrap();
+        assert!(graph.warnings().is_empty());
+        assert!(graph.nodes[0].label.is_none());
+        assert!(graph.nodes[0]
+            .attr_object("graphics")
+            .unwrap()
+            .pairs
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_float_forms() {
+        let text = "graph [\nsci 1e3\nsmall -2.5E-2\nbig 99999999999999999999\n\
+                    whole 1.0\nint 1000\ninf -inf\nnan NaN\n]";
+        let graph = Graph::from_str(text).unwrap();
+        let value = |key: &str| graph.get_attribute(key).unwrap().1.clone();
+        assert_eq!(value("sci"), GMLValue::GMLFloat(1000.0));
+        assert_eq!(value("small"), GMLValue::GMLFloat(-0.025));
+        assert_eq!(value("big"), GMLValue::GMLFloat(1e20));
+        assert_eq!(value("whole"), GMLValue::GMLFloat(1.0));
+        assert_eq!(value("int"), GMLValue::GMLInt(1000));
+        assert_eq!(value("inf"), GMLValue::GMLFloat(f64::NEG_INFINITY));
+        assert!(value("nan").as_f64().unwrap().is_nan());
+
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(read.get_attribute("inf"), graph.get_attribute("inf"));
+        assert_eq!(read.get_attribute("big"), graph.get_attribute("big"));
+        assert!(GMLObject::from_str("x 1e").is_err());
+    }
+
+    #[test]
+    fn edge_ids() {
+        let text = "graph [\nnode [\nid 1\n]\nnode [\nid 2\n]\n\
+                    edge [\nid 10\nsource 1\ntarget 2\n]\n\
+                    edge [\nid 11\nsource 2\ntarget 1\n]\n\
+                    edge [\nsource 1\ntarget 1\n]\n\
+                    edge [\nid \"12\"\nsource 2\ntarget 2\n]\n]";
+        let graph = Graph::from_str(text).unwrap();
+        assert_eq!(graph.edges[0].id(), Some(10));
+        assert_eq!(graph.edges[2].id(), None);
+        assert_eq!(graph.edges[3].id(), None);
+        let edge = graph.edge_by_id(11).unwrap();
+        assert_eq!((edge.source, edge.target), (2, 1));
+        assert!(graph.edge_by_id(12).is_none());
+        assert!(graph.edge_by_id(1).is_none());
+    }
+
+    #[test]
+    fn element_fragments() {
+        let wrapped = Node::from_gml_str("node [\n\tid 4\n\tlabel \"four\"\n\tx 1.5\n]").unwrap();
+        let bare = Node::from_gml_str("[\nid 4\nlabel \"four\"\nx 1.5\n]").unwrap();
+        let pairs = Node::from_gml_str("\nid 4\nlabel \"four\"\nx 1.5\n").unwrap();
+        assert_eq!(wrapped, bare);
+        assert_eq!(wrapped, pairs);
+        assert_eq!(wrapped.label.as_deref(), Some("four"));
+        assert_eq!(
+            Node::from_gml_str(&wrapped.to_gml_string().unwrap()).unwrap(),
+            wrapped
+        );
+
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        for edge in &graph.edges {
+            assert_eq!(
+                &Edge::from_gml_str(&edge.to_gml_string().unwrap()).unwrap(),
+                edge
+            );
+        }
+        let edge = Edge::from_gml_str("[\nsource 1\ntarget 2\n]").unwrap();
+        assert_eq!((edge.source, edge.target), (1, 2));
+
+        let err = Node::from_gml_str("label \"no id\"").unwrap_err();
+        assert!(matches!(&err, Error::MissingKey { key, .. } if key == "id"));
+        let err = Edge::from_gml_str("edge [\nsource 1\n]").unwrap_err();
+        assert!(matches!(&err, Error::MissingKey { key, .. } if key == "target"));
+        assert!(Node::from_gml_str("").unwrap_err().is_missing_key());
+        assert!(Node::from_gml_str("[\nid 1\n] x").unwrap_err().is_syntax());
+
+        assert!(GMLObject::from_fragment_str("  \n")
+            .unwrap()
+            .pairs
+            .is_empty());
+        assert_eq!(
+            GMLObject::from_fragment_str("[]").unwrap(),
+            GMLObject::from_fragment_str("").unwrap()
+        );
+    }
+
+    #[test]
+    fn retain_whitelisted_attrs() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        for (i, edge) in graph.edges.iter_mut().enumerate() {
+            edge.set_attribute("weight", i as i64);
+        }
+        graph.set_attribute("license", "CC0");
+        let original = graph.clone();
+        assert!(graph.nodes.iter().any(|node| !node.attrs.is_empty()));
+
+        graph.retain_attrs(&[], &["weight"], &[]);
+        assert!(graph.attrs.is_empty());
+        assert_eq!(graph.directed, original.directed);
+        for (node, before) in graph.nodes.iter().zip(&original.nodes) {
+            assert!(node.attrs.is_empty());
+            assert_eq!((node.id, &node.label), (before.id, &before.label));
+        }
+        for (i, edge) in graph.edges.iter().enumerate() {
+            assert_eq!(
+                edge.attrs,
+                [("weight".to_owned(), GMLValue::GMLInt(i as i64))]
+            );
+            assert_eq!(edge.source, original.edges[i].source);
+        }
+    }
+
+    #[test]
+    fn parse_float_endpoints() {
+        let edge = |source: &str| {
+            Graph::from_str(&format!(
+                "graph [\nnode [\nid 1\n]\nedge [\nsource {}\ntarget 1\n]\n]",
+                source
+            ))
+            .map(|graph| graph.edges[0].source)
+        };
+        assert_eq!(edge("1.0"), Ok(1));
+        assert_eq!(edge("-4.0"), Ok(-4));
+        assert_eq!(edge("2E1"), Ok(20));
+        assert!(matches!(
+            edge("1.5"),
+            Err(Error::WrongType { key, found: GMLValue::GMLFloat(_), .. }) if key == "source"
+        ));
+        assert_eq!(edge("nan").unwrap_err().kind(), ErrorKind::WrongType);
+        assert_eq!(edge("inf").unwrap_err().kind(), ErrorKind::WrongType);
+        assert_eq!(edge("1e300").unwrap_err().kind(), ErrorKind::WrongType);
+        let edge = Edge::from_gml_str("source 3\ntarget 7.0").unwrap();
+        assert_eq!((edge.source, edge.target), (3, 7));
+    }
+
+    #[test]
+    fn parse_equals_separator() {
+        let text = "graph [\nnode [\nid = 5\nlabel= \"a\"\n]\nnode [\nid 6\n]\nedge [\nsource =5\ntarget = 6\ngraphics = [\nx = 1.5\n]\n]\n]";
+        let err = Graph::from_str(text).unwrap_err();
+        assert!(err.is_syntax());
+        assert!(err
+            .to_string()
+            .contains("`=` before the value of \"id\" on line 3"));
+        assert!(GMLObject::from_str("x = 1").is_err());
+
+        let options = ParseOptions {
+            allow_equals_separator: true,
+            ..Default::default()
+        };
+        let graph = Graph::from_str_with(text, &options).unwrap();
+        assert_eq!(graph.nodes[0].id, 5);
+        assert_eq!(graph.nodes[0].label.as_deref(), Some("a"));
+        assert_eq!((graph.edges[0].source, graph.edges[0].target), (5, 6));
+        assert_eq!(graph.edges[0].attr_at_f64("graphics/x"), Some(1.5));
+        let plain = Graph::from_str(&text.replace(" = ", " ").replace('=', " ")).unwrap();
+        assert_eq!(graph.nodes, plain.nodes);
+        assert_eq!(graph.edges, plain.edges);
+
+        // The separator is not a value
+        assert!(GMLObject::from_str_with("x =", &options).is_err());
+        assert!(GMLObject::from_str_with("x = = 1", &options).is_err());
+    }
+
+    #[test]
+    fn parse_scalar_lists() {
+        let root = GMLObject::from_str("levels [ 1 2 3 ]").unwrap();
+        let ints = [
+            GMLValue::GMLInt(1),
+            GMLValue::GMLInt(2),
+            GMLValue::GMLInt(3),
+        ];
+        assert_eq!(
+            root.get("levels").and_then(GMLValue::as_list),
+            Some(&ints[..])
+        );
+        assert_eq!(root.get("levels").unwrap().gml_type(), GMLType::List);
+
+        let text = "graph [\nnode [\nid 1\nnames [\"a\" 'b' -1.5]\n]\n]";
+        let graph = Graph::from_str(text).unwrap();
+        let names = graph.nodes[0].attr_at("names").and_then(GMLValue::as_list);
+        assert_eq!(
+            names,
+            Some(
+                &[
+                    GMLValue::GMLString("a".into()),
+                    GMLValue::GMLString("b".into()),
+                    GMLValue::GMLFloat(-1.5),
+                ][..]
+            )
+        );
+        let written = graph.to_gml().unwrap();
+        assert!(written.contains("names [ \"a\" \"b\" -1.5 ]\n"));
+        assert_eq!(Graph::from_str(&written).unwrap().nodes, graph.nodes);
+
+        // Pairs inside brackets are still objects
+        let root = GMLObject::from_str("x [\n\"id\" 5\n]").unwrap();
+        assert!(root.get("x").unwrap().as_object().is_some());
+        assert!(GMLObject::from_str("x [ 1 y 2 ]").is_err());
+        assert!(GMLObject::from_str("x [\n1 2\n]").is_err());
+
+        let nested = GMLObject {
+            pairs: vec![(
+                "x".into(),
+                GMLValue::GMLList(vec![GMLValue::GMLList(Vec::new())]),
+            )],
+        };
+        let err = nested
+            .write_gml(Vec::new(), &SerializeOptions::default())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn edge_pairs() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.edges.push(Edge::new(99, 0));
+        let pairs: Vec<(i64, i64)> = graph.edge_pairs().collect();
+        assert_eq!(pairs.len(), graph.edges.len());
+        for (pair, edge) in pairs.iter().zip(&graph.edges) {
+            assert_eq!(*pair, (edge.source, edge.target));
+        }
+        assert_eq!(pairs.last(), Some(&(99, 0)));
+        assert_eq!(Graph::new().edge_pairs().next(), None);
+    }
+
+    #[test]
+    fn edge_numeric_value() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let edge = &mut graph.edges[0];
+        assert_eq!(edge.label.as_deref(), Some("P: 1.00"));
+        assert_eq!(edge.numeric_value(), Some(1.0));
+        edge.set_attribute("value", 3);
+        assert_eq!(edge.numeric_value(), Some(3.0));
+        edge.set_attribute("weight", 0.25);
+        assert_eq!(edge.numeric_value(), Some(0.25));
+        edge.set_attribute("weight", "heavy");
+        assert_eq!(edge.numeric_value(), Some(3.0));
+        assert_eq!(graph.edges[1].numeric_value(), Some(0.67));
+
+        let mut edge = Edge::new(1, 2);
+        assert_eq!(edge.numeric_value(), None);
+        for (label, value) in [
+            ("-2.5e1 km", Some(-25.0)),
+            ("range 1.5-2", Some(1.5)),
+            ("v.5", Some(0.5)),
+            ("x-.5", Some(-0.5)),
+            ("3e", Some(3.0)),
+            ("none - here.", None),
+        ] {
+            edge.label = Some(label.into());
+            assert_eq!(edge.numeric_value(), value, "{}", label);
+        }
     }
 }
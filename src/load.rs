@@ -0,0 +1,276 @@
+//! Loading every GML file of a directory, see [load_dir].
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Graph, ParseOptions, Result};
+
+/// Settings for [load_dir].
+#[derive(Debug, Clone, Default)]
+pub struct LoadDirOptions {
+    /// Also load the files of subdirectories, at any depth
+    pub recursive: bool,
+    /// Only load files whose name matches this pattern, where `*` matches any number of
+    /// characters and `?` a single one, e.g. `*.gml`. Every file is loaded when None.
+    pub pattern: Option<String>,
+    /// Used to parse every file
+    pub parse: ParseOptions,
+    /// The most files parsed at the same time, one per CPU when None
+    pub max_parallelism: Option<usize>,
+}
+
+/// Parse every file in the directory `path` in parallel.
+///
+/// Each file is read like [Graph::from_path], so compressed files are supported with the
+/// `gzip` feature, and parsed with [LoadDirOptions::parse]. A file that fails to load doesn't
+/// affect the others. A directory that can't be listed is reported with its own path, and so
+/// is an entry of it that can't be read, without stopping the listing.
+/// Symbolic links are followed, but a directory reached again through one is only listed once.
+/// The results are sorted by path.
+///
+/// Files are parsed on the rayon thread pool with the `rayon` feature and on scoped threads
+/// otherwise.
+pub fn load_dir<P: AsRef<Path>>(
+    path: P,
+    options: &LoadDirOptions,
+) -> Vec<(PathBuf, Result<Graph>)> {
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    collect_files(
+        path.as_ref(),
+        options,
+        &mut HashSet::new(),
+        &mut files,
+        &mut failed,
+    );
+    files.sort();
+    let load = |path: &PathBuf| {
+        let graph = Graph::from_path_with(path, &options.parse);
+        (path.clone(), graph)
+    };
+    let mut results = load_all(&files, options.max_parallelism, load);
+    results.extend(failed);
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+// `visited` holds the canonical paths of the directories listed so far, so that a symbolic
+// link back to a parent doesn't recurse forever
+fn collect_files(
+    dir: &Path,
+    options: &LoadDirOptions,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+    failed: &mut Vec<(PathBuf, Result<Graph>)>,
+) {
+    let real = match fs::canonicalize(dir) {
+        Ok(real) => real,
+        Err(err) => return failed.push((dir.to_owned(), Err(Error::from(err)))),
+    };
+    if !visited.insert(real) {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => return failed.push((dir.to_owned(), Err(Error::from(err)))),
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                failed.push((dir.to_owned(), Err(Error::from(err))));
+                continue;
+            }
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            if options.recursive {
+                collect_files(&path, options, visited, files, failed);
+            }
+            continue;
+        }
+        let name = entry.file_name();
+        let matches = match &options.pattern {
+            Some(pattern) => glob_match(pattern, &name.to_string_lossy()),
+            None => true,
+        };
+        if matches {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn load_all<T, F>(files: &[PathBuf], max_parallelism: Option<usize>, load: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&PathBuf) -> T + Send + Sync,
+{
+    use rayon::prelude::*;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(max_parallelism.unwrap_or(0))
+        .build();
+    match pool {
+        Ok(pool) => pool.install(|| files.par_iter().map(load).collect()),
+        Err(_) => files.iter().map(load).collect(),
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+fn load_all<T, F>(files: &[PathBuf], max_parallelism: Option<usize>, load: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&PathBuf) -> T + Send + Sync,
+{
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let threads = max_parallelism
+        .or_else(|| std::thread::available_parallelism().ok().map(Into::into))
+        .unwrap_or(1)
+        .clamp(1, files.len().max(1));
+    let next = AtomicUsize::new(0);
+    let mut loaded: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut loaded = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(i) else {
+                            return loaded;
+                        };
+                        loaded.push((i, load(file)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("loading a file doesn't panic"))
+            .collect()
+    });
+    loaded.sort_by_key(|(i, _)| *i);
+    loaded.into_iter().map(|(_, result)| result).collect()
+}
+
+// Matches `*` and `?` wildcards against the whole name
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, n));
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star;
+                    n = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("*.gml", "a.gml"));
+        assert!(glob_match("*.gml", ".gml"));
+        assert!(!glob_match("*.gml", "a.gml.gz"));
+        assert!(glob_match("*.gml*", "a.gml.gz"));
+        assert!(glob_match("net?.gml", "net1.gml"));
+        assert!(!glob_match("net?.gml", "net10.gml"));
+        assert!(glob_match("*a*b*", "xxaxxbxx"));
+        assert!(!glob_match("*a*b", "xxaxxbxx"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("a", ""));
+    }
+
+    #[test]
+    fn load_directory() {
+        let dir = std::env::temp_dir().join(format!("gml_parser_load_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::copy("tests/wikipedia.gml", dir.join("a.gml")).unwrap();
+        fs::copy("tests/synoptic.gml", dir.join("nested/b.gml")).unwrap();
+        fs::copy("tests/wikipedia.gml.gz", dir.join("c.gml.gz")).unwrap();
+        fs::write(dir.join("bad.gml"), "graph [").unwrap();
+        fs::write(dir.join("corrupt.gml.gz"), [0x1f, 0x8b, 0, 1, 2, 3]).unwrap();
+        fs::write(dir.join("notes.txt"), "not gml").unwrap();
+
+        let options = LoadDirOptions {
+            pattern: Some("*.gml*".into()),
+            max_parallelism: Some(2),
+            ..Default::default()
+        };
+        let results = load_dir(&dir, &options);
+        let names: Vec<_> = results
+            .iter()
+            .map(|(path, _)| path.strip_prefix(&dir).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["a.gml", "bad.gml", "c.gml.gz", "corrupt.gml.gz"]
+        );
+        assert_eq!(results[0].1.as_ref().unwrap().nodes.len(), 3);
+        assert!(results[1].1.as_ref().unwrap_err().is_syntax());
+        #[cfg(feature = "gzip")]
+        {
+            assert_eq!(results[2].1.as_ref().unwrap().nodes.len(), 3);
+            let err = results[3].1.as_ref().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Decompression);
+        }
+        #[cfg(not(feature = "gzip"))]
+        assert!(results[2].1.is_err());
+
+        let options = LoadDirOptions {
+            recursive: true,
+            pattern: Some("?.gml".into()),
+            parse: ParseOptions {
+                spans: true,
+                ..Default::default()
+            },
+            max_parallelism: None,
+        };
+        let results = load_dir(&dir, &options);
+        assert_eq!(results.len(), 2);
+        assert!(results[1].0.ends_with("nested/b.gml"));
+        let nested = results[1].1.as_ref().unwrap();
+        assert!(nested.nodes[0].source_span().is_some());
+
+        // A link back to a parent is listed once
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, dir.join("nested/parent")).unwrap();
+            std::os::unix::fs::symlink(dir.join("nested"), dir.join("linked")).unwrap();
+            let results = load_dir(&dir, &options);
+            assert_eq!(results.len(), 2);
+        }
+
+        let missing = load_dir(dir.join("missing"), &LoadDirOptions::default());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].1.as_ref().unwrap_err().kind(), ErrorKind::Io);
+    }
+}
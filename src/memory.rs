@@ -0,0 +1,126 @@
+//! Rough accounting of the memory held by parsed values.
+
+use std::mem::size_of;
+
+use crate::{Edge, GMLObject, GMLValue, Graph, Node, Warning};
+
+impl GMLObject {
+    /// An estimate of the bytes this object owns on the heap, not counting the object itself.
+    ///
+    /// Allocations are counted by capacity, so the estimate follows what the allocator
+    /// handed out rather than what is in use. Allocator overhead is ignored.
+    pub fn estimated_heap_size(&self) -> usize {
+        pairs_heap_size(&self.pairs)
+    }
+}
+
+impl Graph {
+    /// An estimate of the bytes this graph owns on the heap, not counting the graph itself.
+    ///
+    /// See [GMLObject::estimated_heap_size] for how it is computed.
+    pub fn estimated_heap_size(&self) -> usize {
+        let nodes: usize = self.nodes.iter().map(node_heap_size).sum();
+        let edges: usize = self.edges.iter().map(edge_heap_size).sum();
+        let warnings: usize = self.warnings.iter().map(warning_heap_size).sum();
+        self.nodes.capacity() * size_of::<Node>()
+            + nodes
+            + self.edges.capacity() * size_of::<Edge>()
+            + edges
+            + self.warnings.capacity() * size_of::<Warning>()
+            + warnings
+            + option_string(&self.label)
+            + option_string(&self.name)
+            + pairs_heap_size(&self.attrs)
+    }
+}
+
+fn node_heap_size(node: &Node) -> usize {
+    option_string(&node.label) + pairs_heap_size(&node.attrs) + span_heap_size(&node.span)
+}
+
+fn edge_heap_size(edge: &Edge) -> usize {
+    option_string(&edge.label) + pairs_heap_size(&edge.attrs) + span_heap_size(&edge.span)
+}
+
+fn warning_heap_size(warning: &Warning) -> usize {
+    match warning {
+        Warning::NotABool { element, key, .. } => element.capacity() + key.capacity(),
+        Warning::SkippedKey { key, .. } => key.capacity(),
+        Warning::UnknownKey {
+            element,
+            key,
+            suggestion,
+        } => element.capacity() + key.capacity() + option_string(suggestion),
+        Warning::RecoveredBrackets { .. } => 0,
+    }
+}
+
+fn pairs_heap_size(pairs: &Vec<(String, GMLValue)>) -> usize {
+    let contents: usize = pairs
+        .iter()
+        .map(|(key, value)| key.capacity() + value_heap_size(value))
+        .sum();
+    pairs.capacity() * size_of::<(String, GMLValue)>() + contents
+}
+
+fn value_heap_size(value: &GMLValue) -> usize {
+    match value {
+        GMLValue::GMLString(s) => s.capacity(),
+        GMLValue::GMLInt(_) | GMLValue::GMLFloat(_) => 0,
+        GMLValue::GMLObject(obj) => size_of::<GMLObject>() + obj.estimated_heap_size(),
+        GMLValue::GMLList(items) => {
+            items.capacity() * size_of::<GMLValue>()
+                + items.iter().map(value_heap_size).sum::<usize>()
+        }
+    }
+}
+
+fn option_string(s: &Option<String>) -> usize {
+    s.as_ref().map_or(0, String::capacity)
+}
+
+fn span_heap_size<T>(span: &Option<Box<T>>) -> usize {
+    span.as_ref().map_or(0, |_| size_of::<T>())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn generated(nodes: usize) -> String {
+        let mut text = String::from("graph [\n");
+        for id in 0..nodes {
+            text.push_str(&format!(
+                "node [\nid {}\nlabel \"node number {}\"\ngraphics [\nx 1.5\ny 2.5\n]\n]\n",
+                id, id
+            ));
+            if id > 0 {
+                text.push_str(&format!("edge [\nsource {}\ntarget {}\n]\n", id - 1, id));
+            }
+        }
+        text.push(']');
+        text
+    }
+
+    #[test]
+    fn heap_size_scales_with_content() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let small = GMLObject::from_str(&file).unwrap();
+        assert!(small.estimated_heap_size() > file.len() / 2);
+
+        let small_text = generated(100);
+        let large_text = generated(1000);
+        let small = GMLObject::from_str(&small_text).unwrap();
+        let large = GMLObject::from_str(&large_text).unwrap();
+        let ratio = large.estimated_heap_size() as f64 / small.estimated_heap_size() as f64;
+        assert!((8.0..12.0).contains(&ratio), "{}", ratio);
+
+        let small = Graph::from_str(&small_text).unwrap();
+        let large = Graph::from_str(&large_text).unwrap();
+        assert!(small.estimated_heap_size() < large.estimated_heap_size());
+        let ratio = large.estimated_heap_size() as f64 / small.estimated_heap_size() as f64;
+        assert!((8.0..12.0).contains(&ratio), "{}", ratio);
+        assert_eq!(Graph::new().estimated_heap_size(), 0);
+    }
+}
@@ -0,0 +1,463 @@
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use crate::GMLObject;
+
+/// Options controlling how GML text is turned into a [Graph](crate::Graph).
+///
+/// The default matches the behavior of [Graph::from_str](crate::Graph::from_str). The
+/// functions that parse text have a `_with` variant taking these options, such as
+/// [Graph::from_path_with](crate::Graph::from_path_with), except for [raw::parse] and
+/// [raw::tokens] which return the tokens of the grammar before any option applies. Some
+/// options are only used by some of the functions, as noted on them.
+///
+/// [raw::parse]: crate::raw::parse
+/// [raw::tokens]: crate::raw::tokens
+///
+/// The fields can be set directly or with the methods of the same name:
+/// ```
+/// use gml_parser::{DuplicatePolicy, Graph, ParseOptions};
+///
+/// let options = ParseOptions::default()
+///     .strict(true)
+///     .skip_key("graphics")
+///     .on_duplicate_node_id(DuplicatePolicy::KeepFirst)
+///     .max_pairs_per_object(1000);
+/// let graph = Graph::from_str_with("graph [\ndirected 1\n]", &options).unwrap();
+/// assert_eq!(graph.directed, Some(true));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Record the byte range of every `node [...]` and `edge [...]` block in the source text.
+    /// See [Node::source_span](crate::Node::source_span) and [Edge::source_span](crate::Edge::source_span).
+    pub spans: bool,
+    /// Keep integers written with leading zeros (`zip 00501`) as [GMLString](crate::GMLValue::GMLString)
+    /// so that the zeros survive a round trip. By default they are parsed as ints.
+    pub leading_zeros_as_strings: bool,
+    /// What to do when several nodes share the same id
+    pub on_duplicate_node_id: DuplicatePolicy,
+    /// Key of the root object holding the nodes and edges, `graph` by default.
+    /// Some exporters write e.g. `network [...]` instead.
+    pub root_key: String,
+    /// Also collect `node` and `edge` entries nested one level deeper, inside another object
+    /// of the graph such as `nodes [ node [...] node [...] ]`. Other keys of that object are
+    /// kept as a graph attribute. Nested elements don't get a source span.
+    pub flatten_wrapper: bool,
+    /// Bounds on the size of the document, none by default
+    pub limits: ParseLimits,
+    /// Keys whose values are left out wherever they appear, such as large `graphics` blocks.
+    ///
+    /// The grammar still matches the whole document, skipped values included, so they are
+    /// checked for syntax errors and the parse takes about as long as without this option.
+    /// The pairs are dropped before their values are turned into
+    /// [GMLValue](crate::GMLValue)s, which saves the memory they would take. Run
+    /// `cargo bench --bench skip_keys` to compare the timings.
+    pub skip_keys: HashSet<String>,
+    /// Add a [Warning::SkippedKey](crate::Warning::SkippedKey) to the graph for every key of
+    /// [ParseOptions::skip_keys] that was found.
+    ///
+    /// Only the parsers that build a [Graph](crate::Graph) have somewhere to put the warnings:
+    /// [Graph::from_str_with](crate::Graph::from_str_with), the functions reading files and
+    /// readers through it and `Graph::from_async_reader_with` of the `async` feature. The
+    /// [GMLObject](crate::GMLObject) parsers drop the keys without recording them.
+    pub record_skipped_keys: bool,
+    /// Reads the endpoints of edges lacking a `source` or `target` key, for dialects that
+    /// store them differently. The edge fails to parse as usual when it returns `None`.
+    pub edge_endpoint_parser: Option<EdgeEndpointParser>,
+    /// Accept letters outside of ASCII in unquoted keys, such as `nœud`. On by default;
+    /// when off such keys fail with [Error::Syntax](crate::Error::Syntax). Quoted keys may
+    /// always contain any character.
+    pub unicode_identifiers: bool,
+    /// What to do with graph, node and edge keys that are neither read into a field nor
+    /// allowed, such as a misspelled `lable`. Kept as attributes by default.
+    pub unknown_keys: UnknownKeyPolicy,
+    /// Accept an `=` between a key and its value, as in `id = 5`, written by some
+    /// nonstandard exporters. Off by default, when such pairs fail with
+    /// [Error::Syntax](crate::Error::Syntax).
+    pub allow_equals_separator: bool,
+    /// Fail with [Error::NotABool](crate::Error::NotABool) on flags such as `directed` whose
+    /// value is neither 0 nor 1. Off by default, when such a flag is read as missing and a
+    /// [Warning::NotABool](crate::Warning::NotABool) is added to the graph instead.
+    pub strict: bool,
+    /// When the brackets of the document don't match, e.g. because the last `]` is missing
+    /// or a trailing record has one too many, read the part that can be recovered instead of
+    /// failing with [Error::Syntax](crate::Error::Syntax). A
+    /// [Warning::RecoveredBrackets](crate::Warning::RecoveredBrackets) on the graph says what
+    /// was dropped. Only used when parsing a [Graph](crate::Graph) from a whole text, as by
+    /// [Graph::from_str_with](crate::Graph::from_str_with) and
+    /// [Graph::from_path_with](crate::Graph::from_path_with), off by default.
+    ///
+    /// Source spans of records that were closed this way end at the end of the text.
+    pub recover_brackets: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            spans: false,
+            leading_zeros_as_strings: false,
+            on_duplicate_node_id: DuplicatePolicy::default(),
+            root_key: "graph".to_owned(),
+            flatten_wrapper: false,
+            limits: ParseLimits::default(),
+            skip_keys: HashSet::new(),
+            record_skipped_keys: false,
+            edge_endpoint_parser: None,
+            unicode_identifiers: true,
+            unknown_keys: UnknownKeyPolicy::default(),
+            allow_equals_separator: false,
+            strict: false,
+            recover_brackets: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Set [ParseOptions::spans]
+    pub fn spans(mut self, spans: bool) -> Self {
+        self.spans = spans;
+        self
+    }
+    /// Set [ParseOptions::leading_zeros_as_strings]
+    pub fn leading_zeros_as_strings(mut self, leading_zeros_as_strings: bool) -> Self {
+        self.leading_zeros_as_strings = leading_zeros_as_strings;
+        self
+    }
+    /// Set [ParseOptions::on_duplicate_node_id]
+    pub fn on_duplicate_node_id(mut self, policy: DuplicatePolicy) -> Self {
+        self.on_duplicate_node_id = policy;
+        self
+    }
+    /// Set [ParseOptions::root_key]
+    pub fn root_key<S: Into<String>>(mut self, root_key: S) -> Self {
+        self.root_key = root_key.into();
+        self
+    }
+    /// Set [ParseOptions::flatten_wrapper]
+    pub fn flatten_wrapper(mut self, flatten_wrapper: bool) -> Self {
+        self.flatten_wrapper = flatten_wrapper;
+        self
+    }
+    /// Set [ParseOptions::limits]
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+    /// Set [ParseLimits::max_pairs_per_object] of [ParseOptions::limits]
+    pub fn max_pairs_per_object(mut self, limit: usize) -> Self {
+        self.limits.max_pairs_per_object = Some(limit);
+        self
+    }
+    /// Set [ParseLimits::max_nodes] of [ParseOptions::limits]
+    pub fn max_nodes(mut self, limit: usize) -> Self {
+        self.limits.max_nodes = Some(limit);
+        self
+    }
+    /// Set [ParseLimits::max_edges] of [ParseOptions::limits]
+    pub fn max_edges(mut self, limit: usize) -> Self {
+        self.limits.max_edges = Some(limit);
+        self
+    }
+    /// Add a key to [ParseOptions::skip_keys]
+    pub fn skip_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.skip_keys.insert(key.into());
+        self
+    }
+    /// Replace [ParseOptions::skip_keys]
+    pub fn skip_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.skip_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Set [ParseOptions::record_skipped_keys]
+    pub fn record_skipped_keys(mut self, record_skipped_keys: bool) -> Self {
+        self.record_skipped_keys = record_skipped_keys;
+        self
+    }
+    /// Set [ParseOptions::edge_endpoint_parser]
+    pub fn edge_endpoint_parser(mut self, parser: EdgeEndpointParser) -> Self {
+        self.edge_endpoint_parser = Some(parser);
+        self
+    }
+    /// Set [ParseOptions::unicode_identifiers]
+    pub fn unicode_identifiers(mut self, unicode_identifiers: bool) -> Self {
+        self.unicode_identifiers = unicode_identifiers;
+        self
+    }
+    /// Set [ParseOptions::unknown_keys]
+    pub fn unknown_keys(mut self, policy: UnknownKeyPolicy) -> Self {
+        self.unknown_keys = policy;
+        self
+    }
+    /// Set [ParseOptions::allow_equals_separator]
+    pub fn allow_equals_separator(mut self, allow_equals_separator: bool) -> Self {
+        self.allow_equals_separator = allow_equals_separator;
+        self
+    }
+    /// Set [ParseOptions::strict]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+    /// Set [ParseOptions::recover_brackets]
+    pub fn recover_brackets(mut self, recover_brackets: bool) -> Self {
+        self.recover_brackets = recover_brackets;
+        self
+    }
+}
+
+/// A function returning the source and target of an `edge [...]` object,
+/// see [ParseOptions::edge_endpoint_parser].
+///
+/// ```
+/// use gml_parser::{EdgeEndpointParser, GMLValue, Graph, ParseOptions};
+///
+/// // Edges written as `edge [ pair "1 2" ]`
+/// let pair = EdgeEndpointParser::new(|edge| {
+///     let Some(GMLValue::GMLString(pair)) = edge.get("pair") else {
+///         return None;
+///     };
+///     let (source, target) = pair.split_once(' ')?;
+///     Some((source.parse().ok()?, target.parse().ok()?))
+/// });
+/// let options = ParseOptions {
+///     edge_endpoint_parser: Some(pair),
+///     ..Default::default()
+/// };
+/// let graph = Graph::from_str_with("graph [\nedge [\npair \"1 2\"\n]\n]", &options).unwrap();
+/// assert_eq!((graph.edges[0].source, graph.edges[0].target), (1, 2));
+/// ```
+#[derive(Clone)]
+pub struct EdgeEndpointParser(Arc<EndpointFn>);
+
+type EndpointFn = dyn Fn(&GMLObject) -> Option<(i64, i64)> + Send + Sync;
+
+impl EdgeEndpointParser {
+    pub fn new<F>(parser: F) -> Self
+    where
+        F: Fn(&GMLObject) -> Option<(i64, i64)> + Send + Sync + 'static,
+    {
+        EdgeEndpointParser(Arc::new(parser))
+    }
+    pub(crate) fn endpoints(&self, edge: &GMLObject) -> Option<(i64, i64)> {
+        (self.0)(edge)
+    }
+}
+
+impl fmt::Debug for EdgeEndpointParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EdgeEndpointParser(..)")
+    }
+}
+
+/// Bounds that make parsing fail instead of building unreasonably large values,
+/// see [ParseOptions::limits].
+#[derive(Debug, Clone, Default)]
+pub struct ParseLimits {
+    /// The most pairs a single `[...]` object may contain. A larger object fails with
+    /// [Error::TooManyPairs](crate::Error::TooManyPairs).
+    ///
+    /// The check runs once the grammar has matched the whole document, before the values of
+    /// the object are built. It bounds the memory of the parsed values but not the time and
+    /// memory taken by the match itself, so limit the size of untrusted input as well.
+    pub max_pairs_per_object: Option<usize>,
+    /// The most nodes a graph may have. A larger graph fails with
+    /// [Error::TooManyNodes](crate::Error::TooManyNodes).
+    ///
+    /// When parsing text, the `node` keys of every root `graph` object, and of its wrapper
+    /// objects with [ParseOptions::flatten_wrapper], are counted as the values are built, and
+    /// the parse fails at the first one past the limit. A [GMLObject](crate::GMLObject) passed
+    /// to [Graph::from_gml_with](crate::Graph::from_gml_with) is checked before converting it.
+    pub max_nodes: Option<usize>,
+    /// The most edges a graph may have. A larger graph fails with
+    /// [Error::TooManyEdges](crate::Error::TooManyEdges), checked the same way as
+    /// [ParseLimits::max_nodes].
+    pub max_edges: Option<usize>,
+}
+
+/// How [Graph](crate::Graph) conversion treats keys that are not expected, see
+/// [ParseOptions::unknown_keys].
+///
+/// Keys read into a field, such as `id`, `label`, `source` or `directed`, are always
+/// expected. Only the keys directly inside the graph, a node or an edge are checked,
+/// not the ones of nested objects such as `graphics [...]`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum UnknownKeyPolicy {
+    /// Keep every key as an attribute
+    #[default]
+    Keep,
+    /// Keep every key but add a [Warning::UnknownKey](crate::Warning::UnknownKey) to the
+    /// graph for each one that is not allowed
+    Warn { allow: AllowedKeys },
+    /// Fail the conversion with [Error::UnknownKey](crate::Error::UnknownKey) on the first
+    /// key that is not allowed
+    Error { allow: AllowedKeys },
+}
+
+/// The attribute keys expected on each kind of element, see [UnknownKeyPolicy].
+///
+/// Unknown keys are reported with the closest expected key as a suggestion.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AllowedKeys {
+    pub graph: HashSet<String>,
+    pub node: HashSet<String>,
+    pub edge: HashSet<String>,
+}
+
+/// How [Graph](crate::Graph) conversion treats nodes that share an id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Keep every node as declared
+    #[default]
+    KeepAll,
+    /// Fail the conversion with [Error::DuplicateNodeId](crate::Error::DuplicateNodeId)
+    Error,
+    /// Keep the first declared node and drop the later ones
+    KeepFirst,
+    /// Keep the last declared node, in the position of the first one
+    KeepLast,
+    /// Keep the first declared node and add the attributes it is missing from the later ones.
+    /// The first label wins, falling back to a later one if the first node has none.
+    MergeAttrs,
+    /// Merge every node into the first declared one, with later values taking precedence.
+    /// An attribute the first node already has is overwritten in place, new attributes are
+    /// appended and the label of the last node that has one is kept. A key that repeats is
+    /// replaced as a whole: every pair of the first node with that key gives way to every
+    /// pair of the later node with that key, at the position of the first one.
+    MergeLastWins,
+    /// Give later duplicates fresh ids above the current maximum. Edges are not rewritten
+    /// so they keep referring to the first declared node. Fails with
+    /// [Error::Other](crate::Error::Other) when the ids would go past `i64::MAX`.
+    Renumber,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    // What the options can change about a parsed graph
+    type Summary = (
+        Option<bool>,
+        Vec<Node>,
+        Vec<Edge>,
+        Vec<(String, GMLValue)>,
+        Vec<Warning>,
+    );
+
+    fn summary(graph: Result<Graph>) -> Result<Summary> {
+        graph.map(|graph| {
+            (
+                graph.directed,
+                graph.nodes.clone(),
+                graph.edges.clone(),
+                graph.attributes().to_vec(),
+                graph.warnings().to_vec(),
+            )
+        })
+    }
+
+    #[test]
+    fn element_limits() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let options = ParseOptions::default().max_nodes(2);
+        let err = Graph::from_str_with(&file, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 3, limit: 2 });
+        assert_eq!(err.kind(), ErrorKind::Limit);
+        // The parse fails by itself, before there is anything to convert
+        let err = GMLObject::from_str_with(&file, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 3, limit: 2 });
+        let root = GMLObject::from_str(&file).unwrap();
+        let err = Graph::from_gml_with(root, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 3, limit: 2 });
+        let huge = format!("graph [\n{}]", "node [\nid 1\n]\n".repeat(1000));
+        let err = Graph::from_str_with(&huge, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 3, limit: 2 });
+        // Each root graph has its own count
+        let two = "graph [\nnode [\nid 1\n]\n]\ngraph [\nnode [\nid 1\n]\nnode [\nid 2\n]\n]";
+        assert!(GMLObject::from_str_with(two, &options).is_ok());
+
+        let options = ParseOptions::default().max_nodes(3).max_edges(2);
+        let err = Graph::from_str_with(&file, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyEdges { count: 3, limit: 2 });
+        let options = ParseOptions::default().max_nodes(3).max_edges(3);
+        assert_eq!(
+            Graph::from_str_with(&file, &options).unwrap().nodes.len(),
+            3
+        );
+
+        let nested = "graph [\nnodes [\nnode [\nid 1\n]\nnode [\nid 2\n]\n]\n]";
+        let options = ParseOptions::default().max_nodes(1);
+        assert!(Graph::from_str_with(nested, &options).is_ok());
+        let options = options.flatten_wrapper(true);
+        let err = Graph::from_str_with(nested, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 2, limit: 1 });
+        let err = GMLObject::from_str_with(nested, &options).unwrap_err();
+        assert_eq!(err, Error::TooManyNodes { count: 2, limit: 1 });
+        let root = GMLObject::from_str(nested).unwrap();
+        assert!(Graph::from_gml_with(root, &options).is_err());
+    }
+
+    #[test]
+    fn options_matrix() {
+        let path = "tests/options.gml";
+        let text = fs::read_to_string(path).unwrap();
+        for bits in 0..32 {
+            let set = |bit: u32| bits & (1 << bit) != 0;
+            let mut options = ParseOptions::default()
+                .leading_zeros_as_strings(set(0))
+                .record_skipped_keys(set(2))
+                .strict(set(3));
+            if set(1) {
+                options = options.skip_key("graphics");
+            }
+            if set(4) {
+                options = options.on_duplicate_node_id(DuplicatePolicy::KeepFirst);
+            }
+
+            let parsed = summary(Graph::from_str_with(&text, &options));
+            assert_eq!(
+                summary(Graph::from_reader_with(text.as_bytes(), &options)),
+                parsed
+            );
+            assert_eq!(summary(Graph::from_path_with(path, &options)), parsed);
+            let root = GMLObject::from_path_with(path, &options);
+            assert_eq!(
+                summary(root.and_then(|root| Graph::from_gml_with(root, &options))).map(|s| s.1),
+                parsed.clone().map(|s| s.1)
+            );
+            #[cfg(feature = "async")]
+            for size in [1, 7, text.len()] {
+                let mut parser = crate::stream::StreamParser::new(&options);
+                let graph = text
+                    .as_bytes()
+                    .chunks(size)
+                    .try_for_each(|chunk| parser.feed(chunk))
+                    .and_then(|_| parser.finish_graph());
+                assert_eq!(summary(graph), parsed, "options {:05b}", bits);
+            }
+
+            if set(3) {
+                assert_eq!(parsed.unwrap_err().kind(), ErrorKind::WrongType);
+                continue;
+            }
+            let (directed, nodes, _, _, warnings) = parsed.unwrap();
+            assert_eq!(directed, None);
+            assert_eq!(nodes.len(), if set(4) { 1 } else { 2 });
+            let zip = if set(0) {
+                GMLValue::GMLString("00501".into())
+            } else {
+                GMLValue::GMLInt(501)
+            };
+            assert_eq!(nodes[0].attr_at("zip"), Some(&zip));
+            assert_eq!(nodes[0].has_attribute("graphics"), !set(1));
+            let skipped = Warning::SkippedKey {
+                key: "graphics".into(),
+                count: 1,
+            };
+            assert_eq!(warnings.contains(&skipped), set(1) && set(2));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Export to the `.net` format of Pajek.
+
+use std::{collections::HashMap, fmt::Write};
+
+use crate::Graph;
+
+impl Graph {
+    /// Render the graph as a Pajek `.net` network.
+    ///
+    /// Node ids are replaced by their position among the distinct ids in increasing order,
+    /// starting at 1. Every vertex is written with its label in double quotes, or its
+    /// original id when it has none, and the first declared node wins when an id repeats.
+    /// Pajek can't escape `"` so those become `'`.
+    ///
+    /// Edges are listed under `*Arcs` when the graph is directed (`directed` is `Some(true)`)
+    /// and under `*Edges` otherwise. Edges to missing nodes are left out.
+    pub fn to_pajek(&self) -> String {
+        let mut ids: Vec<i64> = self.nodes.iter().map(|node| node.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let indices: HashMap<i64, usize> =
+            ids.iter().enumerate().map(|(i, id)| (*id, i + 1)).collect();
+        let mut labels = vec![None; ids.len()];
+        for node in &self.nodes {
+            let label = &mut labels[indices[&node.id] - 1];
+            if label.is_none() {
+                let text = node.label.clone().unwrap_or_else(|| node.id.to_string());
+                *label = Some(text.replace('"', "'"));
+            }
+        }
+
+        let mut out = String::new();
+        writeln!(out, "*Vertices {}", ids.len()).unwrap();
+        for (i, label) in labels.into_iter().enumerate() {
+            writeln!(out, "{} \"{}\"", i + 1, label.expect("every id has a node")).unwrap();
+        }
+        let section = if self.directed == Some(true) {
+            "*Arcs"
+        } else {
+            "*Edges"
+        };
+        writeln!(out, "{}", section).unwrap();
+        for edge in &self.edges {
+            if let (Some(source), Some(target)) =
+                (indices.get(&edge.source), indices.get(&edge.target))
+            {
+                writeln!(out, "{} {}", source, target).unwrap();
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn pajek_sections() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.nodes[0].id = 10;
+        graph.edges[0].source = 10;
+        graph.edges[2].target = 10;
+        graph.nodes[1].label = None;
+        graph.nodes[2].label = Some("say \"hi\"".into());
+        let mut duplicate = Node::new(10);
+        duplicate.label = Some("ignored".into());
+        graph.nodes.push(duplicate);
+        assert_eq!(
+            graph.to_pajek(),
+            "*Vertices 3\n1 \"2\"\n2 \"say 'hi'\"\n3 \"node 1\"\n*Arcs\n3 1\n1 2\n2 3\n"
+        );
+
+        graph.directed = None;
+        graph.edges.push(Edge::new(2, 99));
+        let pajek = graph.to_pajek();
+        assert!(pajek.ends_with("*Edges\n3 1\n1 2\n2 3\n"));
+        assert!(!pajek.contains("*Arcs"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Lookup of nested values by path, see [GMLObject::pointer].
+
+use crate::{GMLObject, GMLValue};
+
+impl GMLObject {
+    /// The value at `path`, a list of keys separated by `/` that are looked up one object
+    /// after the other: `graph/node/label` is the label of the first node of the first graph.
+    ///
+    /// A key may be followed by an index in brackets to pick a later pair with that key,
+    /// counting from zero: `graph/node[2]/label` is the label of the third node. Keys
+    /// containing `/` or `[` can't be looked up this way.
+    ///
+    /// ```
+    /// use gml_parser::{GMLObject, GMLValue};
+    ///
+    /// let root = GMLObject::from_str("graph [\nnode [\nid 1\n]\nnode [\nid 2\n]\n]").unwrap();
+    /// assert_eq!(root.pointer("graph/node[1]/id"), Some(&GMLValue::GMLInt(2)));
+    /// assert_eq!(root.pointer("graph/node[2]/id"), None);
+    /// ```
+    pub fn pointer(&self, path: &str) -> Option<&GMLValue> {
+        lookup(&self.pairs, path)
+    }
+}
+
+pub(crate) fn lookup<'v>(mut pairs: &'v [(String, GMLValue)], path: &str) -> Option<&'v GMLValue> {
+    let mut segments = path.split('/').peekable();
+    while let Some(segment) = segments.next() {
+        let (key, index) = match segment.strip_suffix(']').and_then(|s| s.split_once('[')) {
+            Some((key, index)) => (key, index.parse().ok()?),
+            None => (segment, 0),
+        };
+        let value = pairs
+            .iter()
+            .filter(|(k, _)| k == key)
+            .nth(index)
+            .map(|(_, value)| value)?;
+        if segments.peek().is_none() {
+            return Some(value);
+        }
+        pairs = &value.as_object()?.pairs;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn attribute_paths() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let node = &mut graph.nodes[0];
+        node.set_attribute("size", 3);
+        node.attrs.push((
+            "graphics".into(),
+            GMLValue::GMLObject(Box::new(
+                GMLObject::from_str("x 1.5\nlabel \"second\"").unwrap(),
+            )),
+        ));
+        let node = &graph.nodes[0];
+
+        assert_eq!(
+            node.attr_at("graphics/type"),
+            node.attr_object("graphics").unwrap().get("type")
+        );
+        assert_eq!(node.attr_at_str("graphics/type"), Some("oval"));
+        assert_eq!(node.attr_at_f64("graphics[1]/x"), Some(1.5));
+        assert_eq!(node.attr_at_str("graphics[1]/label"), Some("second"));
+        assert_eq!(node.attr_at("size"), Some(&GMLValue::GMLInt(3)));
+        assert_eq!(node.attr_at_f64("size"), Some(3.0));
+
+        // Missing keys, indices and intermediate objects
+        assert_eq!(node.attr_at("graphics/missing"), None);
+        assert_eq!(node.attr_at("graphics[2]/type"), None);
+        assert_eq!(node.attr_at("missing/type"), None);
+        assert_eq!(node.attr_at("size/type"), None);
+        assert_eq!(node.attr_at("graphics[x]/type"), None);
+        assert_eq!(node.attr_at_str("size"), None);
+        assert_eq!(node.attr_at(""), None);
+    }
+
+    #[test]
+    fn object_pointer() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let root = GMLObject::from_str(&file).unwrap();
+        assert_eq!(
+            root.pointer("graph/node[1]/label"),
+            Some(&GMLValue::GMLString("node 2".into()))
+        );
+        assert_eq!(
+            root.pointer("graph/edge[2]/source"),
+            Some(&GMLValue::GMLInt(3))
+        );
+        assert_eq!(
+            root.pointer("graph/node/id"),
+            root.pointer("graph/node[0]/id")
+        );
+        assert!(root.pointer("graph").unwrap().as_object().is_some());
+        assert_eq!(root.pointer("graph/node[3]/id"), None);
+        assert_eq!(root.pointer("graph/id/x"), None);
+    }
+}
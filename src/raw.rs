@@ -0,0 +1,94 @@
+//! Access to the pest parse tree underneath [GMLObject](crate::GMLObject).
+//!
+//! The tree keeps everything the higher level types throw away, such as the exact text and
+//! position of every token. Its shape follows the grammar of this crate, so the [Rule]
+//! variants and how they nest may change between minor versions as the grammar is fixed
+//! or extended. Prefer [GMLObject](crate::GMLObject) when it has what you need.
+
+use std::ops::Range;
+
+use pest::iterators::{FlatPairs, Pairs};
+
+use crate::{parse_text, Result, Rule};
+
+/// Parse `text` into the pest tree. The result holds a single [Rule::text] pair whose
+/// children alternate between [Rule::identifier] and [Rule::value], followed by [Rule::EOI].
+///
+/// No [ParseOptions](crate::ParseOptions) apply. In particular the grammar accepts `key = value`
+/// pairs, which the other parsers reject unless
+/// [ParseOptions::allow_equals_separator](crate::ParseOptions::allow_equals_separator) is set,
+/// so check whether the text of a [Rule::value] starts with `=` to tell them apart.
+pub fn parse(text: &str) -> Result<Pairs<'_, Rule>> {
+    Ok(Pairs::single(parse_text(text)?))
+}
+
+/// Every node of the parse tree of `text` in document order, parents before their children.
+/// `=` separators are accepted as in [parse].
+pub fn tokens(text: &str) -> Result<RawTokens<'_>> {
+    Ok(RawTokens {
+        inner: parse(text)?.flatten(),
+    })
+}
+
+/// A node of the parse tree, see [tokens].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawToken<'i> {
+    pub rule: Rule,
+    /// The source text covered by the token, including its children
+    pub text: &'i str,
+    /// Byte range of [RawToken::text] in the document
+    pub span: Range<usize>,
+}
+
+/// Iterator returned by [tokens].
+pub struct RawTokens<'i> {
+    inner: FlatPairs<'i, Rule>,
+}
+
+impl<'i> Iterator for RawTokens<'i> {
+    type Item = RawToken<'i>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let pair = self.inner.next()?;
+        let span = pair.as_span();
+        Some(RawToken {
+            rule: pair.as_rule(),
+            text: span.as_str(),
+            span: span.start()..span.end(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, fs};
+
+    #[test]
+    fn count_raw_tokens() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut counts: HashMap<Rule, usize> = HashMap::new();
+        for token in tokens(&file).unwrap() {
+            assert_eq!(&file[token.span.clone()], token.text);
+            *counts.entry(token.rule).or_default() += 1;
+        }
+        assert_eq!(counts[&Rule::text], 1);
+        assert_eq!(counts[&Rule::identifier], 29);
+        assert_eq!(counts[&Rule::value], 29);
+        assert_eq!(counts[&Rule::object], 7);
+        assert_eq!(counts[&Rule::string], 8);
+        assert_eq!(counts[&Rule::number], 14);
+
+        let file = parse(&file).unwrap().next().unwrap();
+        assert_eq!(file.as_rule(), Rule::text);
+        let key = file.into_inner().next().unwrap();
+        assert_eq!(key.as_str(), "graph");
+        assert!(parse("graph [").unwrap_err().is_syntax());
+
+        let values: Vec<_> = tokens("id = 1\nlabel \"a\"")
+            .unwrap()
+            .filter(|token| token.rule == Rule::value)
+            .map(|token| token.text)
+            .collect();
+        assert_eq!(values, ["= 1", "\"a\""]);
+    }
+}
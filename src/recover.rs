@@ -0,0 +1,141 @@
+//! Best-effort repair of unbalanced brackets, see [ParseOptions::recover_brackets].
+//!
+//! [ParseOptions::recover_brackets]: crate::ParseOptions::recover_brackets
+
+use pest::{error::InputLocation, Parser};
+
+use crate::{GMLParser, Rule, Warning};
+
+/// Rewrite `text`, which failed to parse, into a prefix of it followed by the `]` still
+/// missing at that point, along with a description of what was changed.
+///
+/// An extra `]` cuts the text right before it. Otherwise the missing brackets are added at the
+/// end, and if that isn't enough the text is also cut at the start of the line where the
+/// parser failed. Returns None when neither gives text that parses.
+pub(crate) fn recover_brackets(text: &str) -> Option<(String, Warning)> {
+    let (repaired, warning) = balance(text, None);
+    if warning
+        == (Warning::RecoveredBrackets {
+            closed: 0,
+            truncated_at_line: None,
+        })
+    {
+        // The brackets are fine so the error is something else
+        return None;
+    }
+    let err = match GMLParser::parse(Rule::text, &repaired) {
+        Ok(_) => return Some((repaired, warning)),
+        Err(err) => err,
+    };
+    // Errors past the original text point at the brackets added above
+    let position = match err.location {
+        InputLocation::Pos(p) | InputLocation::Span((p, _)) => p.min(text.len()),
+    };
+    let line_start = text[..position].rfind('\n').map_or(0, |i| i + 1);
+    let line = text[..line_start].matches('\n').count() + 1;
+    let (repaired, warning) = balance(&text[..line_start], Some(line));
+    GMLParser::parse(Rule::text, &repaired).ok()?;
+    Some((repaired, warning))
+}
+
+// Cut `text` at the first `]` without a matching `[`, or close the brackets left open
+fn balance(text: &str, mut truncated_at_line: Option<usize>) -> (String, Warning) {
+    let mut depth = 0usize;
+    let mut end = text.len();
+    // The quote of the string being scanned and whether the previous char was a backslash
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => {
+                end = i;
+                truncated_at_line = Some(text[..i].matches('\n').count() + 1);
+                break;
+            }
+            (None, ']') => depth -= 1,
+            (None, _) => {}
+        }
+    }
+    let mut repaired = text[..end].trim_end().to_owned();
+    for _ in 0..depth {
+        repaired.push_str("\n]");
+    }
+    let warning = Warning::RecoveredBrackets {
+        closed: depth,
+        truncated_at_line,
+    };
+    (repaired, warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn recover_brackets() {
+        let options = ParseOptions::default().recover_brackets(true);
+        let recovered = |text: &str| {
+            assert!(Graph::from_str(text).unwrap_err().is_syntax());
+            Graph::from_str_with(text, &options).unwrap()
+        };
+
+        let missing = "graph [\nnode [\nid 1\n]\nnode [\nid 2\nlabel \"a [b\"\n]\nedge [\nsource 1\ntarget 2\n]\n";
+        let graph = recovered(missing);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[1].label.as_deref(), Some("a [b"));
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(
+            graph.warnings(),
+            [Warning::RecoveredBrackets {
+                closed: 1,
+                truncated_at_line: None,
+            }]
+        );
+
+        let unclosed = "graph [\nnode [\nid 1\n]\nnode [\nid 2\n\n";
+        let graph = recovered(unclosed);
+        assert_eq!(graph.nodes.len(), 2);
+        let graph = Graph::from_str_with(unclosed, &options.clone().spans(true)).unwrap();
+        let span = graph.nodes[1].source_span().unwrap();
+        assert_eq!(&unclosed[span], "node [\nid 2\n\n");
+        assert_eq!(
+            graph.warnings()[0].to_string(),
+            "GMLWarning: Unbalanced brackets, added 2 missing `]`"
+        );
+
+        let extra = "graph [\nnode [\nid 1\n]\n]\n]\nnode [\nid 2\n]";
+        let graph = recovered(extra);
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(
+            graph.warnings()[0].to_string(),
+            "GMLWarning: Unbalanced brackets, dropped the text from line 6 on"
+        );
+
+        let cut = "graph [\nnode [\nid 1\n]\nnode [\nid 2\nlabel \"unterminated\n]\n";
+        let graph = recovered(cut);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[1].label, None);
+        assert_eq!(
+            graph.warnings(),
+            [Warning::RecoveredBrackets {
+                closed: 2,
+                truncated_at_line: Some(7),
+            }]
+        );
+
+        for text in [
+            "graph [\nnode [\nid\n]\n]\n",
+            "graph [\nnode [\nid 1 2\n]\n]\n",
+        ] {
+            assert!(Graph::from_str_with(text, &options).is_err());
+        }
+        let valid = Graph::from_str_with("graph [\nid 1\n]", &options).unwrap();
+        assert!(valid.warnings().is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! Bulk rewriting of labels, see [Graph::relabel_nodes] and [Graph::anonymize].
+
+use std::collections::BTreeMap;
+
+use crate::{rng::SplitMix64, GMLValue, Graph, Node};
+
+/// Several different values that were given the same new label,
+/// see [Graph::relabel_nodes] and [Graph::anonymize].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelCollision {
+    pub label: String,
+    /// The distinct values that became `label`, in the order they were first seen
+    pub originals: Vec<String>,
+}
+
+// Hex digits of the pseudonyms written by Graph::anonymize
+const PSEUDONYM_DIGITS: usize = 12;
+
+impl Graph {
+    /// Set the label of every node to what `f` returns for it, clearing it on None.
+    ///
+    /// Returns the new labels shared by nodes that had different labels before, sorted by
+    /// new label. Nodes that had no label are not taken into account.
+    pub fn relabel_nodes<F: FnMut(&Node) -> Option<String>>(
+        &mut self,
+        mut f: F,
+    ) -> Vec<LabelCollision> {
+        let mut sources = Sources::default();
+        for node in &mut self.nodes {
+            let label = f(node);
+            if let (Some(original), Some(label)) = (&node.label, &label) {
+                sources.record(label, original);
+            }
+            node.label = label;
+        }
+        sources.collisions()
+    }
+    /// Replace every label of the nodes, the edges and the graph itself with what `f` returns
+    /// for it. Missing labels stay missing.
+    pub fn map_labels<F: FnMut(&str) -> String>(&mut self, mut f: F) {
+        let nodes = self.nodes.iter_mut().map(|node| &mut node.label);
+        let edges = self.edges.iter_mut().map(|edge| &mut edge.label);
+        for label in nodes.chain(edges).chain([&mut self.label]).flatten() {
+            *label = f(label);
+        }
+    }
+    /// Replace the labels of the nodes and edges, and their string attributes whose key is
+    /// one of `keys`, with pseudonyms such as `anon_3f09c1a2b7de`.
+    ///
+    /// A pseudonym only depends on the original string and `seed`, so equal strings get the
+    /// same pseudonym wherever they appear, also across graphs anonymized with the same seed.
+    /// Different strings get the same pseudonym with a very small probability, those cases
+    /// are returned as by [Graph::relabel_nodes]. Nested attributes are left as they are.
+    pub fn anonymize(&mut self, seed: u64, keys: &[&str]) -> Vec<LabelCollision> {
+        self.anonymize_with_digits(seed, keys, PSEUDONYM_DIGITS)
+    }
+
+    fn anonymize_with_digits(
+        &mut self,
+        seed: u64,
+        keys: &[&str],
+        digits: usize,
+    ) -> Vec<LabelCollision> {
+        let mut sources = Sources::default();
+        let mut replace = |value: &mut String| {
+            let replacement = pseudonym(seed, value, digits);
+            sources.record(&replacement, value);
+            *value = replacement;
+        };
+        let nodes = self.nodes.iter_mut().map(|n| (&mut n.label, &mut n.attrs));
+        let edges = self.edges.iter_mut().map(|e| (&mut e.label, &mut e.attrs));
+        for (label, attrs) in nodes.chain(edges) {
+            if let Some(label) = label {
+                replace(label);
+            }
+            for (key, value) in attrs.iter_mut() {
+                if let GMLValue::GMLString(value) = value {
+                    if keys.contains(&key.as_str()) {
+                        replace(value);
+                    }
+                }
+            }
+        }
+        sources.collisions()
+    }
+}
+
+// The distinct originals of every new label
+#[derive(Default)]
+struct Sources(BTreeMap<String, Vec<String>>);
+
+impl Sources {
+    fn record(&mut self, label: &str, original: &str) {
+        let originals = self.0.entry(label.to_owned()).or_default();
+        if !originals.iter().any(|o| o == original) {
+            originals.push(original.to_owned());
+        }
+    }
+
+    fn collisions(self) -> Vec<LabelCollision> {
+        self.0
+            .into_iter()
+            .filter(|(_, originals)| originals.len() > 1)
+            .map(|(label, originals)| LabelCollision { label, originals })
+            .collect()
+    }
+}
+
+// Stable across platforms and releases, unlike the hashers of the standard library
+fn pseudonym(seed: u64, value: &str, digits: usize) -> String {
+    let mut hash = SplitMix64(seed).next_u64();
+    for byte in value.bytes() {
+        hash = SplitMix64(hash ^ u64::from(byte)).next_u64();
+    }
+    let hex = format!("{:016x}", hash);
+    format!("anon_{}", &hex[..digits])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn relabel_nodes() {
+        let file = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let collisions = graph.relabel_nodes(|node| node.label.as_ref().map(|l| l.to_uppercase()));
+        assert!(collisions.is_empty());
+        assert_eq!(graph.nodes[2].label.as_deref(), Some("B"));
+
+        graph.nodes[3].label = None;
+        let collisions = graph.relabel_nodes(|node| match node.id {
+            0 => None,
+            3 => Some("x".into()),
+            _ if node.label.as_deref() < Some("C") => Some("x".into()),
+            _ => node.label.clone(),
+        });
+        assert_eq!(
+            collisions,
+            vec![LabelCollision {
+                label: "x".into(),
+                originals: vec!["A".into(), "B".into()],
+            }]
+        );
+        assert_eq!(graph.nodes[0].label, None);
+        assert_eq!(graph.nodes[3].label.as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn map_labels() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.edges[1].label = None;
+        graph.map_labels(str::to_uppercase);
+        assert_eq!(graph.label.as_deref(), Some("HELLO, I AM A GRAPH"));
+        assert_eq!(graph.nodes[2].label.as_deref(), Some("NODE 3"));
+        assert_eq!(
+            graph.edges[0].label.as_deref(),
+            Some("EDGE FROM NODE 1 TO NODE 2")
+        );
+        assert_eq!(graph.edges[1].label, None);
+    }
+
+    #[test]
+    fn anonymize() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.nodes[0].set_attribute("owner", "alice");
+        graph.nodes[1].set_attribute("owner", "node 1");
+        graph.nodes[2].set_attribute("owner", 7);
+        graph.nodes[2].set_attribute("team", "alice");
+        let mut first = graph.clone();
+        assert!(first.anonymize(1, &["owner"]).is_empty());
+        let mut second = graph.clone();
+        second.nodes.reverse();
+        second.anonymize(1, &["owner"]);
+
+        let label = first.nodes[0].label.clone().unwrap();
+        assert!(label.starts_with("anon_") && label.len() == 17);
+        assert_eq!(first.nodes[1].attr_str("owner"), Some(label.as_str()));
+        assert_eq!(first.nodes[0].label, second.nodes[2].label);
+        assert_eq!(first.nodes[0].attributes(), second.nodes[2].attributes());
+        assert_eq!(first.nodes[2].attr_i64("owner"), Some(7));
+        assert_eq!(first.nodes[2].attr_str("team"), Some("alice"));
+        assert!(first
+            .edges
+            .iter()
+            .all(|e| e.label.as_ref().unwrap().starts_with("anon_")));
+        assert_eq!(first.label, graph.label);
+
+        let mut other_seed = graph.clone();
+        other_seed.anonymize(2, &["owner"]);
+        assert_ne!(other_seed.nodes[0].label, first.nodes[0].label);
+
+        // With a single hex digit the 40 node labels can only get 16 pseudonyms
+        let mut graph = graph.clone();
+        for (i, node) in graph.nodes.iter_mut().enumerate() {
+            node.label = Some(format!("node {}", i));
+        }
+        graph.nodes.extend((3..40).map(|i| {
+            let mut node = Node::new(i);
+            node.label = Some(format!("node {}", i));
+            node
+        }));
+        let collisions = graph.anonymize_with_digits(1, &[], 1);
+        assert!(!collisions.is_empty());
+        for collision in &collisions {
+            assert_eq!(collision.label.len(), 6);
+            assert!(collision.originals.len() > 1);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Counters collected while parsing, see [GMLObject::from_str_with_report].
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crate::{parse_text, GMLObject, Graph, ParseOptions, Result};
+
+/// What a single parse went through, for monitoring ingestion.
+///
+/// The counters describe the parsed tree, so values left out because of
+/// [ParseOptions::skip_keys] are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Key-value pairs at any depth, including those holding objects
+    pub pairs: usize,
+    /// `node` pairs of the root-level [ParseOptions::root_key] objects, including those of
+    /// wrapper objects when [ParseOptions::flatten_wrapper] is set
+    pub nodes: usize,
+    /// `edge` pairs, counted like [ParseReport::nodes]
+    pub edges: usize,
+    /// How deeply objects are nested: 0 without objects, 1 when the root-level objects
+    /// don't contain any, and so on
+    pub max_depth: usize,
+    /// Bytes of all string values, without the quotes
+    pub string_bytes: usize,
+    /// Wall-clock time of the whole call
+    pub duration: Duration,
+}
+
+// State shared by the recursive calls of GMLObject::parse_with
+#[derive(Debug, Default)]
+pub(crate) struct ParseState {
+    // Values left out because of ParseOptions::skip_keys
+    pub(crate) skipped: BTreeMap<String, usize>,
+    pub(crate) report: ParseReport,
+    // Objects around the pairs being parsed
+    pub(crate) depth: usize,
+    // Whether the pairs being parsed are those of a root-level graph object
+    pub(crate) in_graph: bool,
+    // Whether they are those of an object of the graph flattened by ParseOptions::flatten_wrapper
+    pub(crate) in_wrapper: bool,
+    // Nodes and edges of the current root-level graph object, for ParseLimits
+    pub(crate) graph_nodes: usize,
+    pub(crate) graph_edges: usize,
+}
+
+impl GMLObject {
+    /// Parse GML text like [GMLObject::from_str_with] and report what was parsed.
+    ///
+    /// The counters are collected during the parse rather than by walking the result.
+    pub fn from_str_with_report(
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<(GMLObject, ParseReport)> {
+        let start = Instant::now();
+        let file = parse_text(text)?;
+        let mut state = ParseState::default();
+        let root = GMLObject::parse_with(file.into_inner(), options, &mut state)?;
+        state.report.duration = start.elapsed();
+        Ok((root, state.report))
+    }
+}
+
+impl Graph {
+    /// Parse GML text like [Graph::from_str_with] and report what was parsed,
+    /// see [GMLObject::from_str_with_report].
+    ///
+    /// The duration includes converting the parsed text into the graph.
+    pub fn from_str_with_report(
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<(Graph, ParseReport)> {
+        let start = Instant::now();
+        let mut state = ParseState::default();
+        let graph = Graph::parse_str(text, options, &mut state)?;
+        state.report.duration = start.elapsed();
+        Ok((graph, state.report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn wikipedia_report() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let options = ParseOptions::default();
+        let (root, report) = GMLObject::from_str_with_report(&file, &options).unwrap();
+        assert_eq!(root, GMLObject::from_str(&file).unwrap());
+        assert_eq!(report.pairs, 29);
+        assert_eq!(report.nodes, 3);
+        assert_eq!(report.edges, 3);
+        assert_eq!(report.max_depth, 2);
+        assert_eq!(report.string_bytes, 137);
+
+        let (graph, graph_report) = Graph::from_str_with_report(&file, &options).unwrap();
+        assert_eq!(graph_report.nodes, graph.nodes.len());
+        assert_eq!(graph_report.edges, graph.edges.len());
+        assert_eq!(
+            ParseReport {
+                duration: report.duration,
+                ..graph_report
+            },
+            report
+        );
+    }
+
+    #[test]
+    fn report_matches_structure() {
+        fn count(obj: &GMLObject, depth: usize, report: &mut ParseReport) {
+            report.max_depth = report.max_depth.max(depth);
+            for (_, value) in &obj.pairs {
+                report.pairs += 1;
+                match value {
+                    GMLValue::GMLString(s) => report.string_bytes += s.len(),
+                    GMLValue::GMLObject(obj) => count(obj, depth + 1, report),
+                    _ => {}
+                }
+            }
+        }
+        let options = ParseOptions {
+            skip_keys: ["graphics".to_owned()].into_iter().collect(),
+            flatten_wrapper: true,
+            ..Default::default()
+        };
+        for path in ["tests/synoptic.gml", "tests/wrapped.gml"] {
+            let file = fs::read_to_string(path).unwrap();
+            for options in [ParseOptions::default(), options.clone()] {
+                let (root, report) = GMLObject::from_str_with_report(&file, &options).unwrap();
+                let mut expected = ParseReport::default();
+                count(&root, 0, &mut expected);
+                assert_eq!(report.pairs, expected.pairs, "{}", path);
+                assert_eq!(report.max_depth, expected.max_depth, "{}", path);
+                assert_eq!(report.string_bytes, expected.string_bytes, "{}", path);
+
+                let (graph, report) = Graph::from_str_with_report(&file, &options).unwrap();
+                assert_eq!(report.nodes, graph.nodes.len(), "{}", path);
+                assert_eq!(report.edges, graph.edges.len(), "{}", path);
+            }
+        }
+        assert!(Graph::from_str_with_report("graph [", &options).is_err());
+    }
+}
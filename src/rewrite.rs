@@ -0,0 +1,215 @@
+//! Reading, changing and writing back a GML file in one call, see [rewrite_file].
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    input::read_path, report::ParseState, writer::write_error, Error, GMLObject, Graph,
+    ParseOptions, Result, SerializeOptions,
+};
+
+/// Settings for [rewrite_file_with].
+#[derive(Debug, Clone, Default)]
+pub struct RewriteOptions {
+    /// Used to parse the input file
+    pub parse: ParseOptions,
+    /// Used to write the output file
+    pub serialize: SerializeOptions,
+    /// Check the changed graph with [Graph::validate] before writing it
+    pub validate: bool,
+}
+
+/// The size of the graph before and after the change, returned by [rewrite_file].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewriteReport {
+    pub nodes_before: usize,
+    pub edges_before: usize,
+    pub nodes_after: usize,
+    pub edges_after: usize,
+}
+
+/// Read the graph in `path_in` like [Graph::from_path], change it with `f`, and write it to
+/// `path_out` with the default [SerializeOptions]. `path_in` and `path_out` can be the same.
+///
+/// The other keys of the root object, such as `Creator` or `Version`, are written back
+/// unchanged and in their order, before the graph.
+///
+/// The graph is written to a temporary file next to `path_out` that then replaces it, so
+/// `path_out` is left untouched when anything fails, including `f`.
+///
+/// ```no_run
+/// use gml_parser::rewrite_file;
+///
+/// let report = rewrite_file("in.gml", "out.gml", |graph| {
+///     graph.retain_edges(|edge| edge.source != edge.target);
+///     Ok(())
+/// })
+/// .unwrap();
+/// println!("removed {} self loops", report.edges_before - report.edges_after);
+/// ```
+pub fn rewrite_file<P, Q, F>(path_in: P, path_out: Q, f: F) -> Result<RewriteReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnOnce(&mut Graph) -> Result<()>,
+{
+    rewrite_file_with(path_in, path_out, &RewriteOptions::default(), f)
+}
+
+/// Like [rewrite_file], parsing, checking and writing the graph as set in `options`.
+pub fn rewrite_file_with<P, Q, F>(
+    path_in: P,
+    path_out: Q,
+    options: &RewriteOptions,
+    f: F,
+) -> Result<RewriteReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnOnce(&mut Graph) -> Result<()>,
+{
+    let text = read_path(path_in.as_ref())?;
+    let (mut graph, mut root) =
+        Graph::parse_str_with_root(&text, &options.parse, &mut ParseState::default())?;
+    drop(text);
+    let (nodes_before, edges_before) = (graph.nodes.len(), graph.edges.len());
+    f(&mut graph)?;
+    if options.validate {
+        graph.validate()?;
+    }
+    root.pairs
+        .extend(graph.to_gml_object_with(&options.serialize).pairs);
+    let path_out = path_out.as_ref();
+    let temp = temp_path(path_out);
+    if let Err(err) = write_to(&temp, &root, &options.serialize) {
+        let _ = fs::remove_file(&temp);
+        return Err(write_error(err));
+    }
+    if let Err(err) = fs::rename(&temp, path_out) {
+        let _ = fs::remove_file(&temp);
+        return Err(Error::from(err));
+    }
+    Ok(RewriteReport {
+        nodes_before,
+        edges_before,
+        nodes_after: graph.nodes.len(),
+        edges_after: graph.edges.len(),
+    })
+}
+
+// In the same directory so that renaming it over `path` doesn't cross file systems. The
+// counter keeps rewrites running at the same time in one process apart.
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), count))
+}
+
+fn write_to(path: &Path, root: &GMLObject, options: &SerializeOptions) -> io::Result<()> {
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    let mut w = io::BufWriter::new(file);
+    root.write_gml(&mut w, options)?;
+    let file = w.into_inner().map_err(io::IntoInnerError::into_error)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn rewrite_removing_self_loops() {
+        let dir = std::env::temp_dir().join(format!("gml_parser_rewrite_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.gml");
+        let output = dir.join("out.gml");
+        fs::copy("tests/isolated.gml", &input).unwrap();
+
+        let report = rewrite_file(&input, &output, |graph| {
+            graph.retain_edges(|edge| edge.source != edge.target);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            report,
+            RewriteReport {
+                nodes_before: 4,
+                edges_before: 2,
+                nodes_after: 4,
+                edges_after: 1,
+            }
+        );
+        let written = Graph::from_path(&output).unwrap();
+        assert_eq!(written.edges, vec![Edge::new(1, 2)]);
+        assert_eq!(written.nodes, Graph::from_path(&input).unwrap().nodes);
+
+        // Failures leave the output as it was
+        let before = fs::read_to_string(&output).unwrap();
+        let failed = rewrite_file(&input, &output, |graph| {
+            graph.nodes.clear();
+            Err(Error::Other("stop".into()))
+        });
+        assert_eq!(failed.unwrap_err(), Error::Other("stop".into()));
+        let options = RewriteOptions {
+            validate: true,
+            ..Default::default()
+        };
+        let failed = rewrite_file_with(&input, &output, &options, |graph| {
+            graph.edges.push(Edge::new(1, 99));
+            Ok(())
+        });
+        assert_eq!(failed.unwrap_err().kind(), ErrorKind::Validation);
+        let failed = rewrite_file(&input, &output, |graph| {
+            graph.nodes[0].set_attribute("bad\nkey", 1);
+            Ok(())
+        });
+        assert_eq!(failed.unwrap_err().kind(), ErrorKind::InvalidKey);
+        assert_eq!(fs::read_to_string(&output).unwrap(), before);
+
+        // Rewriting in place
+        let report = rewrite_file(&input, &input, |graph| {
+            graph.remove_isolated();
+            Ok(())
+        });
+        assert_eq!(report.unwrap().nodes_after, 3);
+        assert_eq!(Graph::from_path(&input).unwrap().nodes.len(), 3);
+
+        let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn rewrite_keeps_root_keys() {
+        let dir = std::env::temp_dir().join(format!("gml_parser_root_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("graph.gml");
+        fs::write(
+            &path,
+            "Creator \"me\"\ngraph [\nnode [\nid 1\n]\n]\nVersion 2\n",
+        )
+        .unwrap();
+
+        rewrite_file(&path, &path, |graph| {
+            graph.nodes.push(Node::new(2));
+            Ok(())
+        })
+        .unwrap();
+        let root = GMLObject::from_path(&path).unwrap();
+        let keys: Vec<&str> = root.pairs.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["Creator", "Version", "graph"]);
+        assert_eq!(root.get("Creator"), Some(&GMLValue::from("me")));
+        assert_eq!(Graph::from_gml(root).unwrap().nodes.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+//! Random subsets of a [Graph] for spot checks of graphs too large to look at whole.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{rng::SplitMix64, Graph, ReadableGMLAttributes};
+
+impl Graph {
+    /// The subgraph induced by `n` nodes chosen uniformly at random.
+    ///
+    /// The result contains the chosen nodes, in their original order, and every edge between
+    /// them. The same graph and seed always give the same sample. Only the first node of every
+    /// id is considered so the sample passes [Graph::validate]. If the graph has fewer than
+    /// `n` distinct ids, all of them are kept.
+    pub fn sample_nodes(&self, n: usize, seed: u64) -> Graph {
+        let mut candidates = self.first_node_indices();
+        let chosen = choose(&mut candidates, n, &mut SplitMix64(seed));
+        let mut graph = self.empty_like();
+        let mut ids = HashSet::with_capacity(chosen.len());
+        for index in chosen {
+            let node = &self.nodes[index];
+            ids.insert(node.id);
+            graph.nodes.push(node.clone());
+        }
+        graph.edges = self
+            .edges
+            .iter()
+            .filter(|edge| ids.contains(&edge.source) && ids.contains(&edge.target))
+            .cloned()
+            .collect();
+        graph
+    }
+    /// `m` edges chosen uniformly at random together with their endpoints.
+    ///
+    /// See [Graph::sample_edges_weighted].
+    pub fn sample_edges(&self, m: usize, seed: u64) -> Graph {
+        self.sample_edges_with(m, seed, None)
+    }
+    /// `m` edges chosen at random with a probability proportional to their `weight_attr`,
+    /// together with their endpoints.
+    ///
+    /// Edges with a missing or non-numeric weight count as weight 1, edges with a weight of
+    /// zero or less are never chosen. Edges referring to missing nodes are never chosen either
+    /// so the sample passes [Graph::validate]. The chosen edges and nodes keep their original
+    /// order and the same graph and seed always give the same sample.
+    pub fn sample_edges_weighted(&self, m: usize, seed: u64, weight_attr: &str) -> Graph {
+        self.sample_edges_with(m, seed, Some(weight_attr))
+    }
+
+    fn sample_edges_with(&self, m: usize, seed: u64, weight_attr: Option<&str>) -> Graph {
+        let nodes: HashMap<i64, usize> = self
+            .first_node_indices()
+            .into_iter()
+            .map(|index| (self.nodes[index].id, index))
+            .collect();
+        let mut candidates: Vec<usize> = (0..self.edges.len())
+            .filter(|&i| {
+                let edge = &self.edges[i];
+                nodes.contains_key(&edge.source) && nodes.contains_key(&edge.target)
+            })
+            .collect();
+        let mut rng = SplitMix64(seed);
+        let chosen = match weight_attr {
+            None => choose(&mut candidates, m, &mut rng),
+            Some(attr) => {
+                // Efraimidis-Spirakis: keep the m largest u^(1/w)
+                let mut keyed: Vec<(f64, usize)> = candidates
+                    .into_iter()
+                    .filter_map(|i| {
+                        let weight = self.edges[i].attr_f64(attr).unwrap_or(1.0);
+                        let u = rng.next_f64();
+                        (weight > 0.0).then(|| (u.ln() / weight, i))
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+                let mut chosen: Vec<usize> = keyed.into_iter().take(m).map(|(_, i)| i).collect();
+                chosen.sort_unstable();
+                chosen
+            }
+        };
+
+        let mut graph = self.empty_like();
+        let mut endpoints = HashSet::new();
+        for index in chosen {
+            let edge = &self.edges[index];
+            endpoints.insert(nodes[&edge.source]);
+            endpoints.insert(nodes[&edge.target]);
+            graph.edges.push(edge.clone());
+        }
+        let mut endpoints: Vec<usize> = endpoints.into_iter().collect();
+        endpoints.sort_unstable();
+        graph.nodes = endpoints
+            .into_iter()
+            .map(|index| self.nodes[index].clone())
+            .collect();
+        graph
+    }
+    // Index of the first node declared with every id
+    fn first_node_indices(&self) -> Vec<usize> {
+        let mut seen = HashSet::with_capacity(self.nodes.len());
+        (0..self.nodes.len())
+            .filter(|&i| seen.insert(self.nodes[i].id))
+            .collect()
+    }
+}
+
+// Up to `count` of the items chosen uniformly, in their original order
+fn choose(items: &mut [usize], count: usize, rng: &mut SplitMix64) -> Vec<usize> {
+    let count = count.min(items.len());
+    // Partial Fisher-Yates shuffle
+    for i in 0..count {
+        let j = i + rng.below(items.len() - i);
+        items.swap(i, j);
+    }
+    let mut chosen = items[..count].to_vec();
+    chosen.sort_unstable();
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    fn synoptic() -> Graph {
+        Graph::from_str(&fs::read_to_string("tests/synoptic.gml").unwrap()).unwrap()
+    }
+    fn ids(graph: &Graph) -> Vec<i64> {
+        graph.nodes.iter().map(|node| node.id).collect()
+    }
+    fn edges(graph: &Graph) -> Vec<(i64, i64)> {
+        graph.edges.iter().map(|e| (e.source, e.target)).collect()
+    }
+
+    #[test]
+    fn sample_nodes() {
+        let graph = synoptic();
+        for seed in 0..20 {
+            let sample = graph.sample_nodes(4, seed);
+            assert_eq!(sample.nodes.len(), 4);
+            assert!(sample.validate().is_ok());
+            assert_eq!(sample.directed, graph.directed);
+            let again = graph.sample_nodes(4, seed);
+            assert_eq!(ids(&again), ids(&sample));
+            assert_eq!(edges(&again), edges(&sample));
+            // Induced: every original edge between sampled nodes is kept
+            let kept = ids(&sample);
+            let expected = graph
+                .edges
+                .iter()
+                .filter(|e| kept.contains(&e.source) && kept.contains(&e.target))
+                .count();
+            assert_eq!(sample.edges.len(), expected);
+        }
+        assert_ne!(
+            ids(&graph.sample_nodes(4, 1)),
+            ids(&graph.sample_nodes(4, 2))
+        );
+        assert_eq!(graph.sample_nodes(100, 0).nodes.len(), graph.nodes.len());
+
+        let file = fs::read_to_string("tests/duplicates.gml").unwrap();
+        let duplicates = Graph::from_str(&file).unwrap();
+        let sample = duplicates.sample_nodes(10, 3);
+        assert!(sample.validate().is_ok());
+        assert_eq!(ids(&sample), vec![1, 2]);
+    }
+
+    #[test]
+    fn sample_edges() {
+        let mut graph = synoptic();
+        graph.edges.push(Edge::new(0, 99));
+        for seed in 0..20 {
+            let sample = graph.sample_edges(3, seed);
+            assert_eq!(sample.edges.len(), 3);
+            assert!(sample.validate().is_ok());
+            let again = graph.sample_edges(3, seed);
+            assert_eq!(edges(&again), edges(&sample));
+            assert_eq!(ids(&again), ids(&sample));
+        }
+        assert_eq!(
+            graph.sample_edges(100, 0).edges.len(),
+            graph.edges.len() - 1
+        );
+
+        // Only the heavy edges are ever chosen when the others weigh nothing
+        for (i, edge) in graph.edges.iter_mut().enumerate() {
+            let weight = if i % 4 == 0 { 1.0 } else { 0.0 };
+            edge.attrs.push(("w".into(), GMLValue::GMLFloat(weight)));
+        }
+        for seed in 0..20 {
+            let sample = graph.sample_edges_weighted(2, seed, "w");
+            assert!(sample.validate().is_ok());
+            assert_eq!(sample.edges.len(), 2);
+            assert!(sample.edges.iter().all(|e| e.attr_f64("w") == Some(1.0)));
+        }
+    }
+}
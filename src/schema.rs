@@ -0,0 +1,129 @@
+use crate::{coerce, Error, GMLType, GMLValue, Graph, ReadableGMLAttributes};
+
+// Ints are accepted wherever a float is expected
+fn accepts(expected: GMLType, value: &GMLValue) -> bool {
+    let found = value.gml_type();
+    found == expected || (expected == GMLType::Float && found == GMLType::Int)
+}
+
+impl GMLValue {
+    // The value converted to `to`, if that can be done without losing information.
+    // Numbers and strings convert into each other, objects only into themselves.
+    pub(crate) fn coerced(&self, to: GMLType) -> Option<GMLValue> {
+        match (self, to) {
+            (value, to) if value.gml_type() == to => Some(value.clone()),
+            (GMLValue::GMLInt(_), GMLType::Float) => coerce::to_f64(self).map(GMLValue::GMLFloat),
+            (GMLValue::GMLFloat(_), GMLType::Int) => coerce::to_i64(self).map(GMLValue::GMLInt),
+            (GMLValue::GMLInt(_) | GMLValue::GMLFloat(_), GMLType::String) => {
+                coerce::to_string_lossy(self).map(|s| GMLValue::GMLString(s.into_owned()))
+            }
+            (GMLValue::GMLString(s), GMLType::Int) => s.trim().parse().ok().map(GMLValue::GMLInt),
+            (GMLValue::GMLString(s), GMLType::Float) => {
+                s.trim().parse().ok().map(GMLValue::GMLFloat)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Attributes that every node and edge of a graph must carry.
+///
+/// ```
+/// use gml_parser::{GMLType, Schema};
+///
+/// let schema = Schema::new()
+///     .require_node("type", GMLType::String)
+///     .require_edge("weight", GMLType::Float);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub node: Vec<(String, GMLType)>,
+    pub edge: Vec<(String, GMLType)>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Require every node to have `key` with a value of type `kind`.
+    /// An int satisfies a [GMLType::Float] requirement.
+    pub fn require_node(mut self, key: &str, kind: GMLType) -> Self {
+        self.node.push((key.to_owned(), kind));
+        self
+    }
+    /// Require every edge to have `key` with a value of type `kind`.
+    /// An int satisfies a [GMLType::Float] requirement.
+    pub fn require_edge(mut self, key: &str, kind: GMLType) -> Self {
+        self.edge.push((key.to_owned(), kind));
+        self
+    }
+}
+
+impl Graph {
+    /// Check every node and edge against the schema, collecting all violations.
+    pub fn validate_schema(&self, schema: &Schema) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        for node in &self.nodes {
+            let element = format!("node {}", node.id);
+            check_record(node, &element, &schema.node, &mut errors);
+        }
+        for edge in &self.edges {
+            let element = format!("edge {} -> {}", edge.source, edge.target);
+            check_record(edge, &element, &schema.edge, &mut errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn check_record<'a, T: ReadableGMLAttributes<'a>>(
+    record: &'a T,
+    element: &str,
+    required: &[(String, GMLType)],
+    errors: &mut Vec<Error>,
+) {
+    for (key, kind) in required {
+        match record.get_attribute(key) {
+            None => errors.push(Error::missing_key(element, key)),
+            Some((_, value)) if !accepts(*kind, value) => {
+                errors.push(Error::wrong_type(element, key, *kind, value.clone()))
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn schema_violations() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+
+        let schema = Schema::new().require_node("thisIsASampleAttribute", GMLType::Float);
+        assert!(graph.validate_schema(&schema).is_ok());
+
+        let schema = Schema::new()
+            .require_node("thisIsASampleAttribute", GMLType::String)
+            .require_edge("weight", GMLType::Float);
+        let errors = graph.validate_schema(&schema).unwrap_err();
+        assert_eq!(errors.len(), 6);
+        assert_eq!(
+            errors
+                .iter()
+                .filter(|e| e.kind() == ErrorKind::WrongType)
+                .count(),
+            3
+        );
+        assert!(errors.iter().any(|e| matches!(
+            e,
+            Error::MissingKey { element, key } if element == "edge 1 -> 2" && key == "weight"
+        )));
+    }
+}
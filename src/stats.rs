@@ -0,0 +1,564 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use crate::{
+    algorithms::mean_degree, canonical::sort_attrs, GMLValue, Graph, HasGMLAttributes,
+    ReadableGMLAttributes,
+};
+
+/// Which records of a [Graph] an attribute is looked up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrScope {
+    Nodes,
+    Edges,
+}
+
+/// Summary of the numeric values of one attribute, see [Graph::attr_stats].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrStats {
+    /// Number of records with a numeric value
+    pub count: usize,
+    /// Number of records without the attribute
+    pub missing: usize,
+    /// Number of records where the attribute is a string or an object
+    pub non_numeric: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Counts of values in [AttrStats::HISTOGRAM_BUCKETS] equal-width buckets spanning `min..=max`.
+    /// When all values are equal they are counted in the first bucket.
+    pub histogram: Vec<usize>,
+}
+
+/// A node or edge with many attributes, see [Graph::oversized_elements].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OversizedElement {
+    pub scope: AttrScope,
+    /// Position in [Graph::nodes] or [Graph::edges]
+    pub index: usize,
+    /// Number of attributes of the element
+    pub attributes: usize,
+}
+
+/// Size and degree summary of a [Graph], see [Graph::stats].
+///
+/// Degrees are the ones of [Graph::degrees], so only outgoing edges count when
+/// [Graph::directed] is `Some(true)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub self_loops: usize,
+    /// Same as [Graph::average_degree], 0 for a graph without nodes
+    pub average_degree: f64,
+    /// 0 for a graph without nodes
+    pub max_degree: usize,
+    /// Number of nodes no edge touches, see [Graph::isolated_nodes]
+    pub isolated_nodes: usize,
+}
+
+impl AttrStats {
+    pub const HISTOGRAM_BUCKETS: usize = 10;
+}
+
+/// An attribute value used as the key of a group by [Graph::partition_nodes_by] and
+/// [Graph::partition_edges_by].
+///
+/// Unlike `==` on [GMLValue], keys are equal exactly when their values are ordered
+/// [Ordering::Equal] by [GMLValue::canonical_cmp]: the pairs of nested objects are sorted as
+/// by [Graph::canonicalize], `-0.0` equals `0.0` and a NaN equals every NaN. An int is still
+/// never equal to a float.
+///
+/// ```
+/// use gml_parser::{GMLValue, PartitionKey};
+///
+/// assert_eq!(PartitionKey::new(GMLValue::GMLFloat(-0.0)), PartitionKey::new(0.0.into()));
+/// assert_eq!(PartitionKey::new(f64::NAN.into()), PartitionKey::new(f64::NAN.into()));
+/// assert_ne!(PartitionKey::new(1.into()), PartitionKey::new(1.0.into()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PartitionKey(GMLValue);
+
+impl PartitionKey {
+    /// The key of `value`, with its nested objects sorted and its floats normalized.
+    pub fn new(mut value: GMLValue) -> Self {
+        normalize(&mut value);
+        PartitionKey(value)
+    }
+    /// The value of the key, in its normalized form.
+    pub fn value(&self) -> &GMLValue {
+        &self.0
+    }
+    /// Take the normalized value out of the key.
+    pub fn into_value(self) -> GMLValue {
+        self.0
+    }
+}
+
+impl From<GMLValue> for PartitionKey {
+    fn from(value: GMLValue) -> Self {
+        PartitionKey::new(value)
+    }
+}
+
+impl PartialEq for PartitionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.canonical_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for PartitionKey {}
+
+impl Hash for PartitionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_value(&self.0, state);
+    }
+}
+
+// Sort nested objects and give every zero and every NaN the same bits, so that values equal
+// under canonical_cmp are also identical
+fn normalize(value: &mut GMLValue) {
+    match value {
+        GMLValue::GMLFloat(f) if *f == 0.0 => *f = 0.0,
+        GMLValue::GMLFloat(f) if f.is_nan() => *f = f64::NAN,
+        GMLValue::GMLObject(obj) => {
+            obj.pairs.iter_mut().for_each(|(_, v)| normalize(v));
+            sort_attrs(&mut obj.pairs);
+        }
+        GMLValue::GMLList(items) => items.iter_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+fn hash_value<H: Hasher>(value: &GMLValue, state: &mut H) {
+    mem::discriminant(value).hash(state);
+    match value {
+        GMLValue::GMLString(s) => s.hash(state),
+        GMLValue::GMLInt(i) => i.hash(state),
+        GMLValue::GMLFloat(f) => f.to_bits().hash(state),
+        GMLValue::GMLObject(obj) => {
+            obj.pairs.len().hash(state);
+            for (key, value) in &obj.pairs {
+                key.hash(state);
+                hash_value(value, state);
+            }
+        }
+        GMLValue::GMLList(items) => {
+            items.len().hash(state);
+            items.iter().for_each(|item| hash_value(item, state));
+        }
+    }
+}
+
+impl Graph {
+    /// Numeric summary of the attribute `name` across the nodes or edges of the graph.
+    /// Ints and floats are both treated as `f64`.
+    ///
+    /// Returns None when no record has a numeric value for the attribute.
+    pub fn attr_stats(&self, scope: AttrScope, name: &str) -> Option<AttrStats> {
+        let values: Vec<Option<&GMLValue>> = match scope {
+            AttrScope::Nodes => self
+                .nodes
+                .iter()
+                .map(|node| node.get_attribute(name).map(|(_, v)| v))
+                .collect(),
+            AttrScope::Edges => self
+                .edges
+                .iter()
+                .map(|edge| edge.get_attribute(name).map(|(_, v)| v))
+                .collect(),
+        };
+        let missing = values.iter().filter(|v| v.is_none()).count();
+        let numbers: Vec<f64> = values.iter().flatten().filter_map(|v| v.as_f64()).collect();
+        if numbers.is_empty() {
+            return None;
+        }
+        let non_numeric = values.len() - missing - numbers.len();
+        let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+
+        let mut histogram = vec![0; AttrStats::HISTOGRAM_BUCKETS];
+        let width = (max - min) / AttrStats::HISTOGRAM_BUCKETS as f64;
+        for n in &numbers {
+            let bucket = if width > 0.0 {
+                (((n - min) / width) as usize).min(AttrStats::HISTOGRAM_BUCKETS - 1)
+            } else {
+                0
+            };
+            histogram[bucket] += 1;
+        }
+        Some(AttrStats {
+            count: numbers.len(),
+            missing,
+            non_numeric,
+            min,
+            max,
+            mean,
+            histogram,
+        })
+    }
+    /// The values of the attribute `key` of every node as `f64`, in node order.
+    /// Nodes without the attribute or with a string or object value are skipped.
+    pub fn node_attr_values_f64(&self, key: &str) -> Vec<f64> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.get_attribute(key).and_then(|(_, v)| v.as_f64()))
+            .collect()
+    }
+    /// The values of the attribute `key` of every edge as `f64`, in edge order,
+    /// like [Graph::node_attr_values_f64].
+    pub fn edge_attr_values_f64(&self, key: &str) -> Vec<f64> {
+        self.edges
+            .iter()
+            .filter_map(|edge| edge.get_attribute(key).and_then(|(_, v)| v.as_f64()))
+            .collect()
+    }
+}
+
+impl Graph {
+    /// Summary of the size and the degrees of the graph.
+    pub fn stats(&self) -> GraphStats {
+        let degrees = self.degrees();
+        GraphStats {
+            nodes: self.nodes.len(),
+            edges: self.edges.len(),
+            self_loops: self.edges.iter().filter(|e| e.source == e.target).count(),
+            average_degree: mean_degree(&degrees),
+            max_degree: degrees.values().copied().max().unwrap_or(0),
+            isolated_nodes: self.isolated_nodes().len(),
+        }
+    }
+    /// The nodes and then the edges with more than `threshold` attributes, in document order.
+    ///
+    /// Useful to find corrupted elements in graphs that were parsed without
+    /// [ParseLimits::max_pairs_per_object](crate::ParseLimits::max_pairs_per_object).
+    pub fn oversized_elements(&self, threshold: usize) -> Vec<OversizedElement> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| (AttrScope::Nodes, node.attributes().len()));
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| (AttrScope::Edges, edge.attributes().len()));
+        let (mut node_index, mut edge_index) = (0, 0);
+        nodes
+            .chain(edges)
+            .filter_map(|(scope, attributes)| {
+                let counter = match scope {
+                    AttrScope::Nodes => &mut node_index,
+                    AttrScope::Edges => &mut edge_index,
+                };
+                let index = *counter;
+                *counter += 1;
+                (attributes > threshold).then_some(OversizedElement {
+                    scope,
+                    index,
+                    attributes,
+                })
+            })
+            .collect()
+    }
+    /// Group the ids of the nodes by the value of their attribute `name`, with the nodes that
+    /// don't have it under None. The ids of each group are in the order of [Graph::nodes].
+    ///
+    /// Values are compared as described on [PartitionKey]: nested objects share a group
+    /// whatever the order of their pairs, `-0.0` and `0.0` share one and so do NaNs.
+    /// Only the first value is used when a node has the attribute more than once.
+    pub fn partition_nodes_by(&self, name: &str) -> HashMap<Option<PartitionKey>, Vec<i64>> {
+        let mut groups: HashMap<Option<PartitionKey>, Vec<i64>> = HashMap::new();
+        for node in &self.nodes {
+            let key = node
+                .get_attribute(name)
+                .map(|(_, value)| PartitionKey::new(value.clone()));
+            groups.entry(key).or_default().push(node.id);
+        }
+        groups
+    }
+    /// Like [Graph::partition_nodes_by] for the edges, which are identified by their position
+    /// in [Graph::edges] since they don't have ids.
+    pub fn partition_edges_by(&self, name: &str) -> HashMap<Option<PartitionKey>, Vec<usize>> {
+        let mut groups: HashMap<Option<PartitionKey>, Vec<usize>> = HashMap::new();
+        for (i, edge) in self.edges.iter().enumerate() {
+            let key = edge
+                .get_attribute(name)
+                .map(|(_, value)| PartitionKey::new(value.clone()));
+            groups.entry(key).or_default().push(i);
+        }
+        groups
+    }
+    /// How many nodes and edges use each key, counting every record once per key.
+    ///
+    /// Besides the attributes this includes `label`. The mandatory `id`, `source` and `target`
+    /// keys are not counted.
+    pub fn attribute_histogram(&self) -> HashMap<String, usize> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let records = self
+            .nodes
+            .iter()
+            .map(|node| (node.label.is_some(), node.attributes()))
+            .chain(
+                self.edges
+                    .iter()
+                    .map(|edge| (edge.label.is_some(), edge.attributes())),
+            );
+        let mut seen = HashSet::new();
+        for (labeled, attrs) in records {
+            seen.clear();
+            if labeled {
+                seen.insert("label");
+            }
+            seen.extend(attrs.iter().map(|(key, _)| key.as_str()));
+            for key in &seen {
+                match counts.get_mut(*key) {
+                    Some(count) => *count += 1,
+                    None => {
+                        counts.insert(key.to_string(), 1);
+                    }
+                }
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn numeric_stats() {
+        let text = "graph [
+edge [
+source 1
+target 2
+weight 1
+]
+edge [
+source 2
+target 3
+weight 2.5
+]
+edge [
+source 3
+target 4
+weight \"heavy\"
+]
+edge [
+source 4
+target 5
+weight 11
+]
+edge [
+source 5
+target 6
+]
+]";
+        let graph = Graph::from_str(text).unwrap();
+        let stats = graph.attr_stats(AttrScope::Edges, "weight").unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.missing, 1);
+        assert_eq!(stats.non_numeric, 1);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 11.0);
+        assert_eq!(stats.mean, 14.5 / 3.0);
+        assert_eq!(stats.histogram, vec![1, 1, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        assert_eq!(graph.attr_stats(AttrScope::Nodes, "weight"), None);
+        assert_eq!(graph.attr_stats(AttrScope::Edges, "missing"), None);
+        assert_eq!(graph.edges[1].attr_f64("weight"), Some(2.5));
+        assert_eq!(graph.edges[0].attr_f64("weight"), Some(1.0));
+        assert_eq!(graph.edges[0].attr_i64("weight"), Some(1));
+        assert_eq!(graph.edges[2].attr_str("weight"), Some("heavy"));
+    }
+
+    #[test]
+    fn numeric_attr_values() {
+        let file = std::fs::read_to_string("tests/weighted.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        let weights = graph.edge_attr_values_f64("weight");
+        assert_eq!(weights, vec![2.5, 1.0, 4.0]);
+        assert_eq!(weights.iter().sum::<f64>(), 7.5);
+        assert!(graph.node_attr_values_f64("weight").is_empty());
+    }
+
+    #[test]
+    fn attribute_histogram() {
+        let file = std::fs::read_to_string("tests/synoptic.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        let counts = graph.attribute_histogram();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["graphics"], 7);
+        assert_eq!(counts["label"], 15);
+
+        graph.edges[0].set_attribute("weight", 2);
+        graph.edges[0]
+            .attributes_mut()
+            .push(("weight".into(), 3.into()));
+        graph.nodes[0].set_attribute("weight", 1.5);
+        let counts = graph.attribute_histogram();
+        assert_eq!(counts["weight"], 2);
+    }
+
+    #[test]
+    fn partition_by_attribute() {
+        let text = "graph [
+node [
+id 1
+kind \"a\"
+size 2
+]
+node [
+id 2
+kind \"b\"
+size 2.0
+]
+node [
+id 3
+kind \"a\"
+size -0.0
+]
+node [
+id 4
+size 0.0
+]
+node [
+id 5
+kind 7
+size [
+w 1
+h 2
+]
+]
+node [
+id 6
+kind 7
+size [
+w 1
+h 2
+]
+]
+node [
+id 7
+size [
+h 2
+w 1
+]
+]
+edge [
+source 1
+target 2
+weight 1
+]
+edge [
+source 2
+target 3
+]
+edge [
+source 3
+target 1
+weight 1
+]
+]";
+        let mut graph = Graph::from_str(text).unwrap();
+        let key = |value: GMLValue| Some(PartitionKey::new(value));
+        let kinds = graph.partition_nodes_by("kind");
+        assert_eq!(kinds.len(), 4);
+        assert_eq!(kinds[&key("a".into())], vec![1, 3]);
+        assert_eq!(kinds[&key("b".into())], vec![2]);
+        assert_eq!(kinds[&key(7.into())], vec![5, 6]);
+        assert_eq!(kinds[&None], vec![4, 7]);
+
+        let sizes = graph.partition_nodes_by("size");
+        assert_eq!(sizes.len(), 4);
+        assert_eq!(sizes[&key(2.into())], vec![1]);
+        assert_eq!(sizes[&key(2.0.into())], vec![2]);
+        assert_eq!(sizes[&key(0.0.into())], vec![3, 4]);
+        // Objects share a group whatever the order of their pairs
+        let object = graph.nodes[6].get_attribute("size").unwrap().1.clone();
+        assert_eq!(sizes[&key(object)], vec![5, 6, 7]);
+        assert!(!sizes.contains_key(&None));
+
+        graph.edges[1].set_attribute("weight", f64::NAN);
+        graph.edges[2].set_attribute("weight", -f64::NAN);
+        let weights = graph.partition_edges_by("weight");
+        assert_eq!(weights[&key(1.into())], vec![0]);
+        assert_eq!(weights[&key(f64::NAN.into())], vec![1, 2]);
+        assert_eq!(weights.len(), 2);
+        assert!(Graph::from_str("graph [ ]")
+            .unwrap()
+            .partition_nodes_by("kind")
+            .is_empty());
+    }
+
+    #[test]
+    fn oversized_elements() {
+        let mut text =
+            String::from("graph [\nnode [\nid 1\n]\nedge [\nsource 1\ntarget 2\n]\nnode [\nid 2\n");
+        for i in 0..50 {
+            text.push_str(&format!("a{} {}\n", i, i));
+        }
+        text.push_str("]\n]");
+
+        let graph = Graph::from_str(&text).unwrap();
+        assert_eq!(
+            graph.oversized_elements(10),
+            vec![OversizedElement {
+                scope: AttrScope::Nodes,
+                index: 1,
+                attributes: 50
+            }]
+        );
+        assert!(graph.oversized_elements(50).is_empty());
+
+        let options = ParseOptions {
+            limits: ParseLimits {
+                max_pairs_per_object: Some(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let err = Graph::from_str_with(&text, &options).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Limit);
+        assert!(matches!(
+            &err,
+            Error::TooManyPairs { element, index: 1, line: 9, limit: 10 } if element == "node"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "GMLError: node #1 starting on line 9 has more than 10 pairs"
+        );
+    }
+
+    #[test]
+    fn graph_stats() {
+        let mut star = Graph::new();
+        star.directed = Some(true);
+        star.nodes.extend((0..6).map(Node::new));
+        star.edges.extend((1..4).map(|leaf| Edge::new(leaf, 0)));
+        star.edges.push(Edge::new(0, 0));
+        assert_eq!(
+            star.stats(),
+            GraphStats {
+                nodes: 6,
+                edges: 4,
+                self_loops: 1,
+                average_degree: 4.0 / 6.0,
+                max_degree: 1,
+                isolated_nodes: 2,
+            }
+        );
+        assert_eq!(star.stats().average_degree, star.average_degree());
+        star.directed = Some(false);
+        assert_eq!(star.stats().average_degree, 8.0 / 6.0);
+        assert_eq!(star.stats().max_degree, 5);
+        assert_eq!(star.stats().average_degree, star.average_degree());
+        assert_eq!(Graph::new().stats().max_degree, 0);
+        assert_eq!(Graph::new().stats().average_degree, 0.0);
+    }
+}
@@ -0,0 +1,484 @@
+//! Incremental parsing of GML text that arrives in chunks, see [GMLObject::from_async_reader].
+//!
+//! Every pair directly inside a top-level object, such as each `node [...]` of `graph [...]`,
+//! is parsed as soon as its text is complete and then dropped. Only the surrounding text is
+//! kept, with each of those pairs replaced by a placeholder. It is parsed once the input ends,
+//! which checks that the pairs are separated the way the grammar requires.
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{
+    parse_text, report::ParseState, Error, GMLObject, GMLValue, Graph, ParseOptions, Result, Rule,
+    Warning,
+};
+
+const PLACEHOLDER: &str = "_ 0";
+const CHUNK_SIZE: usize = 8 * 1024;
+
+impl GMLObject {
+    /// Parse GML text read from `reader`, doing the work as the data arrives instead of
+    /// after reading everything.
+    ///
+    /// Syntax errors inside a pair of a top-level object report positions relative to the
+    /// start of that pair.
+    ///
+    /// The future is cancel safe in the sense that dropping it leaves `reader` usable,
+    /// but the data it already read is lost. Pass `&mut reader` to keep the reader.
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: R) -> Result<Self> {
+        Self::from_async_reader_with(reader, &ParseOptions::default()).await
+    }
+    /// Like [GMLObject::from_async_reader], using the given [ParseOptions].
+    pub async fn from_async_reader_with<R: AsyncRead + Unpin>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        read_all(reader, options).await?.finish()
+    }
+}
+
+impl Graph {
+    /// Parse GML text read from `reader` into a graph, see [GMLObject::from_async_reader].
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: R) -> Result<Self> {
+        Self::from_async_reader_with(reader, &ParseOptions::default()).await
+    }
+    /// Like [Graph::from_async_reader], using the given [ParseOptions].
+    ///
+    /// [ParseOptions::spans] is not supported and leaves the spans empty, and
+    /// [ParseOptions::recover_brackets] is ignored.
+    pub async fn from_async_reader_with<R: AsyncRead + Unpin>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        read_all(reader, options).await?.finish_graph()
+    }
+}
+
+async fn read_all<R: AsyncRead + Unpin>(
+    mut reader: R,
+    options: &ParseOptions,
+) -> Result<StreamParser<'_>> {
+    let mut parser = StreamParser::new(options);
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buf).await?;
+        if read == 0 {
+            return Ok(parser);
+        }
+        parser.feed(&buf[..read])?;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    // Outside of any object
+    Top,
+    // Right after a top-level `[`, which starts an object if a line break follows and a list
+    // otherwise
+    Opened,
+    // Inside a top-level list, which is left in the skeleton
+    List,
+    // Inside a top-level object, between its pairs
+    Between,
+    // Inside a pair of a top-level object
+    Element,
+}
+
+pub(crate) struct StreamParser<'o> {
+    options: &'o ParseOptions,
+    // Shared by the parses of every element
+    state: ParseState,
+    level: Level,
+    // The text read so far with every finished element replaced by PLACEHOLDER
+    skeleton: String,
+    // Parsed elements of every top-level object, in order, None for skipped ones
+    objects: Vec<Vec<Option<(String, GMLValue)>>>,
+    // Text of the current element
+    pending: String,
+    // Bytes of a character split across chunks
+    partial: Vec<u8>,
+    quote: Option<char>,
+    escaped: bool,
+    // Brackets opened inside the current element
+    nesting: usize,
+    // Tokens started at the top of the current element, the key and its value
+    tokens: usize,
+    in_token: bool,
+}
+
+impl<'o> StreamParser<'o> {
+    pub(crate) fn new(options: &'o ParseOptions) -> Self {
+        StreamParser {
+            options,
+            state: ParseState::default(),
+            level: Level::Top,
+            skeleton: String::new(),
+            objects: Vec::new(),
+            pending: String::new(),
+            partial: Vec::new(),
+            quote: None,
+            escaped: false,
+            nesting: 0,
+            tokens: 0,
+            in_token: false,
+        }
+    }
+
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<()> {
+        let mut bytes = std::mem::take(&mut self.partial);
+        bytes.extend_from_slice(chunk);
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(text) => text.len(),
+            // The chunk ends in the middle of a character
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(Error::from(io::Error::new(io::ErrorKind::InvalidData, err))),
+        };
+        let text = std::str::from_utf8(&bytes[..valid]).expect("checked above");
+        for c in text.chars() {
+            self.push(c)?;
+        }
+        self.partial = bytes[valid..].to_vec();
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Result<GMLObject> {
+        self.finish_with_state().map(|(root, _)| root)
+    }
+
+    pub(crate) fn finish_graph(self) -> Result<Graph> {
+        let options = self.options;
+        let (root, state) = self.finish_with_state()?;
+        let mut graph = Graph::from_gml_with(root, options)?;
+        if options.record_skipped_keys {
+            graph.warnings.extend(
+                state
+                    .skipped
+                    .into_iter()
+                    .map(|(key, count)| Warning::SkippedKey { key, count }),
+            );
+        }
+        Ok(graph)
+    }
+
+    fn finish_with_state(mut self) -> Result<(GMLObject, ParseState)> {
+        if !self.partial.is_empty() {
+            let err = std::str::from_utf8(&self.partial).unwrap_err();
+            return Err(Error::from(io::Error::new(io::ErrorKind::InvalidData, err)));
+        }
+        // An unfinished element means a missing `]`, which parsing the skeleton reports
+        self.skeleton.push_str(&self.pending);
+        // Skipping a top-level object here would lose track of which elements are its own,
+        // so those are skipped once the elements are in place
+        let options = ParseOptions {
+            skip_keys: Default::default(),
+            ..self.options.clone()
+        };
+        let mut root = GMLObject::from_str_with(&self.skeleton, &options)?;
+        let objects = root.pairs.iter_mut().filter_map(|(_, value)| match value {
+            GMLValue::GMLObject(obj) => Some(obj),
+            _ => None,
+        });
+        for (obj, elements) in objects.zip(self.objects) {
+            debug_assert_eq!(obj.pairs.len(), elements.len());
+            obj.pairs = elements.into_iter().flatten().collect();
+        }
+        let skip_keys = &self.options.skip_keys;
+        if !skip_keys.is_empty() {
+            let state = &mut self.state;
+            root.pairs.retain(|(key, _)| {
+                let skip = skip_keys.contains(key);
+                if skip {
+                    *state.skipped.entry(key.clone()).or_default() += 1;
+                }
+                !skip
+            });
+        }
+        Ok((root, self.state))
+    }
+
+    fn push(&mut self, c: char) -> Result<()> {
+        match self.level {
+            Level::Top => {
+                self.skeleton.push(c);
+                if self.in_string(c) {
+                    return Ok(());
+                }
+                match c {
+                    '"' | '\'' => self.quote = Some(c),
+                    '[' => self.level = Level::Opened,
+                    _ => {}
+                }
+            }
+            Level::Opened => match c {
+                c if is_line_terminator(c) || c == ']' => {
+                    self.skeleton.push(c);
+                    self.objects.push(Vec::new());
+                    self.level = if c == ']' { Level::Top } else { Level::Between };
+                }
+                c if c.is_whitespace() => self.skeleton.push(c),
+                c => {
+                    self.level = Level::List;
+                    return self.push(c);
+                }
+            },
+            Level::List => {
+                self.skeleton.push(c);
+                if self.in_string(c) {
+                    return Ok(());
+                }
+                match c {
+                    '"' | '\'' => self.quote = Some(c),
+                    ']' => self.level = Level::Top,
+                    _ => {}
+                }
+            }
+            Level::Between => match c {
+                ']' => {
+                    self.skeleton.push(c);
+                    self.level = Level::Top;
+                }
+                ',' => self.skeleton.push(c),
+                c if c.is_whitespace() => self.skeleton.push(c),
+                c => {
+                    self.level = Level::Element;
+                    self.nesting = 0;
+                    self.tokens = 0;
+                    self.in_token = false;
+                    self.push_element(c);
+                }
+            },
+            Level::Element => {
+                let top = self.quote.is_none() && self.nesting == 0;
+                let complete = top && self.tokens >= 2;
+                if complete && (is_line_terminator(c) || c == ',') {
+                    self.finish_element()?;
+                    self.skeleton.push(c);
+                    self.level = Level::Between;
+                } else if top && c == ']' {
+                    self.finish_element()?;
+                    self.skeleton.push(c);
+                    self.level = Level::Top;
+                } else {
+                    self.push_element(c);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_element(&mut self, c: char) {
+        self.pending.push(c);
+        if self.in_string(c) {
+            if self.quote.is_none() && self.nesting == 0 {
+                self.in_token = false;
+            }
+            return;
+        }
+        if c.is_whitespace() {
+            self.in_token = false;
+            return;
+        }
+        if self.nesting == 0 && !self.in_token {
+            self.tokens += 1;
+            self.in_token = true;
+        }
+        match c {
+            '"' | '\'' => self.quote = Some(c),
+            '[' => self.nesting += 1,
+            ']' => {
+                self.nesting -= 1;
+                if self.nesting == 0 {
+                    self.in_token = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Whether `c` is part of a string, updating the quoting state
+    fn in_string(&mut self, c: char) -> bool {
+        let Some(quote) = self.quote else {
+            return false;
+        };
+        if self.escaped {
+            self.escaped = false;
+        } else if c == '\\' {
+            self.escaped = true;
+        } else if c == quote {
+            self.quote = None;
+        }
+        true
+    }
+
+    fn finish_element(&mut self) -> Result<()> {
+        let file = parse_text(&self.pending)?;
+        let keys = file.clone().into_inner();
+        if keys
+            .filter(|entry| entry.as_rule() == Rule::identifier)
+            .count()
+            != 1
+        {
+            return Err(Error::Syntax(format!(
+                "expected a single pair but found {:?}",
+                self.pending
+            )));
+        }
+        self.state.depth = 1;
+        let mut element = GMLObject::parse_with(file.into_inner(), self.options, &mut self.state)?;
+        self.pending.clear();
+        // A skipped element leaves no pair
+        self.objects
+            .last_mut()
+            .expect("elements are only read inside an object")
+            .push(element.pairs.pop());
+        self.skeleton.push_str(PLACEHOLDER);
+        Ok(())
+    }
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamParser;
+    use crate::*;
+    use std::{fs, time::Duration};
+    use tokio::io::AsyncWriteExt;
+
+    fn parse_in_chunks(text: &str, size: usize) -> Result<GMLObject> {
+        let options = ParseOptions::default();
+        let mut parser = StreamParser::new(&options);
+        for chunk in text.as_bytes().chunks(size) {
+            parser.feed(chunk)?;
+        }
+        parser.finish()
+    }
+
+    #[test]
+    fn chunked_matches_full_parse() {
+        let texts = [
+            fs::read_to_string("tests/wikipedia.gml").unwrap(),
+            fs::read_to_string("tests/synoptic.gml").unwrap(),
+            fs::read_to_string("tests/wrapped.gml").unwrap(),
+            "Creator \"[x]\"\ngraph [\n\"id\"5\nnode\n[\nid 1\nlabel 'a ] \"b\" ]'\n]\nedge [\nsource 1\ntarget 1\n],]\nempty [\n]".to_owned(),
+            "graph [\n\tname \"ünïcödé ✓\"\n]".to_owned(),
+            "levels [ 1 'a ] b' 2.5 ]\ngraph [\nlevels [ 3 4 ]\n]\nnone [ ]".to_owned(),
+        ];
+        for text in &texts {
+            let expected = GMLObject::from_str(text).unwrap();
+            for size in [1, 2, 3, 7, 64, text.len()] {
+                assert_eq!(parse_in_chunks(text, size).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn chunked_rejects_invalid_text() {
+        for text in [
+            "graph [\nnode [\nid 1\n]\n",
+            "graph [\nnode [\nid 1\n]\n\nnode [\nid 2\n]\n]",
+            "graph [ node [\nid 1\n]\n]",
+            "graph [\nid 1 2\n]",
+            "graph [\nid\n]",
+            "graph [\nlabel \"open\n]",
+            "graph",
+        ] {
+            assert!(GMLObject::from_str(text).is_err());
+            for size in [1, 5, text.len()] {
+                assert!(parse_in_chunks(text, size).is_err(), "{:?}", text);
+            }
+        }
+        let options = ParseOptions::default();
+        let mut parser = StreamParser::new(&options);
+        parser.feed(&[b'g', 0xe2, 0x9c]).unwrap();
+        assert_eq!(parser.finish().unwrap_err().kind(), ErrorKind::Io);
+    }
+
+    #[test]
+    fn chunked_with_options() {
+        let text = fs::read_to_string("tests/options.gml").unwrap();
+        for options in [
+            ParseOptions::default().skip_keys(["node", "Creator"]),
+            ParseOptions::default().skip_key("graph"),
+            ParseOptions::default().leading_zeros_as_strings(true),
+        ] {
+            let expected = GMLObject::from_str_with(&text, &options).unwrap();
+            for size in [1, 9, text.len()] {
+                let mut parser = StreamParser::new(&options);
+                for chunk in text.as_bytes().chunks(size) {
+                    parser.feed(chunk).unwrap();
+                }
+                assert_eq!(parser.finish().unwrap(), expected);
+            }
+        }
+        let options = ParseOptions::default().max_pairs_per_object(3);
+        let mut parser = StreamParser::new(&options);
+        parser.feed(text.as_bytes()).unwrap();
+        assert_eq!(parser.finish().unwrap_err().kind(), ErrorKind::Limit);
+    }
+
+    #[test]
+    fn elements_are_parsed_as_they_arrive() {
+        let text = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let options = ParseOptions::default();
+        let mut parser = StreamParser::new(&options);
+        parser.feed(&text.as_bytes()[..text.len() / 2]).unwrap();
+        assert!(parser.objects[0].len() > 2);
+        assert!(parser.pending.len() < 200);
+        assert!(parser.skeleton.len() < text.len() / 4);
+    }
+
+    #[tokio::test]
+    async fn async_reader_with_throttled_chunks() {
+        let text = fs::read_to_string("tests/synoptic.gml").unwrap();
+        let (mut writer, reader) = tokio::io::duplex(16);
+        let sent = text.clone();
+        let writing = tokio::spawn(async move {
+            for chunk in sent.as_bytes().chunks(13) {
+                writer.write_all(chunk).await.unwrap();
+                tokio::time::sleep(Duration::from_micros(50)).await;
+            }
+        });
+        let graph = Graph::from_async_reader(reader).await.unwrap();
+        writing.await.unwrap();
+        let expected = Graph::from_str(&text).unwrap();
+        assert_eq!(graph.nodes, expected.nodes);
+        assert_eq!(graph.edges, expected.edges);
+        assert_eq!(graph.attrs, expected.attrs);
+
+        let levels = GMLObject::from_async_reader(&b"levels [ 1 2 3 ]"[..]).await;
+        assert_eq!(
+            levels.unwrap().pairs,
+            [(
+                "levels".to_owned(),
+                GMLValue::GMLList(vec![1.into(), 2.into(), 3.into()])
+            )]
+        );
+
+        let invalid = GMLObject::from_async_reader(&b"graph [\nid 1\n"[..]).await;
+        assert!(invalid.unwrap_err().is_syntax());
+    }
+
+    #[tokio::test]
+    async fn async_reader_cancellation() {
+        let text = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let (mut writer, mut reader) = tokio::io::duplex(4096);
+        let (first, rest) = text.split_at(text.len() / 2);
+        writer.write_all(first.as_bytes()).await.unwrap();
+        // The rest never arrives in time, dropping the future mid-document
+        let timed_out = tokio::time::timeout(
+            Duration::from_millis(20),
+            GMLObject::from_async_reader(&mut reader),
+        )
+        .await;
+        assert!(timed_out.is_err());
+
+        // The reader is still usable but the first half was consumed
+        writer.write_all(rest.as_bytes()).await.unwrap();
+        drop(writer);
+        assert!(GMLObject::from_async_reader(&mut reader).await.is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Detection of unexpected keys during Graph conversion, see [ParseOptions::unknown_keys].
+
+use crate::{Edge, Error, GMLValue, Node, ParseOptions, Result, UnknownKeyPolicy, Warning};
+
+// Keys that Graph conversion reads into fields
+const GRAPH_KEYS: &[&str] = &["id", "directed", "label", "name", "node", "edge"];
+const NODE_KEYS: &[&str] = &["id", "label"];
+const EDGE_KEYS: &[&str] = &["source", "target", "label", "directed"];
+
+// Suggestions further away than this are more confusing than helpful
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Apply [ParseOptions::unknown_keys] to the attributes of a converted graph, its nodes and
+/// its edges.
+pub(crate) fn check_unknown_keys(
+    options: &ParseOptions,
+    attrs: &[(String, GMLValue)],
+    nodes: &[Node],
+    edges: &[Edge],
+    warnings: &mut Vec<Warning>,
+) -> Result<()> {
+    let allow = match &options.unknown_keys {
+        UnknownKeyPolicy::Keep => return Ok(()),
+        UnknownKeyPolicy::Warn { allow } | UnknownKeyPolicy::Error { allow } => allow,
+    };
+    let elements = std::iter::once(("graph", GRAPH_KEYS, &allow.graph, attrs))
+        .chain(
+            nodes
+                .iter()
+                .map(|node| ("node", NODE_KEYS, &allow.node, node.attrs.as_slice())),
+        )
+        .chain(
+            edges
+                .iter()
+                .map(|edge| ("edge", EDGE_KEYS, &allow.edge, edge.attrs.as_slice())),
+        );
+    for (element, known, allowed, attrs) in elements {
+        for (key, _) in attrs {
+            if known.contains(&key.as_str()) || allowed.contains(key) {
+                continue;
+            }
+            let candidates = known
+                .iter()
+                .copied()
+                .chain(allowed.iter().map(String::as_str));
+            let suggestion = suggest(key, candidates);
+            if let UnknownKeyPolicy::Error { .. } = options.unknown_keys {
+                return Err(Error::UnknownKey {
+                    element: element.to_owned(),
+                    key: key.clone(),
+                    suggestion,
+                });
+            }
+            warnings.push(Warning::UnknownKey {
+                element: element.to_owned(),
+                key: key.clone(),
+                suggestion,
+            });
+        }
+    }
+    Ok(())
+}
+
+// The candidate closest to `key` by edit distance, ties broken alphabetically
+fn suggest<'k>(key: &str, candidates: impl Iterator<Item = &'k str>) -> Option<String> {
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE && distance < key.len())
+        .min()
+        .map(|(_, candidate)| candidate.to_owned())
+}
+
+// Levenshtein distance counting characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Distances from the prefix of `a` read so far to every prefix of `b`
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, suggest};
+    use crate::*;
+    use std::fs;
+
+    fn with(policy: UnknownKeyPolicy) -> Result<Graph> {
+        let text =
+            "graph [\ncreator \"x\"\nnode [\nid 1\nlable \"a\"\n]\nnode [\nid 2\nweight 3\n]\n\
+                    edge [\nsource 1\ntagret 2\ntarget 2\n]\n]";
+        let options = ParseOptions {
+            unknown_keys: policy,
+            ..Default::default()
+        };
+        Graph::from_str_with(text, &options)
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(edit_distance("tagret", "target"), 2);
+        assert_eq!(edit_distance("lable", "label"), 2);
+        assert_eq!(edit_distance("", "id"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("nœud", "noeud"), 2);
+        let known = ["source", "target", "label", "weight"];
+        assert_eq!(
+            suggest("tagret", known.into_iter()).as_deref(),
+            Some("target")
+        );
+        assert_eq!(
+            suggest("wieght", known.into_iter()).as_deref(),
+            Some("weight")
+        );
+        assert_eq!(suggest("color", known.into_iter()), None);
+        // Short keys are within two edits of almost anything
+        assert_eq!(suggest("x", ["id"].into_iter()), None);
+    }
+
+    #[test]
+    fn unknown_key_policies() {
+        let kept = with(UnknownKeyPolicy::Keep).unwrap();
+        assert!(kept.warnings().is_empty());
+        assert_eq!(kept.nodes[0].attr_str("lable"), Some("a"));
+
+        let allow = AllowedKeys {
+            graph: ["creator".to_owned()].into(),
+            node: ["weight".to_owned()].into(),
+            edge: Default::default(),
+        };
+        let warned = with(UnknownKeyPolicy::Warn {
+            allow: allow.clone(),
+        })
+        .unwrap();
+        assert_eq!(
+            warned.warnings(),
+            &[
+                Warning::UnknownKey {
+                    element: "node".into(),
+                    key: "lable".into(),
+                    suggestion: Some("label".into()),
+                },
+                Warning::UnknownKey {
+                    element: "edge".into(),
+                    key: "tagret".into(),
+                    suggestion: Some("target".into()),
+                },
+            ]
+        );
+        assert_eq!(warned.nodes, kept.nodes);
+
+        let err = with(UnknownKeyPolicy::Error { allow }).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnknownKey);
+        assert_eq!(
+            err.to_string(),
+            "GMLError: Unknown key `lable` in node, did you mean `label`?"
+        );
+
+        // Nothing allowed: the graph attribute is reported first, without a suggestion
+        let err = with(UnknownKeyPolicy::Error {
+            allow: AllowedKeys::default(),
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "GMLError: Unknown key `creator` in graph");
+
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let options = ParseOptions {
+            unknown_keys: UnknownKeyPolicy::Error {
+                allow: AllowedKeys {
+                    graph: ["comment".to_owned()].into(),
+                    node: ["thisIsASampleAttribute".to_owned()].into(),
+                    edge: Default::default(),
+                },
+            },
+            ..Default::default()
+        };
+        assert!(Graph::from_str_with(&file, &options).is_ok());
+    }
+}
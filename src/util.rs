@@ -0,0 +1,69 @@
+//! Helpers for comparing GML keys, shared by the attribute lookups of this crate.
+//!
+//! Case folding is ASCII only: `ID` matches `id` but `Ü` doesn't match `ü`, the same way
+//! [str::eq_ignore_ascii_case] works. Keys are compared without allocating.
+
+/// Whether the keys `a` and `b` are equal, ignoring ASCII case when `case_insensitive` is set.
+///
+/// ```
+/// use gml_parser::util::key_eq;
+///
+/// assert!(key_eq("Label", "label", true));
+/// assert!(!key_eq("Label", "label", false));
+/// ```
+#[inline]
+pub fn key_eq(a: &str, b: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// How two keys are compared, the mode taken by the attribute lookups of this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyMatch {
+    /// Byte for byte, as GML keys are case sensitive
+    #[default]
+    Exact,
+    /// Ignoring ASCII case, like [key_eq] with `case_insensitive` set
+    IgnoreAsciiCase,
+}
+
+impl KeyMatch {
+    /// Whether the keys `a` and `b` are equal in this mode.
+    ///
+    /// ```
+    /// use gml_parser::util::KeyMatch;
+    ///
+    /// assert!(KeyMatch::IgnoreAsciiCase.matches("ID", "id"));
+    /// assert!(!KeyMatch::Exact.matches("ID", "id"));
+    /// ```
+    #[inline]
+    pub fn matches(self, a: &str, b: &str) -> bool {
+        match self {
+            KeyMatch::Exact => a == b,
+            KeyMatch::IgnoreAsciiCase => a.eq_ignore_ascii_case(b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_folding() {
+        assert!(key_eq("id", "id", false));
+        assert!(key_eq("sourceID", "SOURCEid", true));
+        assert!(!key_eq("sourceID", "SOURCEid", false));
+        assert!(!key_eq("größe", "GRÖSSE", true));
+        assert!(key_eq("Größe", "gRößE", true));
+        assert!(!key_eq("id", "ids", true));
+
+        for (a, b) in [("id", "id"), ("sourceID", "SOURCEid"), ("größe", "GRÖSSE")] {
+            assert_eq!(KeyMatch::Exact.matches(a, b), key_eq(a, b, false));
+            assert_eq!(KeyMatch::IgnoreAsciiCase.matches(a, b), key_eq(a, b, true));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! The `Version` key written by many GML tools.
+
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::{Error, GMLType, GMLValue, Graph, ReadableGMLAttributes};
+
+/// A version number such as `Version 2`, `Version 2.2` or `Version "2.2"`.
+///
+/// Versions compare by their dot separated segments as numbers, so `2.10 > 2.2` and `2 == 2.0`.
+/// Text after the digits of a segment (`2.2-beta`) is compared as a string, with a segment
+/// without any such text being the larger one.
+///
+/// An unquoted version such as `Version 2.10` is parsed as a float before it becomes a
+/// version, so its trailing zeros are lost and it reads as `2.1`, which is less than `2.2`.
+/// Quote versions with more than one digit after the dot to keep them as written.
+///
+/// ```
+/// use gml_parser::GMLVersion;
+///
+/// assert!(GMLVersion::from("2.10") > GMLVersion::from(2.2));
+/// assert!(GMLVersion::from(2) >= GMLVersion::from(2.0));
+/// assert!(GMLVersion::from("2.2-beta") < GMLVersion::from("2.2"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GMLVersion(String);
+
+impl GMLVersion {
+    /// The version as written, floats and ints being formatted the usual way.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    /// The version as a number if it is one, e.g. `2.1` for `"2.10"`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.0.parse().ok()
+    }
+    fn segments(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.0.split('.').map(|segment| {
+            let digits = segment
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(segment.len());
+            let number = segment[..digits].parse().unwrap_or(0);
+            (number, &segment[digits..])
+        })
+    }
+}
+
+impl Ord for GMLVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.segments(), other.segments());
+        loop {
+            let (x, y) = match (a.next(), b.next()) {
+                (None, None) => return Ordering::Equal,
+                (x, y) => (x.unwrap_or((0, "")), y.unwrap_or((0, ""))),
+            };
+            // A suffix marks a pre-release, so the plain segment is greater
+            let ordering = x.0.cmp(&y.0).then_with(|| match (x.1, y.1) {
+                ("", "") => Ordering::Equal,
+                ("", _) => Ordering::Greater,
+                (_, "") => Ordering::Less,
+                (x, y) => x.cmp(y),
+            });
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+}
+
+impl PartialOrd for GMLVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for GMLVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GMLVersion {}
+
+impl Display for GMLVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<i64> for GMLVersion {
+    fn from(value: i64) -> Self {
+        GMLVersion(value.to_string())
+    }
+}
+
+/// The shortest text that reads back as `value`, so `2.10` becomes `2.1`.
+impl From<f64> for GMLVersion {
+    fn from(value: f64) -> Self {
+        GMLVersion(value.to_string())
+    }
+}
+
+impl From<&str> for GMLVersion {
+    fn from(value: &str) -> Self {
+        GMLVersion(value.trim().to_owned())
+    }
+}
+
+impl TryFrom<&GMLValue> for GMLVersion {
+    type Error = Error;
+    /// Fails for objects and lists
+    fn try_from(value: &GMLValue) -> Result<Self, Self::Error> {
+        match value {
+            GMLValue::GMLString(s) => Ok(s.as_str().into()),
+            GMLValue::GMLInt(i) => Ok((*i).into()),
+            GMLValue::GMLFloat(f) => Ok((*f).into()),
+            GMLValue::GMLObject(_) | GMLValue::GMLList(_) => Err(Error::wrong_types(
+                "graph",
+                "Version",
+                &[GMLType::Int, GMLType::Float, GMLType::String],
+                value.clone(),
+            )),
+        }
+    }
+}
+
+impl Graph {
+    /// The `Version` (or `version`) attribute of the graph if it is a number or a string.
+    /// An unquoted `Version 2.10` is read as `2.1`, see [GMLVersion].
+    ///
+    /// Many tools write `Version` next to `graph` rather than inside it. That key is available
+    /// through [Graph::from_gml_with_root] and [GMLVersion]'s `TryFrom<&GMLValue>`.
+    pub fn version(&self) -> Option<GMLVersion> {
+        let (_, value) = self
+            .get_attribute("Version")
+            .or_else(|| self.get_attribute("version"))?;
+        value.try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn version_forms() {
+        for text in ["Version 2", "Version 2.0", "version \"2\""] {
+            let graph = Graph::from_str(&format!("graph [\n{}\n]", text)).unwrap();
+            let version = graph.version().unwrap();
+            assert_eq!(version, GMLVersion::from(2.0));
+            assert_eq!(version.as_f64(), Some(2.0));
+        }
+        let graph = Graph::from_str("graph [\nVersion \"2.10\"\n]").unwrap();
+        let version = graph.version().unwrap();
+        assert_eq!(version.as_str(), "2.10");
+        assert!(version > GMLVersion::from(2.2));
+        assert!(version >= GMLVersion::from("2.10.0"));
+        // Unquoted, the version is a float and loses its trailing zero
+        let graph = Graph::from_str("graph [\nVersion 2.10\n]").unwrap();
+        let version = graph.version().unwrap();
+        assert_eq!(version.as_str(), "2.1");
+        assert!(version < GMLVersion::from(2.2));
+        assert_ne!(version, GMLVersion::from("2.10"));
+        assert!(Graph::new().version().is_none());
+
+        let root = GMLObject::from_str("Version 2.2\ngraph [\n]").unwrap();
+        let (_, rest) = Graph::from_gml_with_root(root).unwrap();
+        let version = GMLVersion::try_from(rest.get("Version").unwrap()).unwrap();
+        assert_eq!(version.to_string(), "2.2");
+        assert!(GMLVersion::try_from(&GMLValue::GMLObject(Box::new(rest))).is_err());
+    }
+}
@@ -0,0 +1,1086 @@
+//! Serialization of a [GMLObject] or [Graph] back into GML text.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Edge, Error, GMLObject, GMLValue, Graph, HasGMLAttributes, Node, Result};
+
+/// Settings for [Graph::write_gml] and [GMLObject::write_gml].
+#[derive(Debug, Clone)]
+pub struct SerializeOptions {
+    /// Written once per nesting level in front of every pair, a tab by default
+    pub indent: String,
+    /// Record who wrote the document in root-level keys, replacing any previous record.
+    /// The written value is not changed.
+    pub stamp_provenance: Option<Provenance>,
+    /// What to do with keys that GML can't represent
+    pub invalid_keys: InvalidKeyPolicy,
+    /// Written as `directed` by [Graph::write_gml] when [Graph::directed] is None, which
+    /// otherwise leaves the key out
+    pub write_missing_directed: Option<bool>,
+    /// Write `label ""` for the nodes and edges without a label in [Graph::write_gml]
+    pub always_write_labels: bool,
+    /// Leave out the attributes of the graph, its nodes and its edges whose value is an empty
+    /// string or an empty object in [Graph::write_gml]. Labels and nested values are kept.
+    pub skip_empty_attrs: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            indent: "\t".to_owned(),
+            stamp_provenance: None,
+            invalid_keys: InvalidKeyPolicy::Error,
+            write_missing_directed: None,
+            always_write_labels: false,
+            skip_empty_attrs: false,
+        }
+    }
+}
+
+/// How the writer handles keys that can't be written, see [SerializeOptions::invalid_keys].
+///
+/// Keys that aren't plain ASCII identifiers, such as `my key` or `größe`, are written in
+/// double quotes, which the parser reads back unchanged. Only keys that are empty or contain
+/// `"`, `\` or a line break can't be written at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidKeyPolicy {
+    /// Fail with an [io::ErrorKind::InvalidInput] error wrapping [Error::InvalidKey]
+    #[default]
+    Error,
+    /// Replace the characters that can't be written with `_`, and an empty key with `_`.
+    /// [GMLObject::sanitize_keys] applies the same replacement and reports it.
+    Sanitize,
+}
+
+/// Root-level keys written in front of the document, see [SerializeOptions::stamp_provenance].
+///
+/// Existing root pairs with any of the written keys are left out, so stamping a document that
+/// was already stamped doesn't repeat them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Written as `Creator`
+    pub creator: String,
+    /// Also write the current time as `gml_parser_timestamp`, in seconds since the unix epoch
+    pub timestamp: bool,
+    /// Written after the other keys
+    pub extra: Vec<(String, GMLValue)>,
+}
+
+impl Provenance {
+    fn pairs(&self) -> Vec<(String, GMLValue)> {
+        let mut pairs = vec![
+            (
+                "Creator".to_owned(),
+                GMLValue::GMLString(self.creator.clone()),
+            ),
+            (
+                "gml_parser_version".to_owned(),
+                GMLValue::GMLString(env!("CARGO_PKG_VERSION").to_owned()),
+            ),
+        ];
+        if self.timestamp {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as i64);
+            pairs.push(("gml_parser_timestamp".to_owned(), GMLValue::GMLInt(now)));
+        }
+        pairs.extend(self.extra.iter().cloned());
+        pairs
+    }
+}
+
+/// Reformat GML text with one pair per line and consistent indentation.
+///
+/// Every pair is kept, in order, including `comment` keys. Besides the layout, numbers are
+/// written in their usual form (`+3` becomes `3`). Formatting already formatted text returns
+/// it unchanged.
+///
+/// ```
+/// use gml_parser::SerializeOptions;
+///
+/// let text = gml_parser::format("graph [\n  node [\nid 1\n      ]\n]", SerializeOptions::default());
+/// assert_eq!(text.unwrap(), "graph [\n\tnode [\n\t\tid 1\n\t]\n]\n");
+/// ```
+pub fn format(text: &str, options: SerializeOptions) -> Result<String> {
+    let root = GMLObject::from_str(text)?;
+    let mut out = Vec::new();
+    root.write_gml(&mut out, &options)
+        .expect("writing to a Vec doesn't fail");
+    Ok(String::from_utf8(out).expect("the writer only emits utf-8"))
+}
+
+/// Write several graphs to a single file, as one `graph [...]` block each separated by an
+/// empty line, which [Graph::all_from_gml] reads back.
+///
+/// Each graph is written as it would be by [Graph::write_gml] with the default
+/// [SerializeOptions].
+pub fn write_graphs<W: io::Write>(graphs: &[Graph], w: &mut W) -> io::Result<()> {
+    write_graphs_with(graphs, w, &SerializeOptions::default())
+}
+
+/// Like [write_graphs], writing each graph with `options`.
+///
+/// The root-level keys of [SerializeOptions::stamp_provenance] are shared by the graphs, so
+/// they are written once at the top of the document.
+pub fn write_graphs_with<W: io::Write>(
+    graphs: &[Graph],
+    w: &mut W,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    let unstamped = SerializeOptions {
+        stamp_provenance: None,
+        ..options.clone()
+    };
+    for (i, graph) in graphs.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        graph.write_gml(&mut *w, if i == 0 { options } else { &unstamped })?;
+    }
+    Ok(())
+}
+
+/// Write each graph to its own file in the directory `dir`, creating it if needed, and
+/// return the paths of the files in the order of `graphs`.
+///
+/// `name_fn` gets the position and the graph and returns the name of the file without the
+/// `.gml` extension, [graph_file_name] is a good default. Characters other than ASCII
+/// letters, digits, `-`, `_` and `.` are replaced with `_`, and names that are taken by an
+/// earlier graph get a `_2`, `_3`, ... suffix, so every graph gets its own file. Existing
+/// files are overwritten.
+pub fn write_graphs_to_dir<P, F>(
+    graphs: &[Graph],
+    dir: P,
+    options: &SerializeOptions,
+    mut name_fn: F,
+) -> io::Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize, &Graph) -> String,
+{
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut names: Vec<String> = Vec::with_capacity(graphs.len());
+    let mut paths = Vec::with_capacity(graphs.len());
+    for (i, graph) in graphs.iter().enumerate() {
+        let stem = sanitize_file_name(&name_fn(i, graph), i);
+        let mut name = stem.clone();
+        let mut n = 1;
+        while names.iter().any(|taken| taken.eq_ignore_ascii_case(&name)) {
+            n += 1;
+            name = format!("{}_{}", stem, n);
+        }
+        let path = dir.join(format!("{}.gml", name));
+        let mut w = BufWriter::new(File::create(&path)?);
+        graph.write_gml(&mut w, options)?;
+        w.flush()?;
+        names.push(name);
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// The file name [write_graphs_to_dir] uses by default: the label of the graph, else
+/// `graph_` followed by its id, else `graph_` followed by its position in the slice.
+pub fn graph_file_name(index: usize, graph: &Graph) -> String {
+    match (&graph.label, graph.id) {
+        (Some(label), _) if !label.trim().is_empty() => label.trim().to_owned(),
+        (_, Some(id)) => format!("graph_{}", id),
+        _ => format!("graph_{}", index),
+    }
+}
+
+// Safe on every platform, and never hidden or empty
+fn sanitize_file_name(name: &str, index: usize) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_matches('.');
+    if name.is_empty() {
+        format!("graph_{}", index)
+    } else {
+        name.to_owned()
+    }
+}
+
+impl GMLObject {
+    /// Render the pairs as GML text using the default [SerializeOptions], except that keys
+    /// that can't be written are sanitized, see [InvalidKeyPolicy::Sanitize].
+    ///
+    /// Fails on the values that [GMLObject::write_gml] can't write, with the [Error] it wraps.
+    pub fn to_gml(&self) -> Result<String> {
+        let options = SerializeOptions {
+            invalid_keys: InvalidKeyPolicy::Sanitize,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        self.write_gml(&mut out, &options).map_err(write_error)?;
+        Ok(String::from_utf8(out).expect("the writer only emits utf-8"))
+    }
+    /// Write the pairs as GML text, one pair per line.
+    ///
+    /// Strings are quoted with `"` unless they contain one, in which case `'` is used. The
+    /// parser reads strings back verbatim, without unescaping, so strings containing both
+    /// quotes, a line break or an escape such as `\x` the grammar rejects fail with
+    /// [io::ErrorKind::InvalidInput].
+    /// Floats are always written with a decimal point so that they read back as floats.
+    /// Lists are written on one line and fail with [io::ErrorKind::InvalidInput] when they hold
+    /// an object or a list, or when they are empty since `[ ]` reads back as an empty object.
+    pub fn write_gml<W: io::Write>(&self, mut w: W, options: &SerializeOptions) -> io::Result<()> {
+        let Some(provenance) = &options.stamp_provenance else {
+            return write_pairs(&mut w, &self.pairs, ROOT, 0, options);
+        };
+        let stamp = provenance.pairs();
+        write_pairs(&mut w, &stamp, ROOT, 0, options)?;
+        for pair in &self.pairs {
+            if !stamp.iter().any(|(key, _)| *key == pair.0) {
+                write_pairs(&mut w, std::slice::from_ref(pair), ROOT, 0, options)?;
+            }
+        }
+        Ok(())
+    }
+    /// Apply [InvalidKeyPolicy::Sanitize] to every key, at any depth.
+    ///
+    /// Returns each key that was replaced with its replacement, once per key. Different keys
+    /// can end up with the same replacement.
+    pub fn sanitize_keys(&mut self) -> Vec<(String, String)> {
+        let mut replaced = Vec::new();
+        sanitize_pairs(&mut self.pairs, &mut replaced);
+        replaced
+    }
+}
+
+impl Graph {
+    /// Convert the graph back into the `graph [...]` root object it would be parsed from.
+    ///
+    /// Graph-level keys and attributes come first, followed by the nodes and then the edges.
+    pub fn to_gml_object(&self) -> GMLObject {
+        self.to_gml_object_with(&SerializeOptions::default())
+    }
+
+    pub(crate) fn to_gml_object_with(&self, options: &SerializeOptions) -> GMLObject {
+        let mut pairs = Vec::new();
+        if let Some(directed) = self.directed.or(options.write_missing_directed) {
+            pairs.push(("directed".to_owned(), GMLValue::GMLInt(directed as i64)));
+        }
+        if let Some(id) = self.id {
+            pairs.push(("id".to_owned(), GMLValue::GMLInt(id)));
+        }
+        if let Some(name) = &self.name {
+            pairs.push(("name".to_owned(), GMLValue::GMLString(name.clone())));
+        }
+        if let Some(label) = &self.label {
+            pairs.push(("label".to_owned(), GMLValue::GMLString(label.clone())));
+        }
+        pairs.extend(attrs(&self.attrs, options));
+        pairs.extend(self.nodes.iter().map(|node| node_pair(node, options)));
+        pairs.extend(self.edges.iter().map(|edge| edge_pair(edge, options)));
+        GMLObject {
+            pairs: vec![(
+                "graph".to_owned(),
+                GMLValue::GMLObject(Box::new(GMLObject { pairs })),
+            )],
+        }
+    }
+    /// Render the graph as GML text using the default [SerializeOptions].
+    ///
+    /// The output parses back into an equal graph, except for attribute order. Fails like
+    /// [GMLObject::to_gml] on values that can't be written.
+    pub fn to_gml(&self) -> Result<String> {
+        self.to_gml_object().to_gml()
+    }
+    /// Write the graph as GML text. See [Graph::to_gml_object] for the layout of the output.
+    ///
+    /// [SerializeOptions::write_missing_directed], [SerializeOptions::always_write_labels] and
+    /// [SerializeOptions::skip_empty_attrs] decide whether missing and empty values are
+    /// written.
+    pub fn write_gml<W: io::Write>(&self, w: W, options: &SerializeOptions) -> io::Result<()> {
+        self.to_gml_object_with(options).write_gml(w, options)
+    }
+    /// Apply [InvalidKeyPolicy::Sanitize] to the attribute keys of the graph, its nodes and
+    /// its edges, like [GMLObject::sanitize_keys].
+    pub fn sanitize_keys(&mut self) -> Vec<(String, String)> {
+        let mut replaced = Vec::new();
+        sanitize_pairs(self.attributes_mut(), &mut replaced);
+        for node in &mut self.nodes {
+            sanitize_pairs(node.attributes_mut(), &mut replaced);
+        }
+        for edge in &mut self.edges {
+            sanitize_pairs(edge.attributes_mut(), &mut replaced);
+        }
+        replaced
+    }
+}
+
+impl Node {
+    /// Render the node as a `node [...]` block, which [Node::from_gml_str] reads back.
+    /// Fails like [GMLObject::to_gml].
+    pub fn to_gml_string(&self) -> Result<String> {
+        GMLObject {
+            pairs: vec![node_pair(self, &SerializeOptions::default())],
+        }
+        .to_gml()
+    }
+}
+
+impl Edge {
+    /// Render the edge as an `edge [...]` block, which [Edge::from_gml_str] reads back.
+    /// Fails like [GMLObject::to_gml].
+    pub fn to_gml_string(&self) -> Result<String> {
+        GMLObject {
+            pairs: vec![edge_pair(self, &SerializeOptions::default())],
+        }
+        .to_gml()
+    }
+}
+
+fn node_pair(node: &Node, options: &SerializeOptions) -> (String, GMLValue) {
+    let mut pairs = vec![("id".to_owned(), GMLValue::GMLInt(node.id))];
+    pairs.extend(label(&node.label, options));
+    pairs.extend(attrs(&node.attrs, options));
+    (
+        "node".to_owned(),
+        GMLValue::GMLObject(Box::new(GMLObject { pairs })),
+    )
+}
+
+fn edge_pair(edge: &Edge, options: &SerializeOptions) -> (String, GMLValue) {
+    let mut pairs = vec![
+        ("source".to_owned(), GMLValue::GMLInt(edge.source)),
+        ("target".to_owned(), GMLValue::GMLInt(edge.target)),
+    ];
+    pairs.extend(label(&edge.label, options));
+    pairs.extend(attrs(&edge.attrs, options));
+    (
+        "edge".to_owned(),
+        GMLValue::GMLObject(Box::new(GMLObject { pairs })),
+    )
+}
+
+fn label(label: &Option<String>, options: &SerializeOptions) -> Option<(String, GMLValue)> {
+    let label = match label {
+        Some(label) => label.clone(),
+        None if options.always_write_labels => String::new(),
+        None => return None,
+    };
+    Some(("label".to_owned(), GMLValue::GMLString(label)))
+}
+
+fn attrs<'a>(
+    attrs: &'a [(String, GMLValue)],
+    options: &'a SerializeOptions,
+) -> impl Iterator<Item = (String, GMLValue)> + 'a {
+    attrs
+        .iter()
+        .filter(|(_, value)| {
+            let empty = match value {
+                GMLValue::GMLString(s) => s.is_empty(),
+                GMLValue::GMLObject(obj) => obj.pairs.is_empty(),
+                _ => false,
+            };
+            !(empty && options.skip_empty_attrs)
+        })
+        .cloned()
+}
+
+// Element name used in errors about root-level keys
+const ROOT: &str = "the document";
+
+// `element` is the key of the object holding the pairs, used in errors
+fn write_pairs<W: io::Write>(
+    w: &mut W,
+    pairs: &[(String, GMLValue)],
+    element: &str,
+    depth: usize,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    for (key, value) in pairs {
+        write_indent(w, depth, options)?;
+        let key = if is_writable_key(key) {
+            Cow::Borrowed(key.as_str())
+        } else if options.invalid_keys == InvalidKeyPolicy::Sanitize {
+            sanitize_key(key)
+        } else {
+            let err = Error::invalid_key(element, key);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, err));
+        };
+        write!(w, "{} ", Key(&key))?;
+        match value {
+            GMLValue::GMLString(s) => write_string(w, s, &key, element)?,
+            GMLValue::GMLInt(i) => write!(w, "{}", i)?,
+            GMLValue::GMLFloat(f) => write!(w, "{}", FloatText(*f))?,
+            GMLValue::GMLObject(obj) if obj.pairs.is_empty() => write!(w, "[]")?,
+            GMLValue::GMLList(items) if items.is_empty() => {
+                let err = Error::Other(format!(
+                    "The list {} of {} is empty, which GML can't tell apart from an empty object",
+                    key, element
+                ));
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, err));
+            }
+            GMLValue::GMLList(items) => {
+                write!(w, "[")?;
+                for item in items {
+                    write!(w, " ")?;
+                    match item {
+                        GMLValue::GMLString(s) => write_string(w, s, &key, element)?,
+                        GMLValue::GMLInt(i) => write!(w, "{}", i)?,
+                        GMLValue::GMLFloat(f) => write!(w, "{}", FloatText(*f))?,
+                        GMLValue::GMLObject(_) | GMLValue::GMLList(_) => {
+                            let err = Error::Other(format!(
+                                "The list {} of {} holds a {} but lists can only hold ints, \
+                                 floats and strings",
+                                key,
+                                element,
+                                item.gml_type()
+                            ));
+                            return Err(io::Error::new(io::ErrorKind::InvalidInput, err));
+                        }
+                    }
+                }
+                write!(w, " ]")?;
+            }
+            GMLValue::GMLObject(obj) => {
+                writeln!(w, "[")?;
+                write_pairs(w, &obj.pairs, &key, depth + 1, options)?;
+                write_indent(w, depth, options)?;
+                write!(w, "]")?;
+            }
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+// The writer reports values it can't write as an io::Error wrapping the crate's Error
+pub(crate) fn write_error(err: io::Error) -> Error {
+    match err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<Error>())
+    {
+        Some(inner) => inner.clone(),
+        None => Error::from(err),
+    }
+}
+
+// Keys the parser reads without quotes, whatever the ParseOptions
+fn is_plain_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Whether `key` can be written, possibly in quotes, see [InvalidKeyPolicy].
+pub(crate) fn is_writable_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(is_unquotable)
+}
+
+fn is_unquotable(c: char) -> bool {
+    matches!(c, '"' | '\\' | '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+fn sanitize_key(key: &str) -> Cow<'_, str> {
+    if is_writable_key(key) {
+        Cow::Borrowed(key)
+    } else if key.is_empty() {
+        Cow::Owned("_".to_owned())
+    } else {
+        Cow::Owned(key.replace(is_unquotable, "_"))
+    }
+}
+
+fn sanitize_pairs(pairs: &mut [(String, GMLValue)], replaced: &mut Vec<(String, String)>) {
+    for (key, value) in pairs {
+        if let Cow::Owned(sanitized) = sanitize_key(key) {
+            let original = std::mem::replace(key, sanitized);
+            if !replaced.iter().any(|(k, _)| *k == original) {
+                replaced.push((original, key.clone()));
+            }
+        }
+        if let GMLValue::GMLObject(obj) = value {
+            sanitize_pairs(&mut obj.pairs, replaced);
+        }
+    }
+}
+
+fn write_indent<W: io::Write>(
+    w: &mut W,
+    depth: usize,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    for _ in 0..depth {
+        w.write_all(options.indent.as_bytes())?;
+    }
+    Ok(())
+}
+
+// A key that has already been checked or sanitized, quoted when needed
+struct Key<'a>(&'a str);
+
+impl fmt::Display for Key<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_plain_key(self.0) {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "\"{}\"", self.0)
+        }
+    }
+}
+
+fn write_string<W: io::Write>(w: &mut W, s: &str, key: &str, element: &str) -> io::Result<()> {
+    if quote_for(s).is_none() {
+        let err = Error::Other(format!(
+            "The string {} of {} holds both kinds of quotes, a line break or an invalid escape, \
+             which GML can't represent",
+            key, element
+        ));
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, err));
+    }
+    write!(w, "{}", Quoted(s))
+}
+
+// Strings are read back verbatim, without unescaping, so a string can only be written when
+// one of the quotes doesn't appear in it unescaped and it has no line break or escape the
+// grammar rejects. Returns the quote to use, `"` if possible.
+fn quote_for(s: &str) -> Option<char> {
+    let (mut double, mut single) = (false, false);
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => double = true,
+            '\'' => single = true,
+            '\n' | '\r' | '\u{2028}' | '\u{2029}' => return None,
+            '\\' => match chars.next() {
+                None | Some('x' | '1'..='9') => return None,
+                // A line continuation
+                Some('\r') => {
+                    chars.next_if_eq(&'\n');
+                }
+                Some('u') => {
+                    for _ in 0..4 {
+                        chars.next_if(char::is_ascii_hexdigit).map(|_| ())?;
+                    }
+                }
+                Some(_) => {}
+            },
+            _ => {}
+        }
+    }
+    match (double, single) {
+        (false, _) => Some('"'),
+        (true, false) => Some('\''),
+        (true, true) => None,
+    }
+}
+
+// A string in the quote chosen by quote_for, or in `"` when it can't be written
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quote = quote_for(self.0).unwrap_or('"');
+        write!(f, "{}{}{}", quote, self.0, quote)
+    }
+}
+
+// Floats always get a decimal point so they are read back as floats
+struct FloatText(f64);
+
+impl fmt::Display for FloatText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.0.to_string();
+        if self.0.is_finite() && !text.contains('.') {
+            write!(f, "{}.0", text)
+        } else {
+            write!(f, "{}", text)
+        }
+    }
+}
+
+/// Renders the value as it would appear after its key in GML text: strings in quotes,
+/// numbers bare, lists as `[ 1 2 3 ]` and objects as a bracketed block with one tab-indented
+/// pair per line.
+///
+/// Unlike [GMLObject::to_gml] this never fails: keys that can't be written are sanitized, see
+/// [InvalidKeyPolicy::Sanitize], and strings and lists that GML can't express, see
+/// [GMLObject::write_gml], are rendered anyway, so the output of those doesn't parse back.
+impl fmt::Display for GMLValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_value(f, self, 0)
+    }
+}
+
+fn fmt_value(f: &mut fmt::Formatter<'_>, value: &GMLValue, depth: usize) -> fmt::Result {
+    match value {
+        GMLValue::GMLString(s) => write!(f, "{}", Quoted(s)),
+        GMLValue::GMLInt(i) => write!(f, "{}", i),
+        GMLValue::GMLFloat(x) => write!(f, "{}", FloatText(*x)),
+        GMLValue::GMLObject(obj) if obj.pairs.is_empty() => write!(f, "[]"),
+        GMLValue::GMLObject(obj) => {
+            writeln!(f, "[")?;
+            for (key, value) in &obj.pairs {
+                write!(f, "{}{} ", "\t".repeat(depth + 1), Key(&sanitize_key(key)))?;
+                fmt_value(f, value, depth + 1)?;
+                writeln!(f)?;
+            }
+            write!(f, "{}]", "\t".repeat(depth))
+        }
+        GMLValue::GMLList(items) => {
+            write!(f, "[")?;
+            for item in items {
+                write!(f, " ")?;
+                fmt_value(f, item, depth)?;
+            }
+            write!(f, " ]")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+
+    #[test]
+    fn write_round_trip() {
+        for path in ["tests/wikipedia.gml", "tests/synoptic.gml"] {
+            let file = fs::read_to_string(path).unwrap();
+            let mut graph = Graph::from_str(&file).unwrap();
+            graph.nodes[0]
+                .attrs
+                .push(("size".into(), GMLValue::GMLFloat(2.0)));
+            graph.nodes[0]
+                .attrs
+                .push(("quote".into(), GMLValue::GMLString("say \"hi\"".into())));
+            let text = graph.to_gml().unwrap();
+            let read = Graph::from_str(&text).unwrap();
+            assert_eq!(read.to_gml().unwrap(), text);
+            assert_eq!(read.nodes, graph.nodes);
+            assert_eq!(read.edges, graph.edges);
+            assert_eq!(read.directed, graph.directed);
+            assert_eq!(read.label, graph.label);
+        }
+    }
+
+    #[test]
+    fn display_values() {
+        let text = "a [\nb 2.0\n\"c d\" [\n]\ne [\nf 'say \"hi\"'\n]\n]";
+        let mut object = GMLObject::from_str(text).unwrap().pairs.remove(0).1;
+        assert_eq!(GMLValue::from("x y").to_string(), "\"x y\"");
+        assert_eq!(GMLValue::from(-3).to_string(), "-3");
+        assert_eq!(GMLValue::from(2.0).to_string(), "2.0");
+        assert_eq!(GMLValue::from(0.25).to_string(), "0.25");
+        assert_eq!(
+            object.to_string(),
+            "[\n\tb 2.0\n\t\"c d\" []\n\te [\n\t\tf 'say \"hi\"'\n\t]\n]"
+        );
+        assert_eq!(
+            GMLValue::from(GMLObject { pairs: Vec::new() }).to_string(),
+            "[]"
+        );
+        let list = GMLValue::GMLList(vec![1.into(), "a".into(), 2.5.into()]);
+        assert_eq!(list.to_string(), "[ 1 \"a\" 2.5 ]");
+
+        // Never fails, even for what can't be written as GML
+        let GMLValue::GMLObject(obj) = &mut object else {
+            panic!("a is an object");
+        };
+        obj.pairs.truncate(1);
+        obj.pairs
+            .push(("bad\nkey".into(), GMLValue::GMLList(vec![list])));
+        assert_eq!(
+            object.to_string(),
+            "[\n\tb 2.0\n\tbad_key [ [ 1 \"a\" 2.5 ] ]\n]"
+        );
+    }
+
+    #[test]
+    fn missing_and_empty_values() {
+        let mut graph = Graph::from_str("graph [\nnode [\nid 1\n]\nnode [\nid 2\nlabel \"b\"\n]\nedge [\nsource 1\ntarget 2\n]\n]").unwrap();
+        graph.set_attribute("comment", "");
+        graph.nodes[0].set_attribute("color", "");
+        graph.nodes[0].set_attribute("graphics", GMLObject { pairs: Vec::new() });
+        graph.nodes[1].set_attribute("size", 0);
+        let write = |graph: &Graph, options: &SerializeOptions| {
+            let mut out = Vec::new();
+            graph.write_gml(&mut out, options).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let omitting = SerializeOptions {
+            skip_empty_attrs: true,
+            ..Default::default()
+        };
+        let omitted = write(&graph, &omitting);
+        assert_eq!(
+            omitted,
+            "graph [\n\tnode [\n\t\tid 1\n\t]\n\tnode [\n\t\tid 2\n\t\tlabel \"b\"\n\t\tsize 0\n\t]\n\tedge [\n\t\tsource 1\n\t\ttarget 2\n\t]\n]\n"
+        );
+        let read = Graph::from_str(&omitted).unwrap();
+        assert_eq!(read.directed, None);
+        assert_eq!(read.nodes[0].label, None);
+        assert!(read.nodes[0].attributes().is_empty());
+
+        let explicit = SerializeOptions {
+            write_missing_directed: Some(false),
+            always_write_labels: true,
+            ..Default::default()
+        };
+        let written = write(&graph, &explicit);
+        assert_eq!(
+            written,
+            "graph [\n\tdirected 0\n\tcomment \"\"\n\tnode [\n\t\tid 1\n\t\tlabel \"\"\n\t\tcolor \"\"\n\t\tgraphics []\n\t]\n\tnode [\n\t\tid 2\n\t\tlabel \"b\"\n\t\tsize 0\n\t]\n\tedge [\n\t\tsource 1\n\t\ttarget 2\n\t\tlabel \"\"\n\t]\n]\n"
+        );
+        let read = Graph::from_str(&written).unwrap();
+        assert_eq!(read.directed, Some(false));
+        assert_eq!(read.nodes[0].label.as_deref(), Some(""));
+        assert_eq!(read.nodes[0].attributes(), graph.nodes[0].attributes());
+        assert_eq!(read.nodes[1], graph.nodes[1]);
+        assert_eq!(read.attributes(), graph.attributes());
+
+        // A graph that knows its direction keeps it
+        graph.directed = Some(true);
+        assert!(write(&graph, &explicit).starts_with("graph [\n\tdirected 1\n"));
+        assert_eq!(
+            write(&graph, &SerializeOptions::default()),
+            graph.to_gml().unwrap()
+        );
+    }
+
+    #[test]
+    fn write_layout() {
+        let root = GMLObject::from_str("graph [\nnode [\nid 1\ngraphics []\n]\n]").unwrap();
+        let options = SerializeOptions {
+            indent: "  ".into(),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        root.write_gml(&mut out, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "graph [\n  node [\n    id 1\n    graphics []\n  ]\n]\n"
+        );
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        for path in [
+            "tests/wikipedia.gml",
+            "tests/synoptic.gml",
+            "tests/wrapped.gml",
+        ] {
+            let file = fs::read_to_string(path).unwrap();
+            let options = SerializeOptions {
+                indent: "    ".into(),
+                ..Default::default()
+            };
+            let once = format(&file, options.clone()).unwrap();
+            assert_eq!(format(&once, options).unwrap(), once);
+            assert_eq!(
+                GMLObject::from_str(&once).unwrap(),
+                GMLObject::from_str(&file).unwrap()
+            );
+        }
+        assert!(format("graph [", SerializeOptions::default())
+            .unwrap_err()
+            .is_syntax());
+    }
+
+    #[test]
+    fn write_multiple_graphs() {
+        let mut graphs = Vec::new();
+        for name in ["wikipedia", "synoptic"] {
+            let file = fs::read_to_string(format!("tests/{}.gml", name)).unwrap();
+            graphs.push(Graph::from_str(&file).unwrap());
+        }
+        let mut out = Vec::new();
+        write_graphs(&graphs, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches("\ngraph [").count(), 1);
+        let read = Graph::all_from_gml(GMLObject::from_str(&text).unwrap()).unwrap();
+        assert_eq!(read.len(), 2);
+        for (read, graph) in read.iter().zip(&graphs) {
+            assert_eq!(read.directed, graph.directed);
+            assert_eq!(read.nodes, graph.nodes);
+            assert_eq!(read.edges, graph.edges);
+        }
+        assert_eq!(read[0].to_gml().unwrap(), graphs[0].to_gml().unwrap());
+
+        let root = GMLObject::from_str("Creator \"x\"\ngraph 1").unwrap();
+        assert!(Graph::all_from_gml(root).is_err());
+
+        let options = SerializeOptions {
+            stamp_provenance: Some(Provenance {
+                creator: "tests".into(),
+                timestamp: false,
+                extra: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_graphs_with(&graphs, &mut out, &options).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("Creator \"tests\"\n"));
+        assert_eq!(text.matches("Creator").count(), 1);
+        let read = Graph::all_from_gml(GMLObject::from_str(&text).unwrap()).unwrap();
+        assert_eq!(read.len(), 2);
+        for (read, graph) in read.iter().zip(&graphs) {
+            assert_eq!(read.to_gml().unwrap(), graph.to_gml().unwrap());
+        }
+    }
+
+    #[test]
+    fn write_graphs_to_files() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let graph = Graph::from_str(&file).unwrap();
+        let mut graphs = vec![graph.clone(), graph.clone(), graph.clone(), graph];
+        graphs[1].label = Some("../etc/passwd".into());
+        graphs[2].label = None;
+        graphs[3].label = None;
+        graphs[3].id = None;
+
+        let dir = std::env::temp_dir().join(format!("gml_parser_write_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let options = SerializeOptions::default();
+        let paths = write_graphs_to_dir(&graphs, &dir, &options, graph_file_name).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Hello__I_am_a_graph.gml",
+                "_etc_passwd.gml",
+                "graph_42.gml",
+                "graph_3.gml"
+            ]
+        );
+        for (path, graph) in paths.iter().zip(&graphs) {
+            assert_eq!(
+                Graph::from_path(path).unwrap().to_gml().unwrap(),
+                graph.to_gml().unwrap()
+            );
+        }
+
+        let paths = write_graphs_to_dir(&graphs, &dir, &options, |_, _| "same".into()).unwrap();
+        let names: Vec<_> = paths.iter().map(|p| p.file_name().unwrap()).collect();
+        assert_eq!(
+            names,
+            ["same.gml", "same_2.gml", "same_3.gml", "same_4.gml"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stamp_provenance() {
+        let write = |root: &GMLObject, options: &SerializeOptions| {
+            let mut out = Vec::new();
+            root.write_gml(&mut out, options).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let mut options = SerializeOptions {
+            stamp_provenance: Some(Provenance {
+                creator: "pipeline".into(),
+                timestamp: false,
+                extra: vec![("run".into(), GMLValue::GMLInt(7))],
+            }),
+            ..Default::default()
+        };
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let original =
+            GMLObject::from_str(&format!("Creator \"old\"\n{}\nCreator \"x\"", file)).unwrap();
+        let once = write(&original, &options);
+        let twice = write(&GMLObject::from_str(&once).unwrap(), &options);
+        assert_eq!(once, twice);
+        let stamped = GMLObject::from_str(&twice).unwrap();
+        let keys: Vec<_> = stamped.pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["Creator", "gml_parser_version", "run", "graph"]);
+        assert_eq!(
+            stamped.get("Creator"),
+            Some(&GMLValue::GMLString("pipeline".into()))
+        );
+        assert_eq!(stamped.get("graph"), original.get("graph"));
+
+        options.stamp_provenance.as_mut().unwrap().timestamp = true;
+        let once = write(&stamped, &options);
+        let twice =
+            GMLObject::from_str(&write(&GMLObject::from_str(&once).unwrap(), &options)).unwrap();
+        assert_eq!(twice.pairs.len(), 5);
+        assert!(twice.get("gml_parser_timestamp").unwrap().as_i64().unwrap() > 1_700_000_000);
+
+        // Graphs are stamped without being changed
+        let graph = Graph::from_str(&file).unwrap();
+        let before = graph.to_gml().unwrap();
+        let mut out = Vec::new();
+        graph.write_gml(&mut out, &options).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Creator \"pipeline\"\n"));
+        assert_eq!(graph.to_gml().unwrap(), before);
+    }
+
+    #[test]
+    fn write_invalid_keys() {
+        let file = fs::read_to_string("tests/wikipedia.gml").unwrap();
+        let mut graph = Graph::from_str(&file).unwrap();
+        graph.nodes[0].set_attribute("my key", 1);
+        graph.nodes[0].set_attribute("größe", 2);
+        graph.nodes[0].set_attribute("a[b]", 3);
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(read.nodes, graph.nodes);
+
+        graph.nodes[1].set_attribute("say \"hi\"", 4);
+        let mut out = Vec::new();
+        let err = graph
+            .write_gml(&mut out, &SerializeOptions::default())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let inner = err.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+        assert_eq!(inner, &Error::invalid_key("node", "say \"hi\""));
+        assert_eq!(inner.kind(), ErrorKind::InvalidKey);
+
+        let read = Graph::from_str(&graph.to_gml().unwrap()).unwrap();
+        assert_eq!(read.nodes[1].attr_i64("say _hi_"), Some(4));
+        let mut sanitized = graph.clone();
+        assert_eq!(
+            sanitized.sanitize_keys(),
+            vec![("say \"hi\"".to_owned(), "say _hi_".to_owned())]
+        );
+        assert_eq!(read.nodes, sanitized.nodes);
+
+        let node = &mut graph.nodes[2];
+        assert_eq!(
+            node.try_set_attribute("line\nbreak", 5).unwrap_err().kind(),
+            ErrorKind::InvalidKey
+        );
+        assert!(node.try_set_attribute("", 5).is_err());
+        assert!(!node.has_attribute(""));
+        node.try_set_attribute("fine key", 5).unwrap();
+        assert_eq!(node.attr_i64("fine key"), Some(5));
+    }
+
+    #[test]
+    fn write_strings() {
+        let write = |s: &str| {
+            let root = GMLObject {
+                pairs: vec![
+                    ("a".into(), GMLValue::from(s)),
+                    ("b".into(), GMLValue::GMLList(vec![1.into(), s.into()])),
+                ],
+            };
+            let mut out = Vec::new();
+            root.write_gml(&mut out, &SerializeOptions::default())
+                .map(|()| (root, String::from_utf8(out).unwrap()))
+        };
+        for s in [
+            "",
+            "a'b",
+            "say \"hi\"",
+            "it\\'s \"x\"",
+            "tab\\t",
+            "\\u00e9",
+            "\\0",
+            "a\\\nb",
+        ] {
+            let (root, text) = write(s).unwrap();
+            assert_eq!(GMLObject::from_str(&text).unwrap(), root, "{:?}", s);
+        }
+        for s in [
+            "he said \"it's\"",
+            "line\nbreak",
+            "\\x41",
+            "\\u12",
+            "\\1",
+            "end\\",
+        ] {
+            let err = write(s).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{:?}", s);
+            assert!(matches!(
+                err.get_ref().unwrap().downcast_ref::<Error>(),
+                Some(Error::Other(_))
+            ));
+        }
+        let mut graph = Graph::from_str("graph [\nnode [\nid 1\n]\n]").unwrap();
+        graph.nodes[0].label = Some("\"'".into());
+        assert!(matches!(graph.to_gml(), Err(Error::Other(_))));
+        assert!(graph.nodes[0].to_gml_string().is_err());
+    }
+
+    #[test]
+    fn write_lists() {
+        let list = |items: Vec<GMLValue>| GMLObject {
+            pairs: vec![("levels".into(), GMLValue::GMLList(items))],
+        };
+        let levels = list(vec![1.into(), "a b".into(), 2.5.into()]);
+        let text = levels.to_gml().unwrap();
+        assert_eq!(text, "levels [ 1 \"a b\" 2.5 ]\n");
+        assert_eq!(GMLObject::from_str(&text).unwrap(), levels);
+
+        // `[ ]` would read back as an empty object
+        assert!(matches!(list(Vec::new()).to_gml(), Err(Error::Other(_))));
+        let nested = list(vec![GMLValue::GMLList(vec![1.into()])]);
+        assert!(matches!(nested.to_gml(), Err(Error::Other(_))));
+    }
+
+    #[test]
+    fn arbitrary_keys_round_trip() {
+        let alphabet = [
+            'a', 'Z', '_', '$', '7', ' ', '\t', '[', ']', '"', '\'', '\\', '\n', '\r', '=', '#',
+            'é', '✓', '\u{2028}', '\u{a0}',
+        ];
+        // Small deterministic LCG so failures are reproducible
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) as usize) % bound
+        };
+        let sanitize = SerializeOptions {
+            invalid_keys: InvalidKeyPolicy::Sanitize,
+            ..Default::default()
+        };
+        for _ in 0..500 {
+            let key: String = (0..next(6))
+                .map(|_| alphabet[next(alphabet.len())])
+                .collect();
+            let inner = GMLObject {
+                pairs: vec![(key.clone(), GMLValue::GMLInt(1))],
+            };
+            let mut root = GMLObject {
+                pairs: vec![
+                    (key.clone(), GMLValue::GMLInt(2)),
+                    ("node".into(), GMLValue::GMLObject(Box::new(inner))),
+                ],
+            };
+            let mut out = Vec::new();
+            match root.write_gml(&mut out, &SerializeOptions::default()) {
+                Ok(()) => {
+                    let text = String::from_utf8(out).unwrap();
+                    assert_eq!(GMLObject::from_str(&text).unwrap(), root, "{:?}", key);
+                }
+                Err(_) => {
+                    assert!(key.is_empty() || key.contains(['"', '\\', '\n', '\r', '\u{2028}']))
+                }
+            }
+            let mut out = Vec::new();
+            root.write_gml(&mut out, &sanitize).unwrap();
+            let read = GMLObject::from_str(&String::from_utf8(out).unwrap()).unwrap();
+            root.sanitize_keys();
+            assert_eq!(read, root, "{:?}", key);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! The allocations of [ArenaObject::from_str_in] compared with [GMLObject::from_str].
+//!
+//! This lives in its own test binary because it installs a counting global allocator.
+#![cfg(feature = "arena")]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fs,
+};
+
+use bumpalo::Bump;
+use gml_parser::{ArenaObject, GMLObject};
+
+// Counts the allocations of each thread so tests running in parallel don't interfere
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn arena_reduces_allocations() {
+    let text = fs::read_to_string("tests/synoptic.gml").unwrap();
+    let (owned, owned_allocations) = allocations(|| GMLObject::from_str(&text).unwrap());
+    let arena = Bump::with_capacity(64 * 1024);
+    let (parsed, arena_allocations) =
+        allocations(|| ArenaObject::from_str_in(&text, &arena).unwrap());
+    assert_eq!(parsed.to_object(), owned);
+    // What is left is the parser's own bookkeeping, shared by both paths
+    assert!(
+        arena_allocations * 10 < owned_allocations,
+        "{} allocations in the arena, {} owned",
+        arena_allocations,
+        owned_allocations
+    );
+}
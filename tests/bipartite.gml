@@ -0,0 +1,63 @@
+graph [
+	directed 0
+	node [
+		id 1
+		label "alice"
+		type "user"
+	]
+	node [
+		id 2
+		label "bob"
+		type "user"
+	]
+	node [
+		id 3
+		label "carol"
+		type "user"
+	]
+	node [
+		id 10
+		label "book"
+		type "item"
+	]
+	node [
+		id 11
+		label "film"
+		type "item"
+	]
+	node [
+		id 12
+		label "song"
+		type "item"
+	]
+	edge [
+		source 1
+		target 10
+		weight 2
+	]
+	edge [
+		source 1
+		target 11
+		weight 1
+	]
+	edge [
+		source 2
+		target 10
+		weight 3
+	]
+	edge [
+		source 3
+		target 11
+		weight 4
+	]
+	edge [
+		source 3
+		target 12
+		weight 1
+	]
+	edge [
+		source 10
+		target 2
+		weight 1
+	]
+]
@@ -0,0 +1,28 @@
+graph [
+	directed 1
+	creator "first"
+	creator "second"
+	node [
+		id 1
+		weight 1
+		weight 2
+		graphics [
+			x 1.0
+			x 1.5
+			y 2.0
+		]
+	]
+	node [
+		id 2
+		weight 4
+		color "red"
+	]
+	edge [
+		source 1
+		target 2
+		weight 0.5
+		color "blue"
+		weight 0.25
+		color "green"
+	]
+]
@@ -0,0 +1,20 @@
+graph [
+	node [
+		id 1
+		label "a"
+		color "red"
+	]
+	node [
+		id 2
+		label "b"
+	]
+	node [
+		id 1
+		label "c"
+		size 3
+	]
+	edge [
+		source 1
+		target 2
+	]
+]
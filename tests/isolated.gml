@@ -0,0 +1,26 @@
+graph [
+	node [
+		id 1
+		label "a"
+	]
+	node [
+		id 2
+		label "b"
+	]
+	node [
+		id 3
+		label "alone"
+	]
+	node [
+		id 4
+		label "loop"
+	]
+	edge [
+		source 1
+		target 2
+	]
+	edge [
+		source 4
+		target 4
+	]
+]
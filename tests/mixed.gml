@@ -0,0 +1,34 @@
+graph [
+	directed 0
+	node [
+		id 1
+	]
+	node [
+		id 2
+	]
+	node [
+		id 3
+	]
+	node [
+		id 4
+	]
+	edge [
+		source 1
+		target 2
+		directed 1
+	]
+	edge [
+		source 2
+		target 3
+	]
+	edge [
+		source 4
+		target 3
+		directed 1
+	]
+	edge [
+		source 4
+		target 1
+		directed 0
+	]
+]
@@ -0,0 +1,15 @@
+network [
+	directed 0
+	node [
+		id 1
+		label "a"
+	]
+	node [
+		id 2
+		label "b"
+	]
+	edge [
+		source 1
+		target 2
+	]
+]
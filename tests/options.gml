@@ -0,0 +1,19 @@
+Creator "options test"
+graph [
+	directed 2
+	node [
+		id 1
+		zip 00501
+		graphics [
+			x 1.5
+		]
+	]
+	node [
+		id 1
+		label "again"
+	]
+	edge [
+		source 1
+		target 1
+	]
+]
@@ -0,0 +1,36 @@
+graph [
+	directed 1
+	node [
+		id 1
+	]
+	node [
+		id 2
+	]
+	node [
+		id 3
+	]
+	edge [
+		source 1
+		target 2
+		weight 2.5
+	]
+	edge [
+		source 1
+		target 2
+		weight 1
+	]
+	edge [
+		source 2
+		target 3
+	]
+	edge [
+		source 3
+		target 3
+		weight 4
+	]
+	edge [
+		source 3
+		target 1
+		weight "heavy"
+	]
+]
@@ -0,0 +1,28 @@
+graph [
+	directed 1
+	graphics [
+		fill "#ffffff"
+	]
+	nodes [
+		node [
+			id 1
+		]
+		node [
+			id 2
+		]
+	]
+	node [
+		id 3
+	]
+	edges [
+		count 2
+		edge [
+			source 1
+			target 2
+		]
+		edge [
+			source 2
+			target 3
+		]
+	]
+]